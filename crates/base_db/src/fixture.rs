@@ -940,6 +940,14 @@ bar() -> ?FOO.
                             eqwalizer_config: EqwalizerConfig {
                                 enable_all: false,
                             },
+                            diagnostics_config: ProjectDiagnosticsConfig {
+                                enabled: [],
+                                disabled: [],
+                                enable_experimental: false,
+                                severity: {},
+                                exclude_apps: [],
+                                exclude_paths: [],
+                            },
                         },
                         ProjectId(
                             1,
@@ -972,6 +980,14 @@ bar() -> ?FOO.
                             eqwalizer_config: EqwalizerConfig {
                                 enable_all: false,
                             },
+                            diagnostics_config: ProjectDiagnosticsConfig {
+                                enabled: [],
+                                disabled: [],
+                                enable_experimental: false,
+                                severity: {},
+                                exclude_apps: [],
+                                exclude_paths: [],
+                            },
                         },
                     },
                 },
@@ -1084,6 +1100,14 @@ foo() -> ?BAR.
                             eqwalizer_config: EqwalizerConfig {
                                 enable_all: false,
                             },
+                            diagnostics_config: ProjectDiagnosticsConfig {
+                                enabled: [],
+                                disabled: [],
+                                enable_experimental: false,
+                                severity: {},
+                                exclude_apps: [],
+                                exclude_paths: [],
+                            },
                         },
                         ProjectId(
                             1,
@@ -1106,6 +1130,14 @@ foo() -> ?BAR.
                             eqwalizer_config: EqwalizerConfig {
                                 enable_all: false,
                             },
+                            diagnostics_config: ProjectDiagnosticsConfig {
+                                enabled: [],
+                                disabled: [],
+                                enable_experimental: false,
+                                severity: {},
+                                exclude_apps: [],
+                                exclude_paths: [],
+                            },
                         },
                     },
                 },
@@ -13,6 +13,7 @@ use std::path::Path;
 use std::sync::Arc;
 
 use elp_project_model::buck::EqwalizerConfig;
+use elp_project_model::buck::ProjectDiagnosticsConfig;
 use elp_project_model::AppName;
 use elp_project_model::AppType;
 use elp_project_model::Project;
@@ -108,6 +109,7 @@ pub struct ProjectData {
     pub otp_project_id: Option<ProjectId>,
     pub app_roots: AppRoots,
     pub eqwalizer_config: EqwalizerConfig,
+    pub diagnostics_config: ProjectDiagnosticsConfig,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -340,6 +342,7 @@ impl<'a> ProjectApps<'a> {
                 otp_project_id: self.otp_project_id,
                 app_roots,
                 eqwalizer_config: project.eqwalizer_config(),
+                diagnostics_config: project.diagnostics_config(),
             };
             app_structure.add_project_data(project_id, project_data);
         }
@@ -11,11 +11,13 @@ use std::cmp::Ordering;
 use std::env;
 use std::fs;
 use std::path::PathBuf;
+use std::str::FromStr;
 
 use bpaf::construct;
 use bpaf::long;
 use bpaf::Bpaf;
 use bpaf::Parser;
+use elp_ide::diagnostics::Severity;
 use itertools::Itertools;
 use serde::Deserialize;
 
@@ -236,11 +238,30 @@ pub struct Lint {
     /// Filter out all reported diagnostics after this line. Valid only for single file
     #[bpaf(argument("LINE_TO"))]
     pub line_to: Option<u32>,
+    /// Report diagnostics with the given code using another severity, one of error, warning, info or hint
+    #[bpaf(argument::<String>("CODE=SEVERITY"), many)]
+    pub severity: Vec<String>,
+    /// Fail if any diagnostic is reported with this severity or above (default is error)
+    #[bpaf(
+        argument("SEVERITY"),
+        fallback("error".to_string()),
+        guard(severity_guard, "Please use one of error, warning, info or hint")
+    )]
+    pub fail_on: String,
     /// Rest of args are space separated list of apps to ignore
     #[bpaf(positional("IGNORED_APPS"))]
     pub ignore_apps: Vec<String>,
 }
 
+#[derive(Clone, Debug, Bpaf)]
+pub struct Format {
+    /// Do not modify the files, list the ones that are not formatted and fail if there are any
+    pub check: bool,
+    /// Files or directories to format (defaults to `.`)
+    #[bpaf(positional("FILES"))]
+    pub files: Vec<PathBuf>,
+}
+
 #[derive(Clone, Debug, Bpaf)]
 pub struct Shell {
     /// Path to directory with project (defaults to `.`)
@@ -262,6 +283,8 @@ pub enum Command {
     GenerateCompletions(GenerateCompletions),
     RunServer(RunServer),
     Lint(Lint),
+    Format(Format),
+    UnusedExports(Lint),
     Version(Version),
     Shell(Shell),
     Help(),
@@ -343,6 +366,18 @@ pub fn command() -> impl Parser<Command> {
         .command("lint")
         .help("Parse files in project and emit diagnostics, optionally apply fixes.");
 
+    let format = format()
+        .map(Command::Format)
+        .to_options()
+        .command("format")
+        .help("Format Erlang files");
+
+    let unused_exports = lint()
+        .map(Command::UnusedExports)
+        .to_options()
+        .command("unused-exports")
+        .help("Report exported functions that are never used in the project");
+
     let run_server = run_server()
         .map(Command::RunServer)
         .to_options()
@@ -376,6 +411,8 @@ pub fn command() -> impl Parser<Command> {
         version,
         shell,
         eqwalize_stats,
+        format,
+        unused_exports,
     ])
     .fallback(Help())
 }
@@ -441,6 +478,10 @@ fn format_guard(format: &Option<String>) -> bool {
     }
 }
 
+fn severity_guard(severity: &String) -> bool {
+    Severity::from_str(severity).is_ok()
+}
+
 fn shell_completer(shell: &String) -> Vec<(String, Option<String>)> {
     let completions = match shell.to_lowercase().chars().next() {
         Some('b') => vec!["bash"],
@@ -246,12 +246,14 @@ fn do_parse_one(
     let url = file_id_to_url(vfs, file_id);
     let mut diagnostics = db.diagnostics(config, file_id, include_generated)?;
     let erlang_service_diagnostics = db.erlang_service_diagnostics(file_id)?;
+    let is_module = db.module_name(file_id)?.is_some();
     diagnostics.extend(
         erlang_service_diagnostics
             .into_iter()
             // Should we return the included file diagnostics as well? Not doing so now.
             .filter_map(|(f, diags)| if f == file_id { Some(diags) } else { None })
-            .flatten(),
+            .flatten()
+            .filter(|d| !(is_module && config.is_computed_natively(d))),
     );
     let line_index = db.line_index(file_id)?;
 
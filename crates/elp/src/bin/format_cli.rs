@@ -0,0 +1,109 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::bail;
+use anyhow::Result;
+use elp::cli::Cli;
+use elp_ide::format_text;
+
+use crate::args::Format;
+
+pub(crate) fn format(args: &Format, cli: &mut dyn Cli) -> Result<()> {
+    let mut paths = Vec::new();
+    if args.files.is_empty() {
+        collect_files(Path::new("."), &mut paths)?;
+    } else {
+        for path in &args.files {
+            collect_files(path, &mut paths)?;
+        }
+    }
+
+    let mut unformatted = 0;
+    let mut failed = 0;
+    for path in paths {
+        let original = fs::read_to_string(&path)?;
+        let crlf = original.contains("\r\n");
+        let text = if crlf {
+            original.replace("\r\n", "\n")
+        } else {
+            original
+        };
+        let formatted = match format_text(&text) {
+            Some(formatted) => formatted,
+            None => {
+                writeln!(
+                    cli.err(),
+                    "{}: syntax errors, not formatted",
+                    path.display()
+                )?;
+                failed += 1;
+                continue;
+            }
+        };
+        if formatted == text {
+            continue;
+        }
+        if args.check {
+            writeln!(cli, "{}", path.display())?;
+            unformatted += 1;
+        } else {
+            let formatted = if crlf {
+                formatted.replace('\n', "\r\n")
+            } else {
+                formatted
+            };
+            fs::write(&path, formatted)?;
+        }
+    }
+
+    if unformatted > 0 {
+        bail!("{} file(s) are not formatted", unformatted);
+    }
+    if failed > 0 {
+        bail!("{} file(s) could not be parsed", failed);
+    }
+    Ok(())
+}
+
+/// Collects the Erlang source files at the given path, recursing into
+/// directories but skipping build output and hidden directories.
+fn collect_files(path: &Path, acc: &mut Vec<PathBuf>) -> Result<()> {
+    if path.is_dir() {
+        let mut entries = fs::read_dir(path)?
+            .map(|entry| entry.map(|it| it.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        entries.sort();
+        for entry in entries {
+            let skip = entry
+                .file_name()
+                .and_then(|name| name.to_str())
+                .map_or(false, |name| name.starts_with('.') || name == "_build");
+            if !skip && (entry.is_dir() || is_erlang_file(&entry)) {
+                collect_files(&entry, acc)?;
+            }
+        }
+    } else if path.is_file() {
+        acc.push(path.to_path_buf());
+    } else {
+        bail!("No such file or directory: {}", path.display());
+    }
+    Ok(())
+}
+
+fn is_erlang_file(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|it| it.to_str()),
+        Some("erl") | Some("hrl") | Some("escript")
+    )
+}
@@ -13,8 +13,10 @@ use std::io::Write;
 use std::path::Path;
 use std::path::PathBuf;
 use std::str;
+use std::str::FromStr;
 use std::sync::Arc;
 
+use anyhow::anyhow;
 use anyhow::bail;
 use anyhow::Result;
 use elp::build::load;
@@ -24,7 +26,9 @@ use elp::convert;
 use elp::document::Document;
 use elp::otp_file_to_ignore;
 use elp_ide::diagnostics;
+use elp_ide::diagnostics::DiagnosticCode;
 use elp_ide::diagnostics::DiagnosticsConfig;
+use elp_ide::diagnostics::Severity;
 use elp_ide::diff::diff_from_textedit;
 use elp_ide::diff::DiffRange;
 use elp_ide::elp_ide_assists::Assist;
@@ -63,6 +67,16 @@ pub fn lint_all(args: &Lint, cli: &mut dyn Cli) -> Result<()> {
     do_codemod(cli, &mut loaded, args)
 }
 
+/// Reports the exported functions that are never used in the project, by
+/// linting it for the `unused_exported_function` diagnostic only.
+pub fn unused_exports(args: &Lint, cli: &mut dyn Cli) -> Result<()> {
+    let args = Lint {
+        diagnostic_filter: Some(DiagnosticCode::UnusedExportedFunction.as_code()),
+        ..args.clone()
+    };
+    lint_all(&args, cli)
+}
+
 /// Changed lines, from and to
 type ChangeRange = (u32, u32);
 
@@ -72,6 +86,7 @@ fn do_parse_all(
     project_id: &ProjectId,
     config: &DiagnosticsConfig,
     include_generated: bool,
+    erlang_service: bool,
     ignore_apps: &[String],
 ) -> Result<
     Vec<(
@@ -106,6 +121,7 @@ fn do_parse_all(
                         file_id,
                         module_name.as_str(),
                         include_generated,
+                        erlang_service,
                         Vec::default(),
                     )
                     .unwrap()
@@ -124,6 +140,7 @@ fn do_parse_one(
     file_id: FileId,
     name: &str,
     include_generated: bool,
+    erlang_service: bool,
     changes: Vec<ChangeRange>,
 ) -> Result<
     Option<(
@@ -133,7 +150,10 @@ fn do_parse_one(
         Vec<ChangeRange>,
     )>,
 > {
-    let diagnostics = db.diagnostics(config, file_id, include_generated)?;
+    let mut diagnostics = db.diagnostics(config, file_id, include_generated)?;
+    if erlang_service {
+        diagnostics.extend(erlang_service_diagnostics(db, config, file_id)?);
+    }
     if !diagnostics.is_empty() {
         let res = (name.to_string(), file_id, diagnostics, changes);
         Ok(Some(res))
@@ -142,6 +162,40 @@ fn do_parse_one(
     }
 }
 
+/// The diagnostics reported for the file itself by the Erlang service
+/// and EDoc, with the configuration of the file applied as for the
+/// native ones. Those also computed natively are left out.
+fn erlang_service_diagnostics(
+    db: &Analysis,
+    config: &DiagnosticsConfig,
+    file_id: FileId,
+) -> Result<Vec<diagnostics::Diagnostic>> {
+    let config = match db.diagnostics_config(config, file_id)? {
+        Some(config) => config,
+        None => return Ok(vec![]),
+    };
+    let is_module = db.module_name(file_id)?.is_some();
+    let mut res = Vec::new();
+    for diags in [
+        db.erlang_service_diagnostics(file_id)?,
+        db.edoc_diagnostics(file_id)?,
+    ] {
+        for (_, ds) in diags.into_iter().filter(|(id, _)| *id == file_id) {
+            res.extend(
+                ds.into_iter()
+                    .filter(|d| {
+                        config.is_enabled(&d.code) && !(is_module && config.is_computed_natively(d))
+                    })
+                    .map(|mut d| {
+                        d.severity = config.severity(&d);
+                        d
+                    }),
+            );
+        }
+    }
+    Ok(res)
+}
+
 // ---------------------------------------------------------------------
 
 pub fn do_codemod(cli: &mut dyn Cli, loaded: &mut LoadResult, args: &Lint) -> Result<()> {
@@ -164,11 +218,26 @@ pub fn do_codemod(cli: &mut dyn Cli, loaded: &mut LoadResult, args: &Lint) -> Re
             diagnostic_filter: Some(diagnostic_filter),
             line_from,
             line_to,
+            severity,
+            fail_on,
             ignore_apps,
             format: _,
         } => {
             let mut cfg = DiagnosticsConfig::default();
             cfg.disable_experimental = args.experimental_diags;
+            let cfg = apply_severity_overrides(cfg, severity)?;
+            // Diagnostics that are off by default are computed when asked for
+            let cfg = match DiagnosticCode::maybe_from_string(diagnostic_filter) {
+                Some(code) => cfg.enable(code),
+                None => cfg,
+            };
+            let fail_on = Severity::from_str(fail_on).map_err(|err| anyhow!(err))?;
+            // Diagnostics from the Erlang service and EDoc are only
+            // computed when asked for, as they are slower to get
+            let erlang_service = matches!(
+                DiagnosticCode::maybe_from_string(diagnostic_filter),
+                Some(DiagnosticCode::ErlangService(_))
+            );
             // Declare outside the block so it has the right lifetime for filter_diagnostics
             let res;
             let mut diags = {
@@ -216,6 +285,7 @@ pub fn do_codemod(cli: &mut dyn Cli, loaded: &mut LoadResult, args: &Lint) -> Re
                         &loaded.project_id,
                         &cfg,
                         args.include_generated,
+                        erlang_service,
                         ignore_apps,
                     )?,
                     (Some(file_id), Some(name)) => do_parse_one(
@@ -224,6 +294,7 @@ pub fn do_codemod(cli: &mut dyn Cli, loaded: &mut LoadResult, args: &Lint) -> Re
                         file_id,
                         &name,
                         args.include_generated,
+                        erlang_service,
                         vec![],
                     )?
                     .map_or(vec![], |x| vec![x]),
@@ -247,17 +318,13 @@ pub fn do_codemod(cli: &mut dyn Cli, loaded: &mut LoadResult, args: &Lint) -> Re
                 }
             } else {
                 diags.sort_by(|(a, _, _), (b, _, _)| a.cmp(b));
-                let mut err_in_diag = false;
+                let err_in_diag = diags
+                    .iter()
+                    .any(|(_, _, diags)| diags.iter().any(|d| d.severity.is_at_least(fail_on)));
                 if args.is_format_json() {
                     for (_name, file_id, diags) in &diags {
                         if args.print_diags {
                             for diag in diags {
-                                match diag.severity {
-                                    diagnostics::Severity::Error => {
-                                        err_in_diag = true;
-                                    }
-                                    _ => {}
-                                };
                                 let vfs_path = loaded.vfs.file_path(*file_id);
                                 let analysis = loaded.analysis();
                                 let root_path = &analysis
@@ -284,12 +351,6 @@ pub fn do_codemod(cli: &mut dyn Cli, loaded: &mut LoadResult, args: &Lint) -> Re
                         writeln!(cli, "  {}: {}", name, diags.len())?;
                         if args.print_diags {
                             for diag in diags {
-                                match diag.severity {
-                                    diagnostics::Severity::Error => {
-                                        err_in_diag = true;
-                                    }
-                                    _ => {}
-                                };
                                 print_diagnostic(diag, &loaded.analysis(), *file_id, cli)?;
                             }
                         }
@@ -325,6 +386,28 @@ pub fn do_codemod(cli: &mut dyn Cli, loaded: &mut LoadResult, args: &Lint) -> Re
     }
 }
 
+/// Apply the `--severity CODE=SEVERITY` overrides given on the command line.
+fn apply_severity_overrides<'a>(
+    cfg: DiagnosticsConfig<'a>,
+    overrides: &[String],
+) -> Result<DiagnosticsConfig<'a>> {
+    overrides.iter().try_fold(cfg, |cfg, s| {
+        let parsed = s.split_once('=').and_then(|(code, severity)| {
+            Some((
+                DiagnosticCode::maybe_from_string(&code.to_string())?,
+                Severity::from_str(severity).ok()?,
+            ))
+        });
+        match parsed {
+            Some((code, severity)) => Ok(cfg.override_severity(code, severity)),
+            None => bail!(
+                "Invalid severity override '{}', expecting CODE=SEVERITY with SEVERITY one of error, warning, info or hint",
+                s
+            ),
+        }
+    })
+}
+
 fn print_diagnostic(
     diag: &diagnostics::Diagnostic,
     analysis: &Analysis,
@@ -513,6 +596,8 @@ impl<'a> Lints<'a> {
                             file_id,
                             &name,
                             self.include_generated,
+                            // Only native diagnostics come with fixes
+                            false,
                             changes,
                         )
                     },
@@ -28,6 +28,7 @@ mod build_info_cli;
 mod elp_parse_cli;
 mod eqwalizer_cli;
 mod erlang_service_cli;
+mod format_cli;
 mod lint_cli;
 mod reporting;
 mod shell;
@@ -76,6 +77,8 @@ fn try_main(cli: &mut dyn Cli, args: Args) -> Result<()> {
         }
         args::Command::BuildInfo(args) => build_info_cli::save_build_info(args)?,
         args::Command::Lint(args) => lint_cli::lint_all(&args, cli)?,
+        args::Command::Format(args) => format_cli::format(&args, cli)?,
+        args::Command::UnusedExports(args) => lint_cli::unused_exports(&args, cli)?,
         args::Command::GenerateCompletions(args) => {
             let instructions = args::gen_completions(&args.shell);
             writeln!(cli, "#Please run this:\n{}", instructions)?
@@ -454,6 +457,83 @@ mod tests {
         );
     }
 
+    #[test_case(false ; "rebar")]
+    #[test_case(true  ; "buck")]
+    fn unused_exports(buck: bool) {
+        simple_snapshot(
+            args_vec!["unused-exports", "--module", "app_a_unused_param"],
+            "linter",
+            expect_file!("../resources/test/linter/unused_exports.stdout"),
+            buck,
+            None,
+        );
+    }
+
+    #[test_case(false ; "rebar")]
+    #[test_case(true  ; "buck")]
+    fn lint_severity_override(buck: bool) {
+        simple_snapshot(
+            args_vec![
+                "lint",
+                "--module",
+                "lints",
+                "--diagnostic-filter",
+                "P1700",
+                "--severity",
+                "P1700=warning",
+            ],
+            "diagnostics",
+            expect_file!("../resources/test/diagnostics/parse_elp_lint_severity.stdout"),
+            buck,
+            None,
+        );
+    }
+
+    #[test_case(false ; "rebar")]
+    #[test_case(true  ; "buck")]
+    fn lint_fail_on_warning(buck: bool) {
+        simple_snapshot_expect_error(
+            args_vec![
+                "lint",
+                "--module",
+                "lints",
+                "--diagnostic-filter",
+                "P1700",
+                "--severity",
+                "P1700=warning",
+                "--fail-on",
+                "warning",
+            ],
+            "diagnostics",
+            expect_file!("../resources/test/diagnostics/parse_elp_lint_severity.stdout"),
+            buck,
+            None,
+        );
+    }
+
+    #[test_case(false ; "rebar")]
+    #[test_case(true  ; "buck")]
+    fn lint_fail_on_warning_no_diags(buck: bool) {
+        simple_snapshot_expect_error(
+            args_vec![
+                "lint",
+                "--module",
+                "lints",
+                "--diagnostic-filter",
+                "P1700",
+                "--severity",
+                "P1700=warning",
+                "--fail-on",
+                "warning",
+                "--no-diags",
+            ],
+            "diagnostics",
+            expect_file!("../resources/test/diagnostics/parse_elp_lint_no_diags.stdout"),
+            buck,
+            None,
+        );
+    }
+
     #[test_case(false ; "rebar")]
     #[test_case(true  ; "buck")]
     fn lint_recursive(buck: bool) {
@@ -672,6 +752,29 @@ mod tests {
         .expect("Bad test");
     }
 
+    #[test]
+    fn format_check_and_write() {
+        let tmp_dir = TempDir::new().expect("Could not create temporary directory");
+        let file = tmp_dir.path().join("main.erl");
+        fs::write(&file, "-module(main).\nfoo( X )->\n  X.\n").unwrap();
+
+        let (stdout, _stderr, code) = elp(args_vec!["format", "--check", &file]);
+        assert_eq!(code, 101);
+        assert_eq!(stdout, format!("{}\n", file.display()));
+
+        let (_stdout, stderr, code) = elp(args_vec!["format", &file]);
+        assert_eq!(code, 0);
+        assert!(stderr.is_empty());
+        assert_eq!(
+            fs::read_to_string(&file).unwrap(),
+            "-module(main).\nfoo(X) ->\n    X.\n"
+        );
+
+        let (stdout, _stderr, code) = elp(args_vec!["format", "--check", &file]);
+        assert_eq!(code, 0);
+        assert!(stdout.is_empty());
+    }
+
     #[test]
     fn help() {
         let args = args::args().run_inner(Args::from(&["--help"])).unwrap_err();
@@ -8,13 +8,16 @@
  */
 
 use std::iter;
+use std::str::FromStr;
 
 use elp_ide::diagnostics::DiagnosticCode;
 use elp_ide::diagnostics::DiagnosticsConfig;
+use elp_ide::diagnostics::Severity;
 use elp_ide::elp_ide_assists::AssistConfig;
 use elp_ide::elp_ide_db::elp_base_db::AbsPathBuf;
 use elp_ide::elp_ide_db::helpers::SnippetCap;
 use elp_ide::InlayHintsConfig;
+use fxhash::FxHashMap;
 use fxhash::FxHashSet;
 use lsp_types::ClientCapabilities;
 use serde::de::DeserializeOwned;
@@ -37,9 +40,21 @@ config_data! {
       diagnostics_enableExperimental: bool = json! { false },
       /// List of ELP diagnostics to disable.
       diagnostics_disabled: FxHashSet<String> = json! { [] },
+      /// List of ELP diagnostics to enable, for those which are off by
+      /// default, such as `unused_exported_function`.
+      diagnostics_enabled: FxHashSet<String> = json! { [] },
+      /// Whether to compute diagnostics in the background for every
+      /// module of the project, not just for the open files.
+      diagnostics_projectProblems_enable: bool = json! { false },
+      /// Severity overrides for diagnostics, from diagnostic code or
+      /// label to one of `error`, `warning`, `info` or `hint`.
+      diagnostics_severity: FxHashMap<String, String> = json! { {} },
       /// Whether to show function parameter name inlay hints at the call
       /// site.
       inlayHints_parameterHints_enable: bool = json! { false },
+      /// Whether to show the types inferred by eqWAlizer for the variables
+      /// bound by a match.
+      inlayHints_typeHints_enable: bool = json! { false },
       /// Whether to show Code Lenses in Erlang files.
       lens_enable: bool = json! { false },
       /// Whether to show the `Run` lenses. Only applies when
@@ -48,6 +63,12 @@ config_data! {
       /// Whether to show the `Debug` lenses. Only applies when
       /// `#elp.lens.enable#` is set.
       lens_debug_enable: bool = json! { false },
+      /// Whether to show the `References` lenses, above functions and
+      /// exported types. Only applies when `#elp.lens.enable#` is set.
+      lens_references_enable: bool = json! { false },
+      /// Whether to show the `Implementations` lenses, above callbacks.
+      /// Only applies when `#elp.lens.enable#` is set.
+      lens_implementations_enable: bool = json! { false },
       /// Configure LSP-based logging using env_logger syntax.
       log: String = json! { "error" },
       /// Whether to show Signature Help.
@@ -65,6 +86,9 @@ impl Default for ConfigData {
 pub struct Config {
     pub root_path: AbsPathBuf,
     pub caps: ClientCapabilities,
+    /// The raw client capabilities, for those from LSP 3.17 that
+    /// `ClientCapabilities` does not model yet.
+    pub unmodelled_caps: serde_json::Value,
     data: ConfigData,
 }
 
@@ -72,6 +96,8 @@ pub struct Config {
 pub struct LensConfig {
     pub run: bool,
     pub debug: bool,
+    pub references: bool,
+    pub implementations: bool,
 }
 
 macro_rules! try_ {
@@ -90,6 +116,7 @@ impl Config {
         Config {
             root_path,
             caps,
+            unmodelled_caps: serde_json::Value::Null,
             data: ConfigData::default(),
         }
     }
@@ -175,8 +202,8 @@ impl Config {
     }
 
     pub fn diagnostics(&self) -> DiagnosticsConfig {
-        // Look up disabled diagnostics using both label and code.
-        DiagnosticsConfig::new(
+        // Look up disabled and enabled diagnostics using both label and code.
+        let config = DiagnosticsConfig::new(
             !self.data.diagnostics_enableExperimental,
             self.data
                 .diagnostics_disabled
@@ -184,7 +211,40 @@ impl Config {
                 .filter_map(DiagnosticCode::maybe_from_string)
                 .collect(),
             vec![],
-        )
+        );
+        let config = self
+            .data
+            .diagnostics_enabled
+            .iter()
+            .filter_map(DiagnosticCode::maybe_from_string)
+            .fold(config, |config, code| config.enable(code));
+        self.data
+            .diagnostics_severity
+            .keys()
+            .filter_map(|code| Some((code, self.severity_override(code)?)))
+            .fold(
+                config,
+                |config, (code, severity)| match DiagnosticCode::maybe_from_string(code) {
+                    Some(code) => config.override_severity(code, severity),
+                    None => config.override_other_severity(code.clone(), severity),
+                },
+            )
+    }
+
+    /// The severity configured for diagnostics with the given code.
+    fn severity_override(&self, code: &str) -> Option<Severity> {
+        let severity = self.data.diagnostics_severity.get(code)?;
+        match Severity::from_str(severity) {
+            Ok(severity) => Some(severity),
+            Err(err) => {
+                log::warn!("Ignoring severity override for {}: {}", code, err);
+                None
+            }
+        }
+    }
+
+    pub fn project_problems(&self) -> bool {
+        self.data.diagnostics_projectProblems_enable
     }
 
     pub fn code_action_group(&self) -> bool {
@@ -202,6 +262,8 @@ impl Config {
         LensConfig {
             run: self.data.lens_enable && self.data.lens_run_enable,
             debug: self.data.lens_enable && self.data.lens_debug_enable,
+            references: self.data.lens_enable && self.data.lens_references_enable,
+            implementations: self.data.lens_enable && self.data.lens_implementations_enable,
         }
     }
 
@@ -216,6 +278,23 @@ impl Config {
         }
     }
 
+    /// Whether the client pulls diagnostics, in which case they must not
+    /// be pushed to it.
+    pub fn pull_diagnostics(&self) -> bool {
+        self.unmodelled_caps
+            .pointer("/textDocument/diagnostic")
+            .map_or(false, |caps| caps.is_object())
+    }
+
+    pub fn diagnostics_refresh(&self) -> bool {
+        try_or!(
+            self.unmodelled_caps
+                .pointer("/workspace/diagnostics/refreshSupport")?
+                .as_bool()?,
+            false
+        )
+    }
+
     pub fn work_done_progress(&self) -> bool {
         try_or!(self.caps.window.as_ref()?.work_done_progress?, false)
     }
@@ -227,6 +306,7 @@ impl Config {
     pub fn inlay_hints(&self) -> InlayHintsConfig {
         InlayHintsConfig {
             parameter_hints: self.data.inlayHints_parameterHints_enable,
+            type_hints: self.data.inlayHints_typeHints_enable,
         }
     }
 
@@ -420,7 +500,7 @@ mod tests {
 
         let s = remove_ws(&schema);
 
-        expect![[r#""elp.ai.enable":{"default":false,"markdownDescription":"EnablesupportforAI-basedcompletions.","type":"boolean"},"elp.diagnostics.disabled":{"default":[],"items":{"type":"string"},"markdownDescription":"ListofELPdiagnosticstodisable.","type":"array","uniqueItems":true},"elp.diagnostics.enableExperimental":{"default":false,"markdownDescription":"WhethertoshowexperimentalELPdiagnosticsthatmight\nhavemorefalsepositivesthanusual.","type":"boolean"},"elp.inlayHints.parameterHints.enable":{"default":false,"markdownDescription":"Whethertoshowfunctionparameternameinlayhintsatthecall\nsite.","type":"boolean"},"elp.lens.debug.enable":{"default":false,"markdownDescription":"Whethertoshowthe`Debug`lenses.Onlyapplieswhen\n`#elp.lens.enable#`isset.","type":"boolean"},"elp.lens.enable":{"default":false,"markdownDescription":"WhethertoshowCodeLensesinErlangfiles.","type":"boolean"},"elp.lens.run.enable":{"default":false,"markdownDescription":"Whethertoshowthe`Run`lenses.Onlyapplieswhen\n`#elp.lens.enable#`isset.","type":"boolean"},"elp.log":{"default":"error","markdownDescription":"ConfigureLSP-basedloggingusingenv_loggersyntax.","type":"string"},"elp.signatureHelp.enable":{"default":false,"markdownDescription":"WhethertoshowSignatureHelp.","type":"boolean"},"#]]
+        expect![[r#""elp.ai.enable":{"default":false,"markdownDescription":"EnablesupportforAI-basedcompletions.","type":"boolean"},"elp.diagnostics.disabled":{"default":[],"items":{"type":"string"},"markdownDescription":"ListofELPdiagnosticstodisable.","type":"array","uniqueItems":true},"elp.diagnostics.enableExperimental":{"default":false,"markdownDescription":"WhethertoshowexperimentalELPdiagnosticsthatmight\nhavemorefalsepositivesthanusual.","type":"boolean"},"elp.diagnostics.enabled":{"default":[],"items":{"type":"string"},"markdownDescription":"ListofELPdiagnosticstoenable,forthosewhichareoffby\ndefault,suchas`unused_exported_function`.","type":"array","uniqueItems":true},"elp.diagnostics.projectProblems.enable":{"default":false,"markdownDescription":"Whethertocomputediagnosticsinthebackgroundforevery\nmoduleoftheproject,notjustfortheopenfiles.","type":"boolean"},"elp.diagnostics.severity":{"default":{},"markdownDescription":"Severityoverridesfordiagnostics,fromdiagnosticcodeor\nlabeltooneof`error`,`warning`,`info`or`hint`.","type":"object"},"elp.inlayHints.parameterHints.enable":{"default":false,"markdownDescription":"Whethertoshowfunctionparameternameinlayhintsatthecall\nsite.","type":"boolean"},"elp.inlayHints.typeHints.enable":{"default":false,"markdownDescription":"WhethertoshowthetypesinferredbyeqWAlizerforthevariables\nboundbyamatch.","type":"boolean"},"elp.lens.debug.enable":{"default":false,"markdownDescription":"Whethertoshowthe`Debug`lenses.Onlyapplieswhen\n`#elp.lens.enable#`isset.","type":"boolean"},"elp.lens.enable":{"default":false,"markdownDescription":"WhethertoshowCodeLensesinErlangfiles.","type":"boolean"},"elp.lens.implementations.enable":{"default":false,"markdownDescription":"Whethertoshowthe`Implementations`lenses,abovecallbacks.\nOnlyapplieswhen`#elp.lens.enable#`isset.","type":"boolean"},"elp.lens.references.enable":{"default":false,"markdownDescription":"Whethertoshowthe`References`lenses,abovefunctionsand\nexportedtypes.Onlyapplieswhen`#elp.lens.enable#`isset.","type":"boolean"},"elp.lens.run.enable":{"default":false,"markdownDescription":"Whethertoshowthe`Run`lenses.Onlyapplieswhen\n`#elp.lens.enable#`isset.","type":"boolean"},"elp.log":{"default":"error","markdownDescription":"ConfigureLSP-basedloggingusingenv_loggersyntax.","type":"string"},"elp.signatureHelp.enable":{"default":false,"markdownDescription":"WhethertoshowSignatureHelp.","type":"boolean"},"#]]
         .assert_eq(s.as_str());
 
         expect![[r#"
@@ -443,11 +523,35 @@ mod tests {
               "markdownDescription": "Whether to show experimental ELP diagnostics that might\nhave more false positives than usual.",
               "type": "boolean"
             },
+            "elp.diagnostics.enabled": {
+              "default": [],
+              "items": {
+                "type": "string"
+              },
+              "markdownDescription": "List of ELP diagnostics to enable, for those which are off by\ndefault, such as `unused_exported_function`.",
+              "type": "array",
+              "uniqueItems": true
+            },
+            "elp.diagnostics.projectProblems.enable": {
+              "default": false,
+              "markdownDescription": "Whether to compute diagnostics in the background for every\nmodule of the project, not just for the open files.",
+              "type": "boolean"
+            },
+            "elp.diagnostics.severity": {
+              "default": {},
+              "markdownDescription": "Severity overrides for diagnostics, from diagnostic code or\nlabel to one of `error`, `warning`, `info` or `hint`.",
+              "type": "object"
+            },
             "elp.inlayHints.parameterHints.enable": {
               "default": false,
               "markdownDescription": "Whether to show function parameter name inlay hints at the call\nsite.",
               "type": "boolean"
             },
+            "elp.inlayHints.typeHints.enable": {
+              "default": false,
+              "markdownDescription": "Whether to show the types inferred by eqWAlizer for the variables\nbound by a match.",
+              "type": "boolean"
+            },
             "elp.lens.debug.enable": {
               "default": false,
               "markdownDescription": "Whether to show the `Debug` lenses. Only applies when\n`#elp.lens.enable#` is set.",
@@ -458,6 +562,16 @@ mod tests {
               "markdownDescription": "Whether to show Code Lenses in Erlang files.",
               "type": "boolean"
             },
+            "elp.lens.implementations.enable": {
+              "default": false,
+              "markdownDescription": "Whether to show the `Implementations` lenses, above callbacks.\nOnly applies when `#elp.lens.enable#` is set.",
+              "type": "boolean"
+            },
+            "elp.lens.references.enable": {
+              "default": false,
+              "markdownDescription": "Whether to show the `References` lenses, above functions and\nexported types. Only applies when `#elp.lens.enable#` is set.",
+              "type": "boolean"
+            },
             "elp.lens.run.enable": {
               "default": false,
               "markdownDescription": "Whether to show the `Run` lenses. Only applies when\n`#elp.lens.enable#` is set.",
@@ -58,6 +58,7 @@ pub fn diagnostic_severity(severity: Severity) -> lsp_types::DiagnosticSeverity
     match severity {
         Severity::Error => lsp_types::DiagnosticSeverity::ERROR,
         Severity::Warning => lsp_types::DiagnosticSeverity::WARNING,
+        Severity::Information => lsp_types::DiagnosticSeverity::INFORMATION,
         Severity::WeakWarning => lsp_types::DiagnosticSeverity::HINT,
     }
 }
@@ -249,6 +250,7 @@ fn ide_to_arc_severity(severity: Severity) -> arc_types::Severity {
     match severity {
         Severity::Error => arc_types::Severity::Error,
         Severity::Warning => arc_types::Severity::Warning,
+        Severity::Information => arc_types::Severity::Advice,
         Severity::WeakWarning => arc_types::Severity::Advice,
     }
 }
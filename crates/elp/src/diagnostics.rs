@@ -17,6 +17,10 @@ use fxhash::FxHashMap;
 use fxhash::FxHashSet;
 use lsp_types::Diagnostic;
 
+use crate::lsp_ext::DocumentDiagnosticReport;
+use crate::lsp_ext::FullDocumentDiagnosticReport;
+use crate::lsp_ext::UnchangedDocumentDiagnosticReport;
+
 #[derive(Debug, Default, Clone)]
 pub(crate) struct DiagnosticCollection {
     pub(crate) native: FxHashMap<FileId, Vec<Diagnostic>>,
@@ -24,37 +28,69 @@ pub(crate) struct DiagnosticCollection {
     pub(crate) eqwalizer: FxHashMap<FileId, Vec<Diagnostic>>,
     pub(crate) edoc: FxHashMap<FileId, Vec<Diagnostic>>,
     changes: FxHashSet<FileId>,
+    /// Bumped on every change, to identify the diagnostics of a file
+    revision: u64,
+    revisions: FxHashMap<FileId, u64>,
 }
 
 impl DiagnosticCollection {
     pub fn set_native(&mut self, file_id: FileId, diagnostics: Vec<Diagnostic>) {
         if !are_all_diagnostics_equal(&self.native, file_id, &diagnostics) {
             set_diagnostics(&mut self.native, file_id, diagnostics);
-            self.changes.insert(file_id);
+            self.changed(file_id);
         }
     }
 
     pub fn set_eqwalizer(&mut self, file_id: FileId, diagnostics: Vec<Diagnostic>) {
         if !are_all_diagnostics_equal(&self.eqwalizer, file_id, &diagnostics) {
             set_diagnostics(&mut self.eqwalizer, file_id, diagnostics);
-            self.changes.insert(file_id);
+            self.changed(file_id);
         }
     }
 
     pub fn set_edoc(&mut self, file_id: FileId, diagnostics: Vec<Diagnostic>) {
         if !are_all_diagnostics_equal(&self.edoc, file_id, &diagnostics) {
             set_diagnostics(&mut self.edoc, file_id, diagnostics);
-            self.changes.insert(file_id);
+            self.changed(file_id);
         }
     }
 
     pub fn set_erlang_service(&mut self, file_id: FileId, diagnostics: Vec<Diagnostic>) {
         if !are_all_diagnostics_equal(&self.erlang_service, file_id, &diagnostics) {
             set_diagnostics(&mut self.erlang_service, file_id, diagnostics);
-            self.changes.insert(file_id);
+            self.changed(file_id);
         }
     }
 
+    pub fn clear(&mut self, file_id: FileId) {
+        self.set_native(file_id, vec![]);
+        self.set_erlang_service(file_id, vec![]);
+        self.set_eqwalizer(file_id, vec![]);
+        self.set_edoc(file_id, vec![]);
+    }
+
+    fn changed(&mut self, file_id: FileId) {
+        self.changes.insert(file_id);
+        self.revision += 1;
+        self.revisions.insert(file_id, self.revision);
+    }
+
+    /// The revision of the diagnostics of the file, which changes
+    /// whenever they do.
+    pub fn revision(&self, file_id: FileId) -> u64 {
+        self.revisions.get(&file_id).copied().unwrap_or_default()
+    }
+
+    /// The files with diagnostics.
+    pub fn file_ids(&self) -> impl Iterator<Item = FileId> + '_ {
+        self.native
+            .keys()
+            .chain(self.erlang_service.keys())
+            .chain(self.eqwalizer.keys())
+            .chain(self.edoc.keys())
+            .copied()
+    }
+
     pub fn diagnostics_for(&self, file_id: FileId) -> impl Iterator<Item = &Diagnostic> {
         let native = self.native.get(&file_id).into_iter().flatten();
         let erlang_service = self.erlang_service.get(&file_id).into_iter().flatten();
@@ -71,6 +107,50 @@ impl DiagnosticCollection {
     }
 }
 
+/// Identifies the state the diagnostics of the server are computed from,
+/// to build the result ids of the pull diagnostics reports.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct DiagnosticsGeneration {
+    /// Identifies the server instance, as clients may keep result ids
+    /// across restarts
+    pub(crate) session: u64,
+    /// Bumped whenever the configuration changes
+    pub(crate) config: u64,
+    /// Bumped whenever the files change
+    pub(crate) source: u64,
+}
+
+impl DiagnosticsGeneration {
+    /// The result id of the diagnostics of a file with the given
+    /// revision in the `DiagnosticCollection`. The source revision is
+    /// only relevant to open documents, whose native diagnostics are
+    /// computed on demand.
+    pub(crate) fn result_id(&self, opened: bool, collection_revision: u64) -> String {
+        let source = if opened { self.source } else { 0 };
+        format!(
+            "{:x}.{}.{}.{}",
+            self.session, self.config, source, collection_revision
+        )
+    }
+}
+
+/// Builds a pull diagnostics report, which is `unchanged` if the result
+/// id is the one previously sent to the client.
+pub(crate) fn document_report(
+    result_id: String,
+    diagnostics: Vec<Diagnostic>,
+    previous_result_id: Option<&str>,
+) -> DocumentDiagnosticReport {
+    if previous_result_id == Some(result_id.as_str()) {
+        DocumentDiagnosticReport::Unchanged(UnchangedDocumentDiagnosticReport { result_id })
+    } else {
+        DocumentDiagnosticReport::Full(FullDocumentDiagnosticReport {
+            result_id: Some(result_id),
+            items: diagnostics,
+        })
+    }
+}
+
 fn are_all_diagnostics_equal(
     map: &FxHashMap<FileId, Vec<Diagnostic>>,
     file_id: FileId,
@@ -162,4 +242,95 @@ mod tests {
         assert_eq!(changes.as_ref(), Some(&expected_changes));
         assert_eq!(diagnostics.diagnostics_for(file_id).next(), None);
     }
+
+    #[test]
+    fn bumps_revision_on_change() {
+        let mut diagnostics = DiagnosticCollection::default();
+        let file_id = FileId(0);
+        let other_file_id = FileId(1);
+        assert_eq!(diagnostics.revision(file_id), 0);
+
+        diagnostics.set_native(file_id, vec![Diagnostic::default()]);
+        let revision = diagnostics.revision(file_id);
+        assert_ne!(revision, 0);
+
+        // Setting the same diagnostics again is not a change
+        diagnostics.set_native(file_id, vec![Diagnostic::default()]);
+        assert_eq!(diagnostics.revision(file_id), revision);
+
+        diagnostics.set_native(other_file_id, vec![Diagnostic::default()]);
+        assert_eq!(diagnostics.revision(file_id), revision);
+
+        diagnostics.clear(file_id);
+        assert_ne!(diagnostics.revision(file_id), revision);
+        assert_eq!(
+            diagnostics.file_ids().collect::<Vec<_>>(),
+            vec![other_file_id]
+        );
+    }
+
+    #[test]
+    fn result_id_changes_with_generation() {
+        let generation = DiagnosticsGeneration {
+            session: 1,
+            config: 2,
+            source: 3,
+        };
+        let result_id = generation.result_id(true, 4);
+        assert_eq!(result_id, "1.2.3.4");
+        // Closed files only change with the collection
+        assert_eq!(generation.result_id(false, 4), "1.2.0.4");
+        for other in [
+            DiagnosticsGeneration {
+                session: 5,
+                ..generation
+            },
+            DiagnosticsGeneration {
+                config: 5,
+                ..generation
+            },
+            DiagnosticsGeneration {
+                source: 5,
+                ..generation
+            },
+        ] {
+            assert_ne!(other.result_id(true, 4), result_id);
+        }
+        assert_ne!(
+            DiagnosticsGeneration {
+                config: 5,
+                ..generation
+            }
+            .result_id(false, 4),
+            generation.result_id(false, 4)
+        );
+    }
+
+    #[test]
+    fn pull_report_is_unchanged_for_same_result_id() {
+        let diagnostic = Diagnostic {
+            message: "unused variable".to_string(),
+            ..Diagnostic::default()
+        };
+
+        let report = document_report("1".to_string(), vec![diagnostic.clone()], None);
+        let result_id = match report {
+            DocumentDiagnosticReport::Full(full) => {
+                assert_eq!(full.items, vec![diagnostic.clone()]);
+                full.result_id.unwrap()
+            }
+            DocumentDiagnosticReport::Unchanged(_) => panic!("expected a full report"),
+        };
+
+        assert_eq!(
+            document_report(result_id.clone(), vec![diagnostic], Some(&result_id)),
+            DocumentDiagnosticReport::Unchanged(UnchangedDocumentDiagnosticReport { result_id })
+        );
+    }
+
+    #[test]
+    fn pull_report_is_full_when_result_id_changes() {
+        let report = document_report("2".to_string(), vec![], Some("1"));
+        assert!(matches!(report, DocumentDiagnosticReport::Full(full) if full.items.is_empty()));
+    }
 }
@@ -19,15 +19,20 @@ use elp_ide::elp_ide_assists::SingleResolve;
 use elp_ide::elp_ide_completion::Completion;
 use elp_ide::elp_ide_completion::Kind;
 use elp_ide::elp_ide_db::assists::AssistContextDiagnostic;
+use elp_ide::elp_ide_db::elp_base_db::FileId;
 use elp_ide::elp_ide_db::elp_base_db::FilePosition;
 use elp_ide::elp_ide_db::elp_base_db::FileRange;
 use elp_ide::elp_ide_db::elp_base_db::ProjectId;
 use elp_ide::elp_ide_db::LineIndex;
 use elp_ide::elp_ide_db::SymbolKind;
+use elp_ide::Annotation;
+use elp_ide::AnnotationConfig;
+use elp_ide::AnnotationKind;
 use elp_ide::Cancellable;
 use elp_ide::HighlightedRange;
 use elp_ide::RangeInfo;
 use elp_ide::TextRange;
+use fxhash::FxHashMap;
 use itertools::Itertools;
 use lsp_server::ErrorCode;
 use lsp_types::CallHierarchyIncomingCall;
@@ -57,6 +62,7 @@ use lsp_types::Url;
 use lsp_types::WorkspaceEdit;
 
 use crate::convert::lsp_to_assist_context_diagnostic;
+use crate::diagnostics;
 use crate::from_proto;
 use crate::lsp_ext;
 use crate::snapshot::Snapshot;
@@ -308,6 +314,42 @@ pub(crate) fn handle_goto_definition(
     Ok(Some(res))
 }
 
+pub(crate) fn handle_goto_implementation(
+    snap: Snapshot,
+    params: lsp_types::request::GotoImplementationParams,
+) -> Result<Option<lsp_types::request::GotoImplementationResponse>> {
+    let _p = profile::span("handle_goto_implementation");
+    let position = from_proto::file_position(&snap, params.text_document_position_params)?;
+    let nav_info = match snap.analysis.goto_implementation(position)? {
+        None => return Ok(None),
+        Some(it) => it,
+    };
+    let src = FileRange {
+        file_id: position.file_id,
+        range: nav_info.range,
+    };
+    let res = to_proto::goto_definition_response(&snap, Some(src), nav_info.info)?;
+    Ok(Some(res))
+}
+
+pub(crate) fn handle_goto_type_definition(
+    snap: Snapshot,
+    params: lsp_types::request::GotoTypeDefinitionParams,
+) -> Result<Option<lsp_types::request::GotoTypeDefinitionResponse>> {
+    let _p = profile::span("handle_goto_type_definition");
+    let position = from_proto::file_position(&snap, params.text_document_position_params)?;
+    let nav_info = match snap.analysis.goto_type_definition(position)? {
+        None => return Ok(None),
+        Some(it) => it,
+    };
+    let src = FileRange {
+        file_id: position.file_id,
+        range: nav_info.range,
+    };
+    let res = to_proto::goto_definition_response(&snap, Some(src), nav_info.info)?;
+    Ok(Some(res))
+}
+
 pub(crate) fn handle_references(
     snap: Snapshot,
     params: lsp_types::ReferenceParams,
@@ -435,6 +477,23 @@ pub(crate) fn handle_document_symbol(
     Ok(Some(res.into()))
 }
 
+pub(crate) fn handle_document_link(
+    snap: Snapshot,
+    params: lsp_types::DocumentLinkParams,
+) -> Result<Option<Vec<lsp_types::DocumentLink>>> {
+    let _p = profile::span("handle_document_link");
+    let file_id = from_proto::file_id(&snap, &params.text_document.uri)?;
+    let line_index = snap.analysis.line_index(file_id)?;
+
+    let res = snap
+        .analysis
+        .document_links(file_id)?
+        .into_iter()
+        .filter_map(|link| to_proto::document_link(&snap, &line_index, link))
+        .collect();
+    Ok(Some(res))
+}
+
 pub(crate) fn handle_workspace_symbol(
     snap: Snapshot,
     params: lsp_types::WorkspaceSymbolParams,
@@ -444,20 +503,19 @@ pub(crate) fn handle_workspace_symbol(
     let mut res = Vec::new();
     for (project_id, _project) in snap.projects.iter().enumerate() {
         let project_id = ProjectId(project_id as u32);
-        for nav in snap.analysis.symbol_search(project_id, &params.query)? {
+        for (nav, container_name) in snap.analysis.symbol_search(project_id, &params.query)? {
             #[allow(deprecated)]
             let info = SymbolInformation {
                 name: nav.name.to_string(),
                 kind: to_proto::symbol_kind(nav.kind),
                 tags: None,
                 location: to_proto::location_from_nav(&snap, nav)?,
-                container_name: None,
+                container_name,
                 deprecated: None,
             };
             res.push(info);
         }
     }
-    res.sort_by(|a, b| a.name.cmp(&b.name));
     Ok(Some(res))
 }
 
@@ -508,6 +566,44 @@ pub(crate) fn handle_folding_range(
     Ok(Some(res))
 }
 
+pub(crate) fn handle_formatting(
+    snap: Snapshot,
+    params: lsp_types::DocumentFormattingParams,
+) -> Result<Option<Vec<lsp_types::TextEdit>>> {
+    let _p = profile::span("handle_formatting");
+    let file_id = from_proto::file_id(&snap, &params.text_document.uri)?;
+    format_file(&snap, file_id, None)
+}
+
+pub(crate) fn handle_range_formatting(
+    snap: Snapshot,
+    params: lsp_types::DocumentRangeFormattingParams,
+) -> Result<Option<Vec<lsp_types::TextEdit>>> {
+    let _p = profile::span("handle_range_formatting");
+    let file_id = from_proto::file_id(&snap, &params.text_document.uri)?;
+    let line_index = snap.analysis.line_index(file_id)?;
+    let range = from_proto::text_range(&line_index, params.range);
+    format_file(&snap, file_id, Some(range))
+}
+
+fn format_file(
+    snap: &Snapshot,
+    file_id: FileId,
+    range: Option<TextRange>,
+) -> Result<Option<Vec<lsp_types::TextEdit>>> {
+    let edit = match snap.analysis.format(file_id, range)? {
+        None => return Ok(None),
+        Some(it) => it,
+    };
+    let line_index = snap.analysis.line_index(file_id)?;
+    let line_endings = snap.line_endings(file_id);
+    Ok(Some(to_proto::text_edit_vec(
+        &line_index,
+        line_endings,
+        edit,
+    )))
+}
+
 pub(crate) fn handle_document_highlight(
     snap: Snapshot,
     params: lsp_types::DocumentHighlightParams,
@@ -634,6 +730,132 @@ pub(crate) fn handle_call_hierarchy_outgoing(
     Ok(Some(res))
 }
 
+pub(crate) fn handle_type_hierarchy_prepare(
+    snap: Snapshot,
+    params: lsp_ext::TypeHierarchyPrepareParams,
+) -> Result<Option<Vec<lsp_ext::TypeHierarchyItem>>> {
+    let _p = profile::span("handle_type_hierarchy_prepare");
+    let position = from_proto::file_position(&snap, params.text_document_position_params)?;
+
+    let nav_info = match snap.analysis.type_hierarchy_prepare(position)? {
+        None => return Ok(None),
+        Some(it) => it,
+    };
+    let res = nav_info
+        .info
+        .into_iter()
+        .map(|it| to_proto::type_hierarchy_item(&snap, it))
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Some(res))
+}
+
+pub(crate) fn handle_type_hierarchy_supertypes(
+    snap: Snapshot,
+    params: lsp_ext::TypeHierarchySupertypesParams,
+) -> Result<Option<Vec<lsp_ext::TypeHierarchyItem>>> {
+    let _p = profile::span("handle_type_hierarchy_supertypes");
+    let fpos = type_hierarchy_item_position(&snap, params.item)?;
+
+    let navs = match snap.analysis.type_hierarchy_supertypes(fpos)? {
+        None => return Ok(None),
+        Some(it) => it,
+    };
+    let res = navs
+        .into_iter()
+        .map(|it| to_proto::type_hierarchy_item(&snap, it))
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Some(res))
+}
+
+pub(crate) fn handle_type_hierarchy_subtypes(
+    snap: Snapshot,
+    params: lsp_ext::TypeHierarchySubtypesParams,
+) -> Result<Option<Vec<lsp_ext::TypeHierarchyItem>>> {
+    let _p = profile::span("handle_type_hierarchy_subtypes");
+    let fpos = type_hierarchy_item_position(&snap, params.item)?;
+
+    let navs = match snap.analysis.type_hierarchy_subtypes(fpos)? {
+        None => return Ok(None),
+        Some(it) => it,
+    };
+    let res = navs
+        .into_iter()
+        .map(|it| to_proto::type_hierarchy_item(&snap, it))
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Some(res))
+}
+
+fn type_hierarchy_item_position(
+    snap: &Snapshot,
+    item: lsp_ext::TypeHierarchyItem,
+) -> Result<FilePosition> {
+    let doc = TextDocumentIdentifier::new(item.uri);
+    let frange = from_proto::file_range(snap, doc, item.selection_range)?;
+    Ok(FilePosition {
+        file_id: frange.file_id,
+        offset: frange.range.start(),
+    })
+}
+
+pub(crate) fn handle_document_diagnostic(
+    snap: Snapshot,
+    params: lsp_ext::DocumentDiagnosticParams,
+) -> Result<lsp_ext::DocumentDiagnosticReport> {
+    let _p = profile::span("handle_document_diagnostic");
+    let file_id = from_proto::file_id(&snap, &params.text_document.uri)?;
+
+    let (result_id, diagnostics) = snap.pull_diagnostics(file_id)?;
+    Ok(diagnostics::document_report(
+        result_id,
+        diagnostics,
+        params.previous_result_id.as_deref(),
+    ))
+}
+
+pub(crate) fn handle_workspace_diagnostic(
+    snap: Snapshot,
+    params: lsp_ext::WorkspaceDiagnosticParams,
+) -> Result<lsp_ext::WorkspaceDiagnosticReport> {
+    let _p = profile::span("handle_workspace_diagnostic");
+
+    // Report on the open documents and on those with diagnostics from
+    // the background checks, as well as on any document the client
+    // already holds diagnostics for, so that stale results get refreshed.
+    let mut previous_result_ids = FxHashMap::default();
+    for previous in params.previous_result_ids {
+        if let Ok(file_id) = from_proto::file_id(&snap, &previous.uri) {
+            previous_result_ids.insert(file_id, previous.value);
+        }
+    }
+    let file_ids = snap
+        .opened_documents()
+        .into_iter()
+        .chain(snap.diagnosed_files())
+        .chain(previous_result_ids.keys().copied())
+        .unique()
+        .collect::<Vec<_>>();
+
+    let items = file_ids
+        .into_iter()
+        .map(|file_id| {
+            let uri = snap.file_id_to_url(file_id);
+            let version = snap.url_file_version(&uri);
+            let (result_id, diagnostics) = snap.pull_diagnostics(file_id)?;
+            let previous_result_id = previous_result_ids.get(&file_id).map(String::as_str);
+            Ok(lsp_ext::WorkspaceDocumentDiagnosticReport {
+                uri,
+                version,
+                report: diagnostics::document_report(result_id, diagnostics, previous_result_id),
+            })
+        })
+        .collect::<Result<_>>()?;
+
+    Ok(lsp_ext::WorkspaceDiagnosticReport { items })
+}
+
 pub(crate) fn handle_signature_help(
     snap: Snapshot,
     params: lsp_types::SignatureHelpParams,
@@ -729,14 +951,18 @@ pub(crate) fn handle_code_lens(
 
     let mut res = Vec::new();
     let lens_config = snap.config.lens();
-    if !lens_config.run {
+    if !lens_config.run && !lens_config.references && !lens_config.implementations {
         // early return before any db query!
         return Ok(Some(res));
     }
 
     let file_id = from_proto::file_id(&snap, &params.text_document.uri)?;
 
-    let annotations = snap.analysis.annotations(file_id)?;
+    let annotation_config = AnnotationConfig {
+        references: lens_config.references,
+        implementations: lens_config.implementations,
+    };
+    let annotations = snap.analysis.annotations(&annotation_config, file_id)?;
     let project_build_data = match snap.analysis.project_id(file_id) {
         Ok(Some(project_id)) => snap
             .get_project(project_id)
@@ -751,6 +977,33 @@ pub(crate) fn handle_code_lens(
     Ok(Some(res))
 }
 
+pub(crate) fn handle_code_lens_resolve(snap: Snapshot, code_lens: CodeLens) -> Result<CodeLens> {
+    let _p = profile::span("handle_code_lens_resolve");
+
+    let data = match code_lens.data.clone() {
+        Some(data) => data,
+        None => return Ok(code_lens),
+    };
+    let data: lsp_ext::CodeLensData = serde_json::from_value(data)?;
+    let pos = from_proto::file_position(&snap, data.position)?;
+    let line_index = snap.analysis.line_index(pos.file_id)?;
+    let range = from_proto::text_range(&line_index, code_lens.range);
+    let kind = match data.kind {
+        lsp_ext::CodeLensKind::References => AnnotationKind::HasReferences { pos, data: None },
+        lsp_ext::CodeLensKind::Usages => AnnotationKind::HasUsages { pos, data: None },
+        lsp_ext::CodeLensKind::Implementations => {
+            AnnotationKind::HasImplementations { pos, data: None }
+        }
+    };
+    let annotation = snap
+        .analysis
+        .resolve_annotation(Annotation { range, kind })?;
+
+    let mut res = Vec::new();
+    to_proto::code_lens(&mut res, &snap, annotation, None)?;
+    Ok(res.pop().unwrap_or(code_lens))
+}
+
 pub(crate) fn handle_external_docs(
     snap: Snapshot,
     params: lsp_types::TextDocumentPositionParams,
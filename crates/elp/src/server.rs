@@ -10,6 +10,8 @@
 use std::fmt;
 use std::mem;
 use std::sync::Arc;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
 
 use always_assert::always;
 use anyhow::bail;
@@ -34,13 +36,17 @@ use elp_ide::elp_ide_db::elp_base_db::SourceRoot;
 use elp_ide::elp_ide_db::elp_base_db::SourceRootId;
 use elp_ide::elp_ide_db::elp_base_db::Vfs;
 use elp_ide::elp_ide_db::elp_base_db::VfsPath;
+use elp_ide::Analysis;
 use elp_ide::AnalysisHost;
+use elp_ide::Cancellable;
 use elp_log::telemetry;
 use elp_log::telemetry::TelemetryMessage;
 use elp_log::timeit;
 use elp_log::Logger;
 use elp_log::TimeIt;
+use elp_project_model::AppType;
 use elp_project_model::Project;
+use fxhash::FxHashSet;
 use lsp_server::Connection;
 use lsp_server::ErrorCode;
 use lsp_server::Notification;
@@ -64,6 +70,7 @@ use self::progress::Spinner;
 use crate::config::Config;
 use crate::convert;
 use crate::diagnostics::DiagnosticCollection;
+use crate::diagnostics::DiagnosticsGeneration;
 use crate::document::Document;
 use crate::handlers;
 use crate::line_endings::LineEndings;
@@ -103,6 +110,29 @@ pub enum Task {
     Progress(ProgressTask),
     ScheduleCache,
     UpdateCache(Spinner, Vec<FileId>),
+    ScheduleProjectDiagnostics(Option<ProjectChanges>),
+    ProjectFiles(Vec<FileId>),
+    ProjectDiagnostics(ProjectDiagnostics),
+}
+
+/// The files changed since the project-wide scan last ran, for it to
+/// re-check only the files depending on them.
+#[derive(Debug)]
+pub struct ProjectChanges {
+    changed: Vec<FileId>,
+    /// The modules the changed files referred to before changing
+    referenced: Vec<FileId>,
+}
+
+/// The state of an in-progress scan computing the diagnostics of every
+/// module of the project, along with the results of its latest batch.
+#[derive(Debug)]
+pub struct ProjectDiagnostics {
+    progress: ProgressBar,
+    total: usize,
+    remaining: Vec<FileId>,
+    native: Vec<(FileId, Vec<Diagnostic>)>,
+    erlang_service: Vec<(FileId, Vec<Diagnostic>)>,
 }
 
 impl fmt::Debug for Event {
@@ -169,7 +199,8 @@ pub struct Server {
     task_pool: TaskHandle,
     project_pool: TaskHandle,
     cache_pool: TaskHandle,
-    diagnostics: DiagnosticCollection,
+    diagnostics: Arc<RwLock<DiagnosticCollection>>,
+    diagnostics_generation: DiagnosticsGeneration,
     req_queue: ReqQueue,
     progress: ProgressManager,
     open_document_versions: SharedMap<VfsPath, i32>,
@@ -184,6 +215,14 @@ pub struct Server {
     project_loader: Arc<Mutex<ProjectLoader>>,
     eqwalizer_diagnostics_requested: bool,
     edoc_diagnostics_requested: bool,
+    project_diagnostics_requested: bool,
+    project_diagnostics_running: bool,
+    /// Files changed on disk since the project-wide scan last ran, to
+    /// re-check along with their dependents
+    project_diagnostics_changed: FxHashSet<FileId>,
+    /// Modules referred to by any file before it changed, since the
+    /// project-wide scan last ran, which may no longer be
+    project_diagnostics_referenced: FxHashSet<FileId>,
     logger: Logger,
     ai_completion: Arc<Mutex<AiCompletion>>,
 
@@ -209,7 +248,13 @@ impl Server {
             task_pool,
             project_pool,
             cache_pool,
-            diagnostics: DiagnosticCollection::default(),
+            diagnostics: Arc::default(),
+            diagnostics_generation: DiagnosticsGeneration {
+                session: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |time| time.as_nanos() as u64),
+                ..DiagnosticsGeneration::default()
+            },
             req_queue: ReqQueue::default(),
             open_document_versions: SharedMap::default(),
             newly_opened_documents: Vec::default(),
@@ -223,6 +268,10 @@ impl Server {
             project_loader: Arc::new(Mutex::new(ProjectLoader::new())),
             eqwalizer_diagnostics_requested: false,
             edoc_diagnostics_requested: false,
+            project_diagnostics_requested: false,
+            project_diagnostics_running: false,
+            project_diagnostics_changed: FxHashSet::default(),
+            project_diagnostics_referenced: FxHashSet::default(),
             logger,
             ai_completion: Arc::new(Mutex::new(ai_completion)),
             vfs_config_version: 0,
@@ -242,6 +291,8 @@ impl Server {
             Arc::clone(&self.line_ending_map),
             Arc::clone(&self.projects),
             Arc::clone(&self.ai_completion),
+            Arc::clone(&self.diagnostics),
+            self.diagnostics_generation,
         )
     }
 
@@ -375,6 +426,11 @@ impl Server {
                     Task::Progress(progress) => self.report_progress(progress),
                     Task::UpdateCache(spinner, files) => self.update_cache(spinner, files),
                     Task::ScheduleCache => self.schedule_cache(),
+                    Task::ScheduleProjectDiagnostics(changes) => {
+                        self.schedule_project_diagnostics(changes)
+                    }
+                    Task::ProjectFiles(files) => self.project_files_collected(files),
+                    Task::ProjectDiagnostics(scan) => self.project_diagnostics_progress(scan),
                 }
 
                 // Coalesce many tasks into a single main loop turn
@@ -394,6 +450,7 @@ impl Server {
 
         if self.status == Status::Running {
             if changed {
+                self.diagnostics_generation.source += 1;
                 self.update_native_diagnostics();
             }
 
@@ -405,14 +462,46 @@ impl Server {
             if mem::take(&mut self.edoc_diagnostics_requested) {
                 self.update_edoc_diagnostics();
             }
+
+            if !self.project_diagnostics_running {
+                if mem::take(&mut self.project_diagnostics_requested) {
+                    self.project_diagnostics_changed.clear();
+                    self.project_diagnostics_referenced.clear();
+                    self.schedule_project_diagnostics(None);
+                } else if !self.project_diagnostics_changed.is_empty() {
+                    let changes = ProjectChanges {
+                        changed: mem::take(&mut self.project_diagnostics_changed)
+                            .into_iter()
+                            .collect(),
+                        referenced: mem::take(&mut self.project_diagnostics_referenced)
+                            .into_iter()
+                            .collect(),
+                    };
+                    self.schedule_project_diagnostics(Some(changes));
+                }
+            }
         }
 
-        if let Some(diagnostic_changes) = self.diagnostics.take_changes() {
+        let diagnostic_changes = self.diagnostics.write().take_changes();
+        if let Some(diagnostic_changes) = diagnostic_changes {
             log::info!("changed diagnostics: {:?}", diagnostic_changes);
 
+            if self.config.pull_diagnostics() {
+                // Clients pulling diagnostics are only asked to pull them again
+                if self.config.diagnostics_refresh() {
+                    self.send_request::<lsp_ext::WorkspaceDiagnosticRefresh>((), |_, _| Ok(()));
+                }
+                return Ok(());
+            }
+
             for file_id in diagnostic_changes {
                 let url = file_id_to_url(&self.vfs.read(), file_id);
-                let diagnostics = self.diagnostics.diagnostics_for(file_id).cloned().collect();
+                let diagnostics = self
+                    .diagnostics
+                    .read()
+                    .diagnostics_for(file_id)
+                    .cloned()
+                    .collect();
                 let version = convert::vfs_path(&url)
                     .map(|path| self.open_document_versions.read().get(&path).cloned())
                     .unwrap_or_default();
@@ -467,15 +556,20 @@ impl Server {
             .on::<request::CodeActionRequest>(handlers::handle_code_action)
             .on::<request::CodeActionResolveRequest>(handlers::handle_code_action_resolve)
             .on::<request::GotoDefinition>(handlers::handle_goto_definition)
+            .on::<request::GotoImplementation>(handlers::handle_goto_implementation)
+            .on::<request::GotoTypeDefinition>(handlers::handle_goto_type_definition)
             .on::<request::References>(handlers::handle_references)
             .on::<request::Completion>(handlers::handle_completion)
             .on::<request::ResolveCompletionItem>(handlers::handle_completion_resolve)
             .on::<request::DocumentSymbolRequest>(handlers::handle_document_symbol)
+            .on::<request::DocumentLinkRequest>(handlers::handle_document_link)
             .on::<request::WorkspaceSymbol>(handlers::handle_workspace_symbol)
             .on::<request::Rename>(handlers::handle_rename)
             .on::<request::HoverRequest>(handlers::handle_hover)
             .on::<request::FoldingRangeRequest>(handlers::handle_folding_range)
             .on::<request::DocumentHighlightRequest>(handlers::handle_document_highlight)
+            .on::<request::Formatting>(handlers::handle_formatting)
+            .on::<request::RangeFormatting>(handlers::handle_range_formatting)
             .on::<lsp_types::request::CallHierarchyPrepare>(handlers::handle_call_hierarchy_prepare)
             .on::<lsp_types::request::CallHierarchyIncomingCalls>(
                 handlers::handle_call_hierarchy_incoming,
@@ -483,6 +577,11 @@ impl Server {
             .on::<lsp_types::request::CallHierarchyOutgoingCalls>(
                 handlers::handle_call_hierarchy_outgoing,
             )
+            .on::<lsp_ext::TypeHierarchyPrepare>(handlers::handle_type_hierarchy_prepare)
+            .on::<lsp_ext::TypeHierarchySupertypes>(handlers::handle_type_hierarchy_supertypes)
+            .on::<lsp_ext::TypeHierarchySubtypes>(handlers::handle_type_hierarchy_subtypes)
+            .on::<lsp_ext::DocumentDiagnosticRequest>(handlers::handle_document_diagnostic)
+            .on::<lsp_ext::WorkspaceDiagnosticRequest>(handlers::handle_workspace_diagnostic)
             .on::<request::SignatureHelpRequest>(handlers::handle_signature_help)
             .on::<request::SelectionRangeRequest>(handlers::handle_selection_range)
             .on::<request::SemanticTokensFullRequest>(handlers::handle_semantic_tokens_full)
@@ -491,6 +590,7 @@ impl Server {
             )
             .on::<request::SemanticTokensRangeRequest>(handlers::handle_semantic_tokens_range)
             .on::<request::CodeLensRequest>(handlers::handle_code_lens)
+            .on::<request::CodeLensResolve>(handlers::handle_code_lens_resolve)
             .on::<request::InlayHintRequest>(handlers::handle_inlay_hints)
             .on::<request::InlayHintResolveRequest>(handlers::handle_inlay_hints_resolve)
             .on::<lsp_ext::ExpandMacro>(handlers::handle_expand_macro)
@@ -575,23 +675,30 @@ impl Server {
                     if this.open_document_versions.write().remove(&path).is_none() {
                         log::error!("unexpected DidCloseTextDocument: {}", path);
                     }
-                }
 
-                // Clear the diagnostics for the previously known version of the file.
-                this.send_notification::<lsp_types::notification::PublishDiagnostics>(
-                    lsp_types::PublishDiagnosticsParams {
-                        uri: params.text_document.uri,
-                        diagnostics: Vec::new(),
-                        version: None,
-                    },
-                );
+                    // Clear the diagnostics for the previously known version of the file,
+                    // unless they are kept up to date by the project-wide scan.
+                    let file_id = this.vfs.read().file_id(&path);
+                    if let Some(file_id) = file_id {
+                        let scanned = this.config.project_problems()
+                            && is_project_scanned(&this.analysis_host.analysis(), file_id)
+                                .unwrap_or(false);
+                        if !scanned {
+                            this.diagnostics.write().clear(file_id);
+                        }
+                    }
+                }
 
                 Ok(())
             })?
             .on::<notification::DidSaveTextDocument>(|this, params| {
-                if convert::vfs_path(&params.text_document.uri).is_ok() {
+                if let Ok(path) = convert::vfs_path(&params.text_document.uri) {
                     this.eqwalizer_diagnostics_requested = true;
                     this.edoc_diagnostics_requested = true;
+                    if this.config.project_problems() {
+                        let file_id = this.vfs.read().file_id(&path);
+                        this.project_diagnostics_changed.extend(file_id);
+                    }
                 }
                 Ok(())
             })?
@@ -619,6 +726,7 @@ impl Server {
                         }
                     }
                 }
+                // The project-wide scan picks up the files once reloaded
                 this.eqwalizer_diagnostics_requested = true;
                 this.edoc_diagnostics_requested = true;
                 Ok(())
@@ -648,6 +756,7 @@ impl Server {
             self.transition(Status::Running);
             self.schedule_compile_deps();
             self.schedule_cache();
+            self.project_diagnostics_requested = self.config.project_problems();
         }
     }
 
@@ -677,6 +786,26 @@ impl Server {
             return false;
         }
 
+        if self.config.project_problems() {
+            // What the changed files referred to must be looked up before
+            // applying the changes, to re-check the modules they no longer
+            // refer to. Open documents are included, as by the time they
+            // are saved their previous contents are gone.
+            let created: FxHashSet<FileId> = changed_files
+                .iter()
+                .filter(|file| matches!(file.change_kind, ChangeKind::Create))
+                .map(|file| file.file_id)
+                .collect();
+            let analysis = self.analysis_host.analysis();
+            for file in &changed_files {
+                if !created.contains(&file.file_id) {
+                    if let Ok(referenced) = analysis.referenced_files(file.file_id) {
+                        self.project_diagnostics_referenced.extend(referenced);
+                    }
+                }
+            }
+        }
+
         // The writes to salsa as these changes are applied below will
         // trigger Cancellation any pending processing.  This makes
         // sure all calculations see a consistent view of the
@@ -687,6 +816,13 @@ impl Server {
 
         for file in &changed_files {
             let file_path = vfs.file_path(file.file_id);
+            // Changes to open documents are only re-checked project-wide
+            // once saved
+            if self.config.project_problems()
+                && !self.open_document_versions.read().contains_key(&file_path)
+            {
+                self.project_diagnostics_changed.insert(file.file_id);
+            }
             // Invalidate DB when making changes to header files
             if let Some((_, Some("hrl"))) = file_path.name_and_extension() {
                 raw_database.set_include_files_revision(raw_database.include_files_revision() + 1);
@@ -700,12 +836,13 @@ impl Server {
                     .insert(file.file_id, line_ending);
                 raw_database.set_file_text(file.file_id, Arc::new(text));
                 // causes us to remove stale squiggles from the UI
-                self.diagnostics.set_eqwalizer(file.file_id, vec![]);
+                self.diagnostics.write().set_eqwalizer(file.file_id, vec![]);
             } else {
                 // TODO (T105975906): Clean up stale .etf files
 
                 // We can't actually delete things from salsa, just set it to empty
                 raw_database.set_file_text(file.file_id, Default::default());
+                self.diagnostics.write().clear(file.file_id);
             };
         }
 
@@ -737,6 +874,10 @@ impl Server {
     }
 
     fn update_native_diagnostics(&mut self) {
+        // Computed on demand for the open documents of clients pulling them
+        if self.config.pull_diagnostics() {
+            return;
+        }
         let opened_documents = self.opened_documents();
         let snapshot = self.snapshot();
 
@@ -752,7 +893,7 @@ impl Server {
 
     fn native_diagnostics_completed(&mut self, diags: Vec<(FileId, Vec<Diagnostic>)>) {
         for (file_id, diagnostics) in diags {
-            self.diagnostics.set_native(file_id, diagnostics);
+            self.diagnostics.write().set_native(file_id, diagnostics);
         }
     }
 
@@ -807,13 +948,13 @@ impl Server {
 
     fn eqwalizer_diagnostics_completed(&mut self, diags: Vec<(FileId, Vec<Diagnostic>)>) {
         for (file_id, diagnostics) in diags {
-            self.diagnostics.set_eqwalizer(file_id, diagnostics);
+            self.diagnostics.write().set_eqwalizer(file_id, diagnostics);
         }
     }
 
     fn edoc_diagnostics_completed(&mut self, diags: Vec<(FileId, Vec<Diagnostic>)>) {
         for (file_id, diagnostics) in diags {
-            self.diagnostics.set_edoc(file_id, diagnostics);
+            self.diagnostics.write().set_edoc(file_id, diagnostics);
         }
     }
 
@@ -843,7 +984,9 @@ impl Server {
 
     fn erlang_service_diagnostics_completed(&mut self, diags: Vec<(FileId, Vec<Diagnostic>)>) {
         for (file_id, diagnostics) in diags.clone() {
-            self.diagnostics.set_erlang_service(file_id, diagnostics);
+            self.diagnostics
+                .write()
+                .set_erlang_service(file_id, diagnostics);
         }
     }
 
@@ -943,7 +1086,25 @@ impl Server {
 
     fn update_configuration(&mut self, config: Config) {
         let _p = profile::span("Server::update_configuration");
-        let _old_config = mem::replace(&mut self.config, Arc::new(config));
+        let old_config = mem::replace(&mut self.config, Arc::new(config));
+        // The diagnostics enabled may have changed
+        self.diagnostics_generation.config += 1;
+        if self.config.project_problems() && !old_config.project_problems() {
+            self.project_diagnostics_requested = true;
+        }
+        if !self.config.project_problems() && old_config.project_problems() {
+            // Clear what the project-wide scan found for the files not open
+            self.project_diagnostics_changed.clear();
+            self.project_diagnostics_referenced.clear();
+            let opened: FxHashSet<FileId> = self.opened_documents().into_iter().collect();
+            let mut diagnostics = self.diagnostics.write();
+            let file_ids: FxHashSet<FileId> = diagnostics.file_ids().collect();
+            for file_id in file_ids {
+                if !opened.contains(&file_id) {
+                    diagnostics.clear(file_id);
+                }
+            }
+        }
 
         self.logger
             .reconfigure(LOGGER_NAME, self.config.log_filter());
@@ -1102,6 +1263,94 @@ impl Server {
         });
     }
 
+    /// Scans the modules of the project's own apps, or only those depending
+    /// on the given changed files.
+    fn schedule_project_diagnostics(&mut self, changes: Option<ProjectChanges>) {
+        if !self.config.project_problems() {
+            return;
+        }
+        self.project_diagnostics_running = true;
+        let snapshot = self.snapshot();
+
+        self.task_pool.handle.spawn_with_sender(move |sender| {
+            match project_diagnostics_files(&snapshot, changes.as_ref()) {
+                Ok(files) => sender.send(Task::ProjectFiles(files)).unwrap(),
+                //rescheduling canceled
+                Err(_) => sender
+                    .send(Task::ScheduleProjectDiagnostics(changes))
+                    .unwrap(),
+            }
+        });
+    }
+
+    fn project_files_collected(&mut self, files: Vec<FileId>) {
+        let total = files.len();
+        let progress = self
+            .progress
+            .begin_bar("Computing project diagnostics".to_string(), total);
+        self.project_diagnostics_progress(ProjectDiagnostics {
+            progress,
+            total,
+            remaining: files,
+            native: vec![],
+            erlang_service: vec![],
+        });
+    }
+
+    /// Stores the results of the latest batch of the project-wide scan, and
+    /// schedules the next one. Each batch uses a fresh snapshot, so that a
+    /// cancelled batch gets resumed against the latest state of the files.
+    fn project_diagnostics_progress(&mut self, mut scan: ProjectDiagnostics) {
+        const BATCH_SIZE: usize = 20;
+
+        for (file_id, diagnostics) in mem::take(&mut scan.native) {
+            self.diagnostics.write().set_native(file_id, diagnostics);
+        }
+        for (file_id, diagnostics) in mem::take(&mut scan.erlang_service) {
+            self.diagnostics
+                .write()
+                .set_erlang_service(file_id, diagnostics);
+        }
+
+        if scan.remaining.is_empty() || !self.config.project_problems() {
+            scan.progress.end();
+            self.project_diagnostics_running = false;
+            return;
+        }
+        scan.progress.report(scan.total - scan.remaining.len(), scan.total);
+
+        let snapshot = self.snapshot();
+        let vfs = Arc::clone(&self.vfs);
+        self.task_pool.handle.spawn_with_sender(move |sender| {
+            for _ in 0..BATCH_SIZE {
+                let file_id = match scan.remaining.pop() {
+                    Some(file_id) => file_id,
+                    None => break,
+                };
+                let native = match snapshot.native_diagnostics(file_id) {
+                    Some(native) => native,
+                    //got canceled
+                    None => {
+                        scan.remaining.push(file_id);
+                        break;
+                    }
+                };
+                if is_supported_by_parse_server(&vfs.read(), file_id) {
+                    match snapshot.erlang_service_diagnostics(file_id) {
+                        Some(diagnostics) => scan.erlang_service.extend(diagnostics),
+                        //got canceled
+                        None => {
+                            scan.remaining.push(file_id);
+                            break;
+                        }
+                    }
+                }
+                scan.native.push((file_id, native));
+            }
+            sender.send(Task::ProjectDiagnostics(scan)).unwrap();
+        });
+    }
+
     fn report_progress(&mut self, task: ProgressTask) {
         let params = match task {
             ProgressTask::BeginNotify(params) => {
@@ -1130,6 +1379,42 @@ impl Server {
     }
 }
 
+/// The files for the project-wide scan to check: the modules of the
+/// project's own apps, or only those depending on the changed files.
+fn project_diagnostics_files(
+    snapshot: &Snapshot,
+    changes: Option<&ProjectChanges>,
+) -> Cancellable<Vec<FileId>> {
+    let mut files = vec![];
+    for (i, _) in snapshot.projects.iter().enumerate() {
+        let project_id = ProjectId(i as u32);
+        let dependents = match changes {
+            Some(changes) => Some(snapshot.analysis.dependent_files(
+                project_id,
+                &changes.changed,
+                &changes.referenced,
+            )?),
+            None => None,
+        };
+        for (_, _, file_id) in snapshot.analysis.module_index(project_id)?.iter_own() {
+            let depends = dependents.as_ref().map_or(true, |dependents| {
+                dependents.binary_search(&file_id).is_ok()
+            });
+            if depends && is_project_scanned(&snapshot.analysis, file_id)? {
+                files.push(file_id);
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Whether the diagnostics of the file are kept up to date by the
+/// project-wide scan, which covers the modules of the project's own apps.
+fn is_project_scanned(analysis: &Analysis, file_id: FileId) -> Cancellable<bool> {
+    Ok(analysis.file_app_type(file_id)? == Some(AppType::App)
+        && analysis.module_name(file_id)?.is_some())
+}
+
 fn parse_id(id: lsp_types::NumberOrString) -> RequestId {
     match id {
         lsp_types::NumberOrString::Number(id) => id.into(),
@@ -14,8 +14,10 @@ use lsp_types::CodeActionOptions;
 use lsp_types::CodeActionProviderCapability;
 use lsp_types::CodeLensOptions;
 use lsp_types::CompletionOptions;
+use lsp_types::DocumentLinkOptions;
 use lsp_types::FoldingRangeProviderCapability;
 use lsp_types::HoverProviderCapability;
+use lsp_types::ImplementationProviderCapability;
 use lsp_types::InlayHintOptions;
 use lsp_types::InlayHintServerCapabilities;
 use lsp_types::OneOf;
@@ -30,6 +32,7 @@ use lsp_types::SignatureHelpOptions;
 use lsp_types::TextDocumentSyncCapability;
 use lsp_types::TextDocumentSyncKind;
 use lsp_types::TextDocumentSyncOptions;
+use lsp_types::TypeDefinitionProviderCapability;
 use lsp_types::WorkDoneProgressOptions;
 
 use crate::semantic_tokens;
@@ -66,8 +69,8 @@ pub fn compute(client: &ClientCapabilities) -> ServerCapabilities {
             },
         }),
         definition_provider: Some(OneOf::Left(true)),
-        type_definition_provider: None,
-        implementation_provider: None,
+        type_definition_provider: Some(TypeDefinitionProviderCapability::Simple(true)),
+        implementation_provider: Some(ImplementationProviderCapability::Simple(true)),
         references_provider: Some(OneOf::Left(true)),
         document_highlight_provider: Some(OneOf::Left(true)),
         document_symbol_provider: Some(OneOf::Left(true)),
@@ -75,10 +78,10 @@ pub fn compute(client: &ClientCapabilities) -> ServerCapabilities {
         code_action_provider: Some(code_action_capabilities(client)),
         // TODO: This will be put behind a GK before shipping
         code_lens_provider: Some(CodeLensOptions {
-            resolve_provider: Some(false),
+            resolve_provider: Some(true),
         }),
-        document_formatting_provider: None,
-        document_range_formatting_provider: None,
+        document_formatting_provider: Some(OneOf::Left(true)),
+        document_range_formatting_provider: Some(OneOf::Left(true)),
         document_on_type_formatting_provider: None,
         rename_provider: Some(OneOf::Right(RenameOptions {
             prepare_provider: Some(false),
@@ -86,7 +89,12 @@ pub fn compute(client: &ClientCapabilities) -> ServerCapabilities {
                 work_done_progress: None,
             },
         })),
-        document_link_provider: None,
+        document_link_provider: Some(DocumentLinkOptions {
+            resolve_provider: Some(false),
+            work_done_progress_options: WorkDoneProgressOptions {
+                work_done_progress: None,
+            },
+        }),
         color_provider: None,
         folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
         declaration_provider: None,
@@ -118,6 +126,27 @@ pub fn compute(client: &ClientCapabilities) -> ServerCapabilities {
     }
 }
 
+/// Server capabilities from LSP 3.17 that the version of lsp-types we
+/// depend on does not model yet. They are added to the serialized
+/// `ServerCapabilities` sent to the client.
+pub fn compute_unmodelled(
+    _client: &ClientCapabilities,
+) -> serde_json::Map<String, serde_json::Value> {
+    let mut capabilities = serde_json::Map::new();
+    capabilities.insert(
+        "typeHierarchyProvider".to_string(),
+        serde_json::Value::Bool(true),
+    );
+    capabilities.insert(
+        "diagnosticProvider".to_string(),
+        serde_json::json!({
+            "interFileDependencies": true,
+            "workspaceDiagnostics": true,
+        }),
+    );
+    capabilities
+}
+
 fn code_action_capabilities(client_caps: &ClientCapabilities) -> CodeActionProviderCapability {
     client_caps
         .text_document
@@ -56,6 +56,7 @@ impl ServerSetup {
     fn initialize(&self) -> Result<Config> {
         let _timer = timeit_with_telemetry!(TelemetryData::Initialize);
         let (id, params) = self.connection.initialize_start()?;
+        let unmodelled_caps = params["capabilities"].clone();
         let params = from_json::<lsp_types::InitializeParams>("InitializeParams", params)?;
 
         let server_capabilities = capabilities::compute(&params.capabilities);
@@ -71,8 +72,13 @@ impl ServerSetup {
             offset_encoding: None,
         };
 
+        let mut result_json = serde_json::to_value(result.clone()).unwrap();
+        if let Some(capabilities) = result_json["capabilities"].as_object_mut() {
+            capabilities.extend(capabilities::compute_unmodelled(&params.capabilities));
+        }
+
         self.connection
-            .initialize_finish(id, serde_json::to_value(result.clone()).unwrap())
+            .initialize_finish(id, result_json)
             .with_context(|| format!("during initialization finish: {:?}", result))?;
 
         let message = format!("ELP version: {}", crate::version());
@@ -99,6 +105,7 @@ impl ServerSetup {
         // anything.  If they match config, that is because we
         // choose this to be so in the client.
         let mut config = Config::new(root_path, params.capabilities);
+        config.unmodelled_caps = unmodelled_caps;
         if let Some(options) = params.initialization_options {
             config.update(options);
         }
@@ -14,14 +14,18 @@ use anyhow::Context;
 use anyhow::Result;
 use elp_ai::AiCompletion;
 use elp_ai::CompletionReceiver;
+use elp_ide::diagnostics;
 use elp_ide::elp_ide_db::elp_base_db::AbsPathBuf;
+use elp_ide::elp_ide_db::elp_base_db::AnchoredPathBuf;
 use elp_ide::elp_ide_db::elp_base_db::FileId;
 use elp_ide::elp_ide_db::elp_base_db::FilePosition;
 use elp_ide::elp_ide_db::elp_base_db::ProjectId;
 use elp_ide::elp_ide_db::elp_base_db::Vfs;
 use elp_ide::elp_ide_db::elp_base_db::VfsPath;
 use elp_ide::elp_ide_db::EqwalizerDiagnostics;
+use elp_ide::elp_ide_db::LineIndex;
 use elp_ide::Analysis;
+use elp_ide::Cancellable;
 use elp_log::timeit_with_telemetry;
 use elp_project_model::Project;
 use fxhash::FxHashMap;
@@ -36,6 +40,8 @@ use serde::Serialize;
 
 use crate::config::Config;
 use crate::convert;
+use crate::diagnostics::DiagnosticCollection;
+use crate::diagnostics::DiagnosticsGeneration;
 use crate::line_endings::LineEndings;
 use crate::server::file_id_to_path;
 use crate::server::file_id_to_url;
@@ -85,6 +91,9 @@ pub struct Snapshot {
     line_ending_map: SharedMap<FileId, LineEndings>,
     pub(crate) projects: Arc<Vec<Project>>,
     ai_completion: Arc<Mutex<AiCompletion>>,
+    diagnostics: Arc<RwLock<DiagnosticCollection>>,
+    /// Identifies the diagnostics reported to clients pulling them
+    diagnostics_generation: DiagnosticsGeneration,
 }
 
 impl Snapshot {
@@ -96,6 +105,8 @@ impl Snapshot {
         line_ending_map: Arc<RwLock<FxHashMap<FileId, LineEndings>>>,
         projects: Arc<Vec<Project>>,
         ai_completion: Arc<Mutex<AiCompletion>>,
+        diagnostics: Arc<RwLock<DiagnosticCollection>>,
+        diagnostics_generation: DiagnosticsGeneration,
     ) -> Self {
         Snapshot {
             config,
@@ -106,6 +117,8 @@ impl Snapshot {
             line_ending_map,
             projects,
             ai_completion,
+            diagnostics,
+            diagnostics_generation,
         }
     }
 
@@ -126,6 +139,19 @@ impl Snapshot {
         file_id_to_url(&self.vfs.read(), id)
     }
 
+    /// Resolve a path relative to the directory of its anchor file
+    pub(crate) fn anchored_path(&self, anchored: &AnchoredPathBuf) -> Result<Url> {
+        let mut base = self.vfs.read().file_path(anchored.anchor);
+        base.pop();
+        let path = base
+            .join(&anchored.path)
+            .with_context(|| format!("cannot resolve {} relative to {}", anchored.path, base))?;
+        let path = path
+            .as_path()
+            .with_context(|| format!("not a file system path: {}", path))?;
+        Ok(convert::url_from_abs_path(path))
+    }
+
     pub(crate) fn url_file_version(&self, url: &Url) -> Option<i32> {
         let path = convert::vfs_path(url).ok()?;
         Some(*self.open_document_versions.read().get(&path)?)
@@ -147,17 +173,19 @@ impl Snapshot {
         let file_url = self.file_id_to_url(file_id);
         let _timer = timeit_with_telemetry!(TelemetryData::NativeDiagnostics { file_url });
 
-        let line_index = self.analysis.line_index(file_id).ok()?;
+        self.compute_native_diagnostics(file_id).ok()
+    }
+
+    fn compute_native_diagnostics(&self, file_id: FileId) -> Cancellable<Vec<Diagnostic>> {
+        let line_index = self.analysis.line_index(file_id)?;
         let url = file_id_to_url(&self.vfs.read(), file_id);
 
-        Some(
-            self.analysis
-                .diagnostics(&self.config.diagnostics(), file_id, false)
-                .ok()?
-                .into_iter()
-                .map(|d| convert::ide_to_lsp_diagnostic(&line_index, &url, &d))
-                .collect(),
-        )
+        Ok(self
+            .analysis
+            .diagnostics(&self.config.diagnostics(), file_id, false)?
+            .into_iter()
+            .map(|d| convert::ide_to_lsp_diagnostic(&line_index, &url, &d))
+            .collect())
     }
 
     pub fn eqwalizer_diagnostics(&self, file_id: FileId) -> Option<Vec<Diagnostic>> {
@@ -174,6 +202,16 @@ impl Snapshot {
             return Some(vec![]);
         }
 
+        // Nor if the project excludes it from diagnostics
+        let config = match self
+            .analysis
+            .diagnostics_config(&self.config.diagnostics(), file_id)
+            .ok()?
+        {
+            Some(config) => config,
+            None => return Some(vec![]),
+        };
+
         let line_index = self.analysis.line_index(file_id).ok()?;
 
         let diags = self
@@ -186,7 +224,15 @@ impl Snapshot {
                     .iter()
                     .flat_map(|(_, diags)| {
                         diags.iter().map(|d| {
-                            convert::eqwalizer_to_lsp_diagnostic(d, &line_index, eqwalizer_enabled)
+                            let mut diag = convert::eqwalizer_to_lsp_diagnostic(
+                                d,
+                                &line_index,
+                                eqwalizer_enabled,
+                            );
+                            if let Some(severity) = config.severity_override(&d.code) {
+                                diag.severity = Some(convert::diagnostic_severity(severity));
+                            }
+                            diag
                         })
                     })
                     .collect(),
@@ -207,19 +253,38 @@ impl Snapshot {
 
         let diags = &*self.analysis.edoc_diagnostics(file_id).ok()?;
 
-        Some(
-            diags
-                .into_iter()
-                .map(|(file_id, ds)| {
-                    (
-                        *file_id,
-                        ds.iter()
-                            .map(|d| convert::ide_to_lsp_diagnostic(&line_index, &url, d))
-                            .collect(),
-                    )
-                })
-                .collect(),
-        )
+        self.convert_diagnostics(diags, &line_index, &url)
+    }
+
+    /// Convert diagnostics not computed by `Analysis::diagnostics` to
+    /// LSP ones, applying the configuration of the file they are
+    /// reported for, combined with the one of its project.
+    fn convert_diagnostics(
+        &self,
+        diags: &[(FileId, Vec<diagnostics::Diagnostic>)],
+        line_index: &LineIndex,
+        url: &Url,
+    ) -> Option<Vec<(FileId, Vec<Diagnostic>)>> {
+        let config = self.config.diagnostics();
+        diags
+            .iter()
+            .map(|(file_id, ds)| {
+                let ds = match self.analysis.diagnostics_config(&config, *file_id).ok()? {
+                    Some(config) => ds
+                        .iter()
+                        .filter(|d| config.is_enabled(&d.code))
+                        .map(|d| {
+                            let mut diag = convert::ide_to_lsp_diagnostic(line_index, url, d);
+                            diag.severity = Some(convert::diagnostic_severity(config.severity(d)));
+                            diag
+                        })
+                        .collect(),
+                    // The project excludes the file from diagnostics
+                    None => vec![],
+                };
+                Some((*file_id, ds))
+            })
+            .collect()
     }
 
     pub fn erlang_service_diagnostics(
@@ -233,19 +298,76 @@ impl Snapshot {
 
         let diags = &*self.analysis.erlang_service_diagnostics(file_id).ok()?;
 
-        Some(
+        // Diagnostics computed natively for modules are not reported twice
+        let diags = if self.analysis.module_name(file_id).ok()?.is_some() {
+            let config = self
+                .analysis
+                .diagnostics_config(&self.config.diagnostics(), file_id)
+                .ok()?
+                .unwrap_or_else(|| self.config.diagnostics());
             diags
-                .into_iter()
-                .map(|(file_id, ds)| {
-                    (
-                        *file_id,
-                        ds.iter()
-                            .map(|d| convert::ide_to_lsp_diagnostic(&line_index, &url, d))
-                            .collect(),
-                    )
+                .iter()
+                .map(|(id, ds)| {
+                    let ds = ds
+                        .iter()
+                        .filter(|d| !config.is_computed_natively(d))
+                        .cloned()
+                        .collect();
+                    (*id, ds)
                 })
-                .collect(),
-        )
+                .collect()
+        } else {
+            diags.to_vec()
+        };
+
+        self.convert_diagnostics(&diags, &line_index, &url)
+    }
+
+    /// All diagnostics for a file, merged into a single list, as
+    /// reported by the pull diagnostics requests, with a result id
+    /// identifying them. The native diagnostics of open documents are
+    /// computed on demand, all others are the ones last computed in the
+    /// background, as otherwise published to clients not pulling them.
+    pub fn pull_diagnostics(&self, file_id: FileId) -> Cancellable<(String, Vec<Diagnostic>)> {
+        let opened = self
+            .url_file_version(&self.file_id_to_url(file_id))
+            .is_some();
+        let native = if opened {
+            Some(self.compute_native_diagnostics(file_id)?)
+        } else {
+            None
+        };
+
+        let collection = self.diagnostics.read();
+        let result_id = self
+            .diagnostics_generation
+            .result_id(opened, collection.revision(file_id));
+        let mut diagnostics = match native {
+            Some(native) => native,
+            None => collection.native.get(&file_id).cloned().unwrap_or_default(),
+        };
+        for cached in [
+            &collection.erlang_service,
+            &collection.eqwalizer,
+            &collection.edoc,
+        ] {
+            diagnostics.extend(cached.get(&file_id).into_iter().flatten().cloned());
+        }
+        Ok((result_id, diagnostics))
+    }
+
+    /// Files with diagnostics last computed in the background
+    pub(crate) fn diagnosed_files(&self) -> Vec<FileId> {
+        self.diagnostics.read().file_ids().collect()
+    }
+
+    pub(crate) fn opened_documents(&self) -> Vec<FileId> {
+        let vfs = self.vfs.read();
+        self.open_document_versions
+            .read()
+            .keys()
+            .filter_map(|path| vfs.file_id(path))
+            .collect()
     }
 
     pub fn get_project(&self, project_id: ProjectId) -> Option<Project> {
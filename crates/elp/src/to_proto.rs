@@ -23,12 +23,15 @@ use elp_ide::elp_ide_db::elp_base_db::FileId;
 use elp_ide::elp_ide_db::elp_base_db::FilePosition;
 use elp_ide::elp_ide_db::elp_base_db::FileRange;
 use elp_ide::elp_ide_db::rename::RenameError;
+use elp_ide::elp_ide_db::source_change::FileSystemEdit;
 use elp_ide::elp_ide_db::source_change::SourceChange;
 use elp_ide::elp_ide_db::LineIndex;
 use elp_ide::elp_ide_db::ReferenceCategory;
 use elp_ide::elp_ide_db::SymbolKind;
 use elp_ide::AnnotationKind;
 use elp_ide::Cancellable;
+use elp_ide::DocumentLink;
+use elp_ide::DocumentLinkTarget;
 use elp_ide::Fold;
 use elp_ide::FoldKind;
 use elp_ide::Highlight;
@@ -95,6 +98,17 @@ pub(crate) fn text_edit(
     lsp_types::TextEdit { range, new_text }
 }
 
+pub(crate) fn text_edit_vec(
+    line_index: &LineIndex,
+    line_endings: LineEndings,
+    text_edit: TextEdit,
+) -> Vec<lsp_types::TextEdit> {
+    text_edit
+        .into_iter()
+        .map(|indel| self::text_edit(line_index, line_endings, indel))
+        .collect()
+}
+
 pub(crate) fn url(snap: &Snapshot, file_id: FileId) -> lsp_types::Url {
     snap.file_id_to_url(file_id)
 }
@@ -145,7 +159,20 @@ pub(crate) fn workspace_edit(
             edits: edit.edits.into_iter().map(From::from).collect(),
         });
     }
-    let document_changes = lsp_types::DocumentChanges::Edits(edits);
+    let document_changes = if source_change.file_system_edits.is_empty() {
+        lsp_types::DocumentChanges::Edits(edits)
+    } else {
+        // Text edits refer to the files by their current name, so
+        // they have to be applied before any file is moved.
+        let mut operations: Vec<_> = edits
+            .into_iter()
+            .map(lsp_types::DocumentChangeOperation::Edit)
+            .collect();
+        for op in source_change.file_system_edits {
+            operations.extend(document_change_operations(snap, op)?);
+        }
+        lsp_types::DocumentChanges::Operations(operations)
+    };
     let workspace_edit = lsp_types::WorkspaceEdit {
         changes: None,
         document_changes: Some(document_changes),
@@ -154,6 +181,49 @@ pub(crate) fn workspace_edit(
     Ok(workspace_edit)
 }
 
+fn document_change_operations(
+    snap: &Snapshot,
+    file_system_edit: FileSystemEdit,
+) -> Result<Vec<lsp_types::DocumentChangeOperation>> {
+    let operations = match file_system_edit {
+        FileSystemEdit::CreateFile {
+            dst,
+            initial_contents,
+        } => {
+            let uri = snap.anchored_path(&dst)?;
+            let create = lsp_types::ResourceOp::Create(lsp_types::CreateFile {
+                uri: uri.clone(),
+                options: None,
+                annotation_id: None,
+            });
+            let contents = lsp_types::TextDocumentEdit {
+                text_document: lsp_types::OptionalVersionedTextDocumentIdentifier {
+                    uri,
+                    version: None,
+                },
+                edits: vec![lsp_types::OneOf::Left(lsp_types::TextEdit {
+                    range: lsp_types::Range::default(),
+                    new_text: initial_contents,
+                })],
+            };
+            vec![
+                lsp_types::DocumentChangeOperation::Op(create),
+                lsp_types::DocumentChangeOperation::Edit(contents),
+            ]
+        }
+        FileSystemEdit::MoveFile { src, dst } => {
+            let rename = lsp_types::ResourceOp::Rename(lsp_types::RenameFile {
+                old_uri: url(snap, src),
+                new_uri: snap.anchored_path(&dst)?,
+                options: None,
+                annotation_id: None,
+            });
+            vec![lsp_types::DocumentChangeOperation::Op(rename)]
+        }
+    };
+    Ok(operations)
+}
+
 pub(crate) fn code_action_kind(kind: AssistKind) -> lsp_types::CodeActionKind {
     match kind {
         AssistKind::None | AssistKind::Generate => lsp_types::CodeActionKind::EMPTY,
@@ -421,6 +491,23 @@ pub(crate) fn folding_range(line_index: &LineIndex, fold: Fold) -> lsp_types::Fo
     }
 }
 
+pub(crate) fn document_link(
+    snap: &Snapshot,
+    line_index: &LineIndex,
+    link: DocumentLink,
+) -> Option<lsp_types::DocumentLink> {
+    let target = match link.target {
+        DocumentLinkTarget::File(file_id) => url(snap, file_id),
+        DocumentLinkTarget::Url(url) => lsp_types::Url::parse(&url).ok()?,
+    };
+    Some(lsp_types::DocumentLink {
+        range: range(line_index, link.range),
+        target: Some(target),
+        tooltip: None,
+        data: None,
+    })
+}
+
 // ---------------------------------------------------------------------
 
 pub(crate) fn call_hierarchy_item(
@@ -442,6 +529,25 @@ pub(crate) fn call_hierarchy_item(
     })
 }
 
+pub(crate) fn type_hierarchy_item(
+    snap: &Snapshot,
+    target: NavigationTarget,
+) -> Result<lsp_ext::TypeHierarchyItem> {
+    let name = target.name.to_string();
+    let kind = symbol_kind(target.kind);
+    let (uri, range, selection_range) = location_info(snap, target)?;
+    Ok(lsp_ext::TypeHierarchyItem {
+        name,
+        kind,
+        tags: None,
+        detail: None,
+        uri,
+        range,
+        selection_range,
+        data: None,
+    })
+}
+
 pub(crate) fn signature_help(
     calls_info: Vec<SignatureHelp>,
     active_parameter: usize,
@@ -668,10 +774,81 @@ pub(crate) fn code_lens(
                 }
             };
         }
+        AnnotationKind::HasReferences { pos, data } => acc.push(references_code_lens(
+            snap,
+            annotation.range,
+            pos,
+            data,
+            lsp_ext::CodeLensKind::References,
+        )?),
+        AnnotationKind::HasUsages { pos, data } => acc.push(references_code_lens(
+            snap,
+            annotation.range,
+            pos,
+            data,
+            lsp_ext::CodeLensKind::Usages,
+        )?),
+        AnnotationKind::HasImplementations { pos, data } => acc.push(references_code_lens(
+            snap,
+            annotation.range,
+            pos,
+            data,
+            lsp_ext::CodeLensKind::Implementations,
+        )?),
     }
     Ok(())
 }
 
+/// A code lens showing the number of locations found for an annotation,
+/// which opens them when clicked. If the locations are not computed yet,
+/// the lens is left to be resolved.
+fn references_code_lens(
+    snap: &Snapshot,
+    annotation_range: TextRange,
+    pos: FilePosition,
+    data: Option<Vec<FileRange>>,
+    kind: lsp_ext::CodeLensKind,
+) -> Result<lsp_types::CodeLens> {
+    let line_index = snap.analysis.line_index(pos.file_id)?;
+    let lens_range = range(&line_index, annotation_range);
+    let position = lsp_types::TextDocumentPositionParams {
+        text_document: lsp_types::TextDocumentIdentifier {
+            uri: url(snap, pos.file_id),
+        },
+        position: position(&line_index, pos.offset),
+    };
+    match data {
+        None => Ok(lsp_types::CodeLens {
+            range: lens_range,
+            command: None,
+            data: Some(serde_json::value::to_value(lsp_ext::CodeLensData {
+                kind,
+                position,
+            })?),
+        }),
+        Some(ranges) => {
+            let locations = ranges
+                .into_iter()
+                .map(|file_range| location(snap, file_range))
+                .collect::<Cancellable<Vec<_>>>()?;
+            let noun = match kind {
+                lsp_ext::CodeLensKind::References => "reference",
+                lsp_ext::CodeLensKind::Usages => "usage",
+                lsp_ext::CodeLensKind::Implementations => "implementation",
+            };
+            let title = match locations.len() {
+                1 => format!("1 {noun}"),
+                n => format!("{n} {noun}s"),
+            };
+            Ok(lsp_types::CodeLens {
+                range: lens_range,
+                command: Some(command::show_references(title, position, locations)),
+                data: None,
+            })
+        }
+    }
+}
+
 pub(crate) mod command {
     use serde_json::to_value;
 
@@ -693,6 +870,25 @@ pub(crate) mod command {
         }
     }
 
+    /// Shows the given locations from a lens. The `elp.showReferences`
+    /// command is provided by the VS Code extension, which forwards it to
+    /// `editor.action.showReferences`; other clients need to register it.
+    pub(crate) fn show_references(
+        title: String,
+        position: lsp_types::TextDocumentPositionParams,
+        locations: Vec<lsp_types::Location>,
+    ) -> lsp_types::Command {
+        lsp_types::Command {
+            title,
+            command: "elp.showReferences".into(),
+            arguments: Some(vec![
+                to_value(position.text_document.uri).unwrap(),
+                to_value(position.position).unwrap(),
+                to_value(locations).unwrap(),
+            ]),
+        }
+    }
+
     pub(crate) fn trigger_parameter_hints() -> lsp_types::Command {
         lsp_types::Command {
             title: "triggerParameterHints".into(),
@@ -709,6 +905,7 @@ pub(crate) fn inlay_hint(
 ) -> Cancellable<lsp_types::InlayHint> {
     match inlay_hint.kind {
         InlayKind::Parameter => inlay_hint.label.append_str(":"),
+        InlayKind::Type => inlay_hint.label.prepend_str(":: "),
     }
 
     let (label, tooltip) = inlay_hint_label(snap, inlay_hint.label)?;
@@ -718,16 +915,19 @@ pub(crate) fn inlay_hint(
             // before annotated thing
             InlayKind::Parameter => position(line_index, inlay_hint.range.start()),
             // after annotated thing
-            // _ => position(line_index, inlay_hint.range.end()),
+            InlayKind::Type => position(line_index, inlay_hint.range.end()),
         },
         padding_left: Some(match inlay_hint.kind {
             InlayKind::Parameter => false,
+            InlayKind::Type => true,
         }),
         padding_right: Some(match inlay_hint.kind {
             InlayKind::Parameter => true,
+            InlayKind::Type => false,
         }),
         kind: match inlay_hint.kind {
             InlayKind::Parameter => Some(lsp_types::InlayHintKind::PARAMETER),
+            InlayKind::Type => Some(lsp_types::InlayHintKind::TYPE),
         },
         text_edits: None,
         data: None,
@@ -27,6 +27,7 @@ use timeout_readwrite::TimeoutReader;
 use timeout_readwrite::TimeoutWriter;
 
 use crate::EqwalizerDiagnostic;
+use crate::EqwalizerTypeInfo;
 
 #[derive(Deserialize, Debug)]
 pub enum EqWAlizerASTFormat {
@@ -62,6 +63,9 @@ pub enum MsgFromEqWAlizer {
     Done {
         diagnostics: FxHashMap<String, Vec<EqwalizerDiagnostic>>,
     },
+    TypeInfoReply {
+        type_info: Vec<EqwalizerTypeInfo>,
+    },
 }
 
 #[derive(Serialize, Debug)]
@@ -71,6 +75,7 @@ pub enum MsgToEqWAlizer {
     ELPExitingModule,
     GetAstBytesReply { ast_bytes_len: u32 },
     CannotCompleteRequest,
+    GetTypeInfo { module: String },
 }
 
 pub struct IpcHandle {
@@ -23,6 +23,7 @@ use std::process::ExitStatus;
 use std::sync::Arc;
 use std::time::Instant;
 
+use anyhow::bail;
 use anyhow::Context;
 use anyhow::Result;
 use ast::form::ExternalForm;
@@ -83,6 +84,15 @@ pub struct EqwalizerDiagnostic {
     pub explanation: Option<String>,
 }
 
+/// The type inferred by eqWAlizer for an expression or a variable
+#[derive(Debug, Deserialize, PartialEq, Eq, Clone)]
+pub struct EqwalizerTypeInfo {
+    #[serde(deserialize_with = "deserialize_text_range")]
+    pub range: TextRange,
+    #[serde(rename(deserialize = "type"))]
+    pub ty: String,
+}
+
 impl EqwalizerDiagnostics {
     pub fn combine(mut self, other: &Self) -> Self {
         match &mut self {
@@ -239,6 +249,26 @@ impl Eqwalizer {
         }
     }
 
+    /// The types inferred for the expressions and variables of the
+    /// module. eqWAlizer only records them when asked to, as it slows down
+    /// type checking, and sends them on request once the module is checked.
+    pub fn type_info(
+        &self,
+        build_info_path: &Path,
+        db: &dyn EqwalizerDiagnosticsDatabase,
+        project_id: ProjectId,
+        module: &str,
+    ) -> Result<Vec<EqwalizerTypeInfo>> {
+        let mut cmd = self.cmd();
+        cmd.arg("ipc");
+        cmd.arg(module);
+        cmd.env("EQWALIZER_IPC", "true");
+        cmd.env("EQWALIZER_USE_ELP_CONVERTED_AST", "true");
+        cmd.env("EQWALIZER_GATHER_TYPE_INFO", "true");
+        add_env(&mut cmd, build_info_path, None);
+        do_type_info(cmd, db, project_id, module)
+    }
+
     pub fn passthrough(
         &self,
         args: &[String],
@@ -265,81 +295,10 @@ fn do_typecheck(
         db.unwind_if_cancelled();
         match handle.receive()? {
             MsgFromEqWAlizer::GetAstBytes { module, format } => {
-                log::debug!(
-                    "received from eqwalizer: GetAstBytes for module {} (format = {:?})",
-                    module,
-                    format
-                );
-                let module_name = ModuleName::new(&module);
-                let ast = {
-                    match format {
-                        EqWAlizerASTFormat::RawForms => {
-                            db.get_erl_ast_bytes(project_id, module_name)
-                        }
-                        EqWAlizerASTFormat::ConvertedForms => {
-                            db.converted_ast_bytes(project_id, module_name)
-                        }
-                        EqWAlizerASTFormat::RawStub => {
-                            db.get_erl_stub_bytes(project_id, module_name)
-                        }
-                        EqWAlizerASTFormat::ConvertedStub => {
-                            db.converted_stub_bytes(project_id, module_name)
-                        }
-                        EqWAlizerASTFormat::ExpandedStub => {
-                            db.expanded_stub_bytes(project_id, module_name)
-                        }
-                        EqWAlizerASTFormat::ContractiveStub => {
-                            db.contractive_stub_bytes(project_id, module_name)
-                        }
-                        EqWAlizerASTFormat::CovariantStub => {
-                            db.covariant_stub_bytes(project_id, module_name)
-                        }
-                        EqWAlizerASTFormat::TransitiveStub => {
-                            db.transitive_stub_bytes(project_id, module_name)
-                        }
-                    }
-                };
-                match ast {
-                    Ok(ast_bytes) => {
-                        log::debug!(
-                            "sending to eqwalizer: GetAstBytesReply for module {}",
-                            module
-                        );
-                        let ast_bytes_len = ast_bytes.len().try_into()?;
-                        let reply = &MsgToEqWAlizer::GetAstBytesReply { ast_bytes_len };
-                        handle.send(reply)?;
-                        handle.receive_newline()?;
-                        handle.send_bytes(&ast_bytes)?;
-                    }
-                    Err(Error::ModuleNotFound(_)) => {
-                        log::debug!(
-                            "module not found, sending to eqwalizer: empty GetAstBytesReply for module {}",
-                            module
-                        );
-                        let ast_bytes_len = 0;
-                        let reply = &MsgToEqWAlizer::GetAstBytesReply { ast_bytes_len };
-                        handle.send(reply)?;
-                        handle.receive_newline()?;
-                    }
-                    Err(Error::ParseError) => {
-                        log::debug!(
-                            "parse error, sending to eqwalizer: CannotCompleteRequest for module {}",
-                            module
-                        );
-                        let reply = &MsgToEqWAlizer::CannotCompleteRequest;
-                        handle.send(reply)?;
-                        return Ok(EqwalizerDiagnostics::NoAst { module });
-                    }
-                    Err(err) => {
-                        log::debug!(
-                            "error {} sending to eqwalizer: CannotCompleteRequest for module {}",
-                            err,
-                            module
-                        );
-                        let reply = &MsgToEqWAlizer::CannotCompleteRequest;
-                        handle.send(reply)?;
-                        return Ok(EqwalizerDiagnostics::Error(err.to_string()));
-                    }
+                if let Some(diagnostics) =
+                    send_ast_bytes(&mut handle, db, project_id, module, format)?
+                {
+                    return Ok(diagnostics);
                 }
             }
             MsgFromEqWAlizer::EqwalizingStart { module } => db.eqwalizing_start(module),
@@ -361,6 +320,52 @@ fn do_typecheck(
     }
 }
 
+fn do_type_info(
+    mut cmd: CommandProxy,
+    db: &dyn EqwalizerDiagnosticsDatabase,
+    project_id: ProjectId,
+    module: &str,
+) -> Result<Vec<EqwalizerTypeInfo>, anyhow::Error> {
+    let mut handle = IpcHandle::from_command(&mut cmd)
+        .with_context(|| format!("starting eqWAlizer process: {:?}", cmd))?;
+    let _pctx = stdx::panic_context::enter(format!("\neqWAlizing with command: {:?}", cmd));
+    loop {
+        db.unwind_if_cancelled();
+        match handle.receive()? {
+            MsgFromEqWAlizer::GetAstBytes {
+                module: ast_module,
+                format,
+            } => match send_ast_bytes(&mut handle, db, project_id, ast_module, format)? {
+                Some(EqwalizerDiagnostics::NoAst { module }) => {
+                    bail!("no AST for module {}", module)
+                }
+                Some(EqwalizerDiagnostics::Error(err)) => bail!(err),
+                Some(EqwalizerDiagnostics::Diagnostics(_)) | None => (),
+            },
+            MsgFromEqWAlizer::Done { .. } => {
+                log::debug!("sending to eqwalizer: GetTypeInfo for module {}", module);
+                let request = &MsgToEqWAlizer::GetTypeInfo {
+                    module: module.to_string(),
+                };
+                handle.send(request)?;
+            }
+            MsgFromEqWAlizer::TypeInfoReply { type_info } => {
+                log::debug!(
+                    "received from eqwalizer: TypeInfoReply with length {}",
+                    type_info.len()
+                );
+                return Ok(type_info);
+            }
+            msg => {
+                log::warn!(
+                    "received unexpected message from eqwalizer, ignoring: {:?}",
+                    msg
+                )
+            }
+        }
+    }
+}
+
 fn shell_typecheck(
     mut cmd: CommandProxy,
     db: &dyn EqwalizerDiagnosticsDatabase,
@@ -431,81 +436,10 @@ fn get_module_diagnostics(
         db.unwind_if_cancelled();
         match handle.receive()? {
             MsgFromEqWAlizer::GetAstBytes { module, format } => {
-                log::debug!(
-                    "received from eqwalizer: GetAstBytes for module {} (format = {:?})",
-                    module,
-                    format
-                );
-                let module_name = ModuleName::new(&module);
-                let ast = {
-                    match format {
-                        EqWAlizerASTFormat::RawForms => {
-                            db.get_erl_ast_bytes(project_id, module_name)
-                        }
-                        EqWAlizerASTFormat::ConvertedForms => {
-                            db.converted_ast_bytes(project_id, module_name)
-                        }
-                        EqWAlizerASTFormat::RawStub => {
-                            db.get_erl_stub_bytes(project_id, module_name)
-                        }
-                        EqWAlizerASTFormat::ConvertedStub => {
-                            db.converted_stub_bytes(project_id, module_name)
-                        }
-                        EqWAlizerASTFormat::ExpandedStub => {
-                            db.expanded_stub_bytes(project_id, module_name)
-                        }
-                        EqWAlizerASTFormat::ContractiveStub => {
-                            db.contractive_stub_bytes(project_id, module_name)
-                        }
-                        EqWAlizerASTFormat::CovariantStub => {
-                            db.covariant_stub_bytes(project_id, module_name)
-                        }
-                        EqWAlizerASTFormat::TransitiveStub => {
-                            db.transitive_stub_bytes(project_id, module_name)
-                        }
-                    }
-                };
-                match ast {
-                    Ok(ast_bytes) => {
-                        log::debug!(
-                            "sending to eqwalizer: GetAstBytesReply for module {}",
-                            module
-                        );
-                        let ast_bytes_len = ast_bytes.len().try_into()?;
-                        let reply = &MsgToEqWAlizer::GetAstBytesReply { ast_bytes_len };
-                        handle.send(reply)?;
-                        handle.receive_newline()?;
-                        handle.send_bytes(&ast_bytes)?;
-                    }
-                    Err(Error::ModuleNotFound(_)) => {
-                        log::debug!(
-                            "module not found, sending to eqwalizer: empty GetAstBytesReply for module {}",
-                            module
-                        );
-                        let ast_bytes_len = 0;
-                        let reply = &MsgToEqWAlizer::GetAstBytesReply { ast_bytes_len };
-                        handle.send(reply)?;
-                        handle.receive_newline()?;
-                    }
-                    Err(Error::ParseError) => {
-                        log::debug!(
-                            "parse error, sending to eqwalizer: CannotCompleteRequest for module {}",
-                            module
-                        );
-                        let reply = &MsgToEqWAlizer::CannotCompleteRequest;
-                        handle.send(reply)?;
-                        return Ok(EqwalizerDiagnostics::NoAst { module });
-                    }
-                    Err(err) => {
-                        log::debug!(
-                            "error {} sending to eqwalizer: CannotCompleteRequest for module {}",
-                            err,
-                            module
-                        );
-                        let reply = &MsgToEqWAlizer::CannotCompleteRequest;
-                        handle.send(reply)?;
-                        return Ok(EqwalizerDiagnostics::Error(err.to_string()));
-                    }
+                if let Some(diagnostics) =
+                    send_ast_bytes(&mut handle, db, project_id, module, format)?
+                {
+                    return Ok(diagnostics);
                 }
             }
             MsgFromEqWAlizer::EqwalizingStart { module } => db.eqwalizing_start(module),
@@ -533,6 +467,80 @@ fn get_module_diagnostics(
     }
 }
 
+/// Replies to a `GetAstBytes` request from eqWAlizer. Returns the result
+/// of the session when the request cannot be completed.
+fn send_ast_bytes(
+    handle: &mut IpcHandle,
+    db: &dyn EqwalizerDiagnosticsDatabase,
+    project_id: ProjectId,
+    module: String,
+    format: EqWAlizerASTFormat,
+) -> Result<Option<EqwalizerDiagnostics>, anyhow::Error> {
+    log::debug!(
+        "received from eqwalizer: GetAstBytes for module {} (format = {:?})",
+        module,
+        format
+    );
+    let module_name = ModuleName::new(&module);
+    let ast = {
+        match format {
+            EqWAlizerASTFormat::RawForms => db.get_erl_ast_bytes(project_id, module_name),
+            EqWAlizerASTFormat::ConvertedForms => db.converted_ast_bytes(project_id, module_name),
+            EqWAlizerASTFormat::RawStub => db.get_erl_stub_bytes(project_id, module_name),
+            EqWAlizerASTFormat::ConvertedStub => db.converted_stub_bytes(project_id, module_name),
+            EqWAlizerASTFormat::ExpandedStub => db.expanded_stub_bytes(project_id, module_name),
+            EqWAlizerASTFormat::ContractiveStub => {
+                db.contractive_stub_bytes(project_id, module_name)
+            }
+            EqWAlizerASTFormat::CovariantStub => db.covariant_stub_bytes(project_id, module_name),
+            EqWAlizerASTFormat::TransitiveStub => db.transitive_stub_bytes(project_id, module_name),
+        }
+    };
+    match ast {
+        Ok(ast_bytes) => {
+            log::debug!(
+                "sending to eqwalizer: GetAstBytesReply for module {}",
+                module
+            );
+            let ast_bytes_len = ast_bytes.len().try_into()?;
+            let reply = &MsgToEqWAlizer::GetAstBytesReply { ast_bytes_len };
+            handle.send(reply)?;
+            handle.receive_newline()?;
+            handle.send_bytes(&ast_bytes)?;
+        }
+        Err(Error::ModuleNotFound(_)) => {
+            log::debug!(
+                "module not found, sending to eqwalizer: empty GetAstBytesReply for module {}",
+                module
+            );
+            let ast_bytes_len = 0;
+            let reply = &MsgToEqWAlizer::GetAstBytesReply { ast_bytes_len };
+            handle.send(reply)?;
+            handle.receive_newline()?;
+        }
+        Err(Error::ParseError) => {
+            log::debug!(
+                "parse error, sending to eqwalizer: CannotCompleteRequest for module {}",
+                module
+            );
+            let reply = &MsgToEqWAlizer::CannotCompleteRequest;
+            handle.send(reply)?;
+            return Ok(Some(EqwalizerDiagnostics::NoAst { module }));
+        }
+        Err(err) => {
+            log::debug!(
+                "error {} sending to eqwalizer: CannotCompleteRequest for module {}",
+                err,
+                module
+            );
+            let reply = &MsgToEqWAlizer::CannotCompleteRequest;
+            handle.send(reply)?;
+            return Ok(Some(EqwalizerDiagnostics::Error(err.to_string())));
+        }
+    }
+    Ok(None)
+}
+
 fn compute_eqwalizer_stats(
     db: &dyn EqwalizerDiagnosticsDatabase,
     project_id: ProjectId,
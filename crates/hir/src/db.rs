@@ -11,13 +11,16 @@ use std::sync::Arc;
 
 use elp_base_db::salsa;
 use elp_base_db::FileId;
+use elp_base_db::ProjectId;
 use elp_base_db::SourceDatabase;
 use elp_base_db::Upcast;
 use elp_syntax::ast;
 use fxhash::FxHashMap;
+use fxhash::FxHashSet;
 
 use crate::body::scope::FunctionScopes;
 use crate::body::DefineBody;
+use crate::deprecation;
 use crate::edoc;
 use crate::edoc::EdocHeader;
 use crate::include;
@@ -39,6 +42,8 @@ use crate::InFile;
 use crate::InFileAstPtr;
 use crate::IncludeAttributeId;
 use crate::MacroName;
+use crate::Name;
+use crate::NameArity;
 use crate::RecordBody;
 use crate::RecordId;
 use crate::ResolvedMacro;
@@ -116,6 +121,17 @@ pub trait MinDefDatabase:
     #[salsa::invoke(include::resolve)]
     fn resolve_include(&self, include_id: InFile<IncludeAttributeId>) -> Option<FileId>;
 
+    #[salsa::invoke(include::header_includers_query)]
+    fn header_includers(&self, project_id: ProjectId) -> Arc<FxHashMap<FileId, FxHashSet<FileId>>>;
+
+    /// The deprecations of OTP functions, described in the given
+    /// `otp_internal` module.
+    #[salsa::invoke(deprecation::otp_internal_deprecations_query)]
+    fn otp_internal_deprecations(
+        &self,
+        file_id: FileId,
+    ) -> Arc<FxHashMap<(Name, NameArity), String>>;
+
     #[salsa::invoke(macro_exp::resolve_query)]
     fn resolve_macro(&self, file_id: FileId, name: MacroName) -> Option<ResolvedMacro>;
 
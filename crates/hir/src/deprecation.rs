@@ -0,0 +1,73 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::sync::Arc;
+
+use elp_base_db::FileId;
+use fxhash::FxHashMap;
+
+use crate::db::MinDefDatabase;
+use crate::Expr;
+use crate::Literal;
+use crate::Name;
+use crate::NameArity;
+use crate::Pat;
+
+/// The deprecations of OTP functions, as described by the clauses of
+/// `otp_internal:obsolete/3` in the given file, which look like
+///
+/// ```erlang
+/// obsolete(crypto, rand_uniform, 2) ->
+///     {deprecated, "use rand:uniform/1 instead"};
+/// ```
+pub(crate) fn otp_internal_deprecations_query(
+    db: &dyn MinDefDatabase,
+    file_id: FileId,
+) -> Arc<FxHashMap<(Name, NameArity), String>> {
+    let mut res = FxHashMap::default();
+    let def_map = db.def_map(file_id);
+    let obsolete =
+        match def_map.get_function(&NameArity::new(Name::from_erlang_service("obsolete"), 3)) {
+            Some(def) => def,
+            None => return Arc::new(res),
+        };
+    let body = obsolete.in_function_body(db, ());
+    let atom_name = |atom| db.lookup_atom(atom);
+    for (_, clause) in body.clauses() {
+        let (module, name, arity) = match clause.pats.as_slice() {
+            [module, name, arity] => (&body[*module], &body[*name], &body[*arity]),
+            _ => continue,
+        };
+        let (module, name, arity) = match (module, name, arity) {
+            (
+                Pat::Literal(Literal::Atom(module)),
+                Pat::Literal(Literal::Atom(name)),
+                Pat::Literal(Literal::Integer(arity)),
+            ) => (atom_name(*module), atom_name(*name), *arity),
+            _ => continue,
+        };
+        let description = match clause.exprs.last().map(|expr| &body[*expr]) {
+            Some(Expr::Tuple { exprs }) => match exprs.as_slice() {
+                [tag, description, ..] => match (&body[*tag], &body[*description]) {
+                    (
+                        Expr::Literal(Literal::Atom(tag)),
+                        Expr::Literal(Literal::String(description)),
+                    ) if atom_name(*tag) == "deprecated" => description.clone(),
+                    _ => continue,
+                },
+                _ => continue,
+            },
+            _ => continue,
+        };
+        if let Ok(arity) = u32::try_from(arity) {
+            res.insert((module, NameArity::new(name, arity)), description);
+        }
+    }
+    Arc::new(res)
+}
@@ -114,6 +114,10 @@ impl FormList {
         self.data.exports.iter()
     }
 
+    pub fn imports(&self) -> impl Iterator<Item = (ImportId, &Import)> {
+        self.data.imports.iter()
+    }
+
     pub fn specs(&self) -> impl Iterator<Item = (SpecId, &Spec)> {
         self.data.specs.iter()
     }
@@ -126,6 +130,14 @@ impl FormList {
         &self.data.pp_directives
     }
 
+    pub fn defines(&self) -> impl Iterator<Item = (DefineId, &Define)> {
+        self.data.defines.iter()
+    }
+
+    pub fn pp_conditions(&self) -> impl Iterator<Item = (PPConditionId, &PPCondition)> {
+        self.data.pp_conditions.iter()
+    }
+
     /// Returns the first -module attribute in the file
     pub fn module_attribute(&self) -> Option<&ModuleAttribute> {
         self.data
@@ -150,6 +162,13 @@ impl FormList {
         self.data.compile_options.iter()
     }
 
+    /// Returns an iterator over the -deprecated attributes in the file
+    pub fn deprecated_attributes(
+        &self,
+    ) -> impl Iterator<Item = (DeprecatedAttributeId, &DeprecatedAttribute)> {
+        self.data.deprecates.iter()
+    }
+
     pub fn find_form(&self, form: &ast::Form) -> Option<FormIdx> {
         self.map_back.get(&AstPtr::new(form)).copied()
     }
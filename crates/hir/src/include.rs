@@ -10,10 +10,15 @@
 use std::sync::Arc;
 
 use elp_base_db::FileId;
+use elp_base_db::ProjectId;
 use elp_base_db::SourceRoot;
 use elp_base_db::SourceRootId;
+use fxhash::FxHashMap;
+use fxhash::FxHashSet;
 
 use crate::db::MinDefDatabase;
+use crate::File;
+use crate::FileKind;
 use crate::InFile;
 use crate::IncludeAttribute;
 use crate::IncludeAttributeId;
@@ -32,6 +37,42 @@ pub(crate) fn resolve(
     IncludeCtx::new(db, include_id.file_id).resolve(include_id.value)
 }
 
+/// The files including each header of the project, either directly or
+/// through other headers.
+pub(crate) fn header_includers_query(
+    db: &dyn MinDefDatabase,
+    project_id: ProjectId,
+) -> Arc<FxHashMap<FileId, FxHashSet<FileId>>> {
+    let mut direct: FxHashMap<FileId, Vec<FileId>> = FxHashMap::default();
+    for &source_root_id in &db.project_data(project_id).source_roots {
+        for file_id in db.source_root(source_root_id).iter() {
+            if (File { file_id }).kind(db.upcast()) == FileKind::Other {
+                continue;
+            }
+            for (idx, _) in db.file_form_list(file_id).includes() {
+                if let Some(header) = db.resolve_include(InFile::new(file_id, idx)) {
+                    direct.entry(header).or_default().push(file_id);
+                }
+            }
+        }
+    }
+
+    let mut res = FxHashMap::default();
+    for &header in direct.keys() {
+        let mut includers = FxHashSet::default();
+        let mut todo = vec![header];
+        while let Some(file_id) = todo.pop() {
+            for &includer in direct.get(&file_id).into_iter().flatten() {
+                if includer != header && includers.insert(includer) {
+                    todo.push(includer);
+                }
+            }
+        }
+        res.insert(header, includers);
+    }
+    Arc::new(res)
+}
+
 impl<'a> IncludeCtx<'a> {
     fn new(db: &'a dyn MinDefDatabase, file_id: FileId) -> Self {
         let source_root_id = db.file_source_root(file_id);
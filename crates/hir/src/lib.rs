@@ -14,6 +14,7 @@ use elp_syntax::ast;
 mod body;
 pub mod db;
 mod def_map;
+mod deprecation;
 mod diagnostics;
 pub mod edoc;
 mod expr;
@@ -81,6 +82,10 @@ pub use form_list::CompileOption;
 pub use form_list::CompileOptionId;
 pub use form_list::Define;
 pub use form_list::DefineId;
+pub use form_list::DeprecatedAttribute;
+pub use form_list::DeprecatedAttributeId;
+pub use form_list::DeprecatedDesc;
+pub use form_list::DeprecatedFa;
 pub use form_list::Export;
 pub use form_list::ExportId;
 pub use form_list::FaEntry;
@@ -232,5 +232,8 @@ pub mod known {
         nowarn_missing_spec,
         warn_missing_spec_all,
         nowarn_missing_spec_all,
+        nowarn_unused_function,
+        nowarn_unused_record,
+        nowarn_unused_type,
     );
 }
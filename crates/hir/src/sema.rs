@@ -163,6 +163,32 @@ impl<'db> Semantic<'db> {
         })
     }
 
+    /// All the modules in the project of `from_file` which declare
+    /// `behaviour` in a `-behaviour` attribute.
+    pub fn behaviour_implementors(&self, from_file: FileId, behaviour: &Module) -> Vec<Module> {
+        let behaviour_name = behaviour.name(self.db);
+        let source_root_id = self.db.file_source_root(from_file);
+        let project_id = match self.db.app_data(source_root_id) {
+            Some(app_data) => app_data.project_id,
+            None => return Vec::default(),
+        };
+        let module_index = self.db.module_index(project_id);
+        let mut implementors: Vec<Module> = module_index
+            .iter_own()
+            .filter(|(_name, _source, file_id)| {
+                self.db
+                    .file_form_list(*file_id)
+                    .behaviour_attributes()
+                    .any(|(_idx, behaviour)| behaviour.name == behaviour_name)
+            })
+            .map(|(_name, _source, file_id)| Module {
+                file: File { file_id },
+            })
+            .collect();
+        implementors.sort_by_key(|module| module.file.file_id);
+        implementors
+    }
+
     pub fn file_edoc_comments(
         &self,
         file_id: FileId,
@@ -8,15 +8,24 @@
  */
 
 use elp_ide_db::elp_base_db::FileId;
+use elp_ide_db::elp_base_db::FilePosition;
+use elp_ide_db::elp_base_db::FileRange;
+use elp_ide_db::find_best_token;
 use elp_ide_db::RootDatabase;
+use elp_ide_db::SymbolClass;
+use elp_syntax::ast;
+use elp_syntax::AstNode;
 use elp_syntax::TextRange;
+use hir::Semantic;
 
+use crate::handlers::goto_implementation::goto_implementation;
 use crate::runnables::runnables;
 use crate::runnables::Runnable;
 
 // Feature: Annotations
 //
-// Provides user with annotations above items (e.g. for running tests)
+// Provides user with annotations above items (e.g. for running tests, or
+// showing the number of references to a function)
 //
 #[derive(Debug)]
 pub struct Annotation {
@@ -27,9 +36,39 @@ pub struct Annotation {
 #[derive(Debug)]
 pub enum AnnotationKind {
     Runnable(Runnable),
+    /// The references to a function.
+    HasReferences {
+        pos: FilePosition,
+        data: Option<Vec<FileRange>>,
+    },
+    /// The usages of an exported type.
+    HasUsages {
+        pos: FilePosition,
+        data: Option<Vec<FileRange>>,
+    },
+    /// The implementations of a callback.
+    HasImplementations {
+        pos: FilePosition,
+        data: Option<Vec<FileRange>>,
+    },
 }
 
-pub(crate) fn annotations(db: &RootDatabase, file_id: FileId) -> Vec<Annotation> {
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AnnotationConfig {
+    /// Annotate functions and exported types with their references.
+    pub references: bool,
+    /// Annotate callbacks with their implementations.
+    pub implementations: bool,
+}
+
+/// Returns the annotations of the file. The `data` of references and
+/// implementations is left empty, as it is expensive to compute, and is
+/// filled in by `resolve_annotation`.
+pub(crate) fn annotations(
+    db: &RootDatabase,
+    config: &AnnotationConfig,
+    file_id: FileId,
+) -> Vec<Annotation> {
     let mut annotations = Vec::default();
 
     for runnable in runnables(db, file_id) {
@@ -39,30 +78,166 @@ pub(crate) fn annotations(db: &RootDatabase, file_id: FileId) -> Vec<Annotation>
             kind: AnnotationKind::Runnable(runnable),
         });
     }
+
+    let sema = Semantic::new(db);
+    let def_map = sema.def_map(file_id);
+    let mut symbol_annotations = Vec::new();
+    if config.references {
+        for (_, def) in def_map.get_functions() {
+            if def.file.file_id != file_id {
+                continue;
+            }
+            let name = def.source(db).clauses().find_map(|clause| match clause {
+                ast::FunctionOrMacroClause::FunctionClause(clause) => clause.name(),
+                ast::FunctionOrMacroClause::MacroCallExpr(_) => None,
+            });
+            if let Some(name) = name {
+                let range = name.syntax().text_range();
+                let pos = FilePosition {
+                    file_id,
+                    offset: range.start(),
+                };
+                symbol_annotations.push(Annotation {
+                    range,
+                    kind: AnnotationKind::HasReferences { pos, data: None },
+                });
+            }
+        }
+        for (_, def) in def_map.get_types() {
+            if def.file.file_id != file_id || !def.exported {
+                continue;
+            }
+            let name = def
+                .source(db)
+                .type_name()
+                .and_then(|type_name| type_name.name());
+            if let Some(name) = name {
+                let range = name.syntax().text_range();
+                let pos = FilePosition {
+                    file_id,
+                    offset: range.start(),
+                };
+                symbol_annotations.push(Annotation {
+                    range,
+                    kind: AnnotationKind::HasUsages { pos, data: None },
+                });
+            }
+        }
+    }
+    if config.implementations {
+        for (_, def) in def_map.get_callbacks() {
+            if def.file.file_id != file_id {
+                continue;
+            }
+            if let Some(name) = def.source(db).fun() {
+                let range = name.syntax().text_range();
+                let pos = FilePosition {
+                    file_id,
+                    offset: range.start(),
+                };
+                symbol_annotations.push(Annotation {
+                    range,
+                    kind: AnnotationKind::HasImplementations { pos, data: None },
+                });
+            }
+        }
+    }
+    symbol_annotations.sort_by_key(|annotation| annotation.range.start());
+    annotations.extend(symbol_annotations);
     annotations
 }
 
+/// Computes the `data` of an annotation returned by `annotations`.
+pub(crate) fn resolve_annotation(db: &RootDatabase, mut annotation: Annotation) -> Annotation {
+    match &mut annotation.kind {
+        AnnotationKind::Runnable(_) => {}
+        AnnotationKind::HasReferences { pos, data } | AnnotationKind::HasUsages { pos, data } => {
+            *data = Some(find_references(db, *pos).unwrap_or_default());
+        }
+        AnnotationKind::HasImplementations { pos, data } => {
+            *data = Some(
+                goto_implementation(db, *pos)
+                    .map(|targets| targets.info.iter().map(|nav| nav.file_range()).collect())
+                    .unwrap_or_default(),
+            );
+        }
+    }
+    annotation
+}
+
+/// The direct references to the function or type defined at the position,
+/// excluding exports and specs.
+fn find_references(db: &RootDatabase, pos: FilePosition) -> Option<Vec<FileRange>> {
+    let sema = Semantic::new(db);
+    let token = find_best_token(&sema, pos)?;
+    let def = match SymbolClass::classify(&sema, token)? {
+        SymbolClass::Definition(def) => def,
+        SymbolClass::Reference { .. } => return None,
+    };
+    let usages = def.usages(&sema).direct_only().all();
+    Some(
+        usages
+            .into_iter()
+            .flat_map(|(file_id, names)| {
+                names.into_iter().map(move |name| FileRange {
+                    file_id,
+                    range: name.syntax().text_range(),
+                })
+            })
+            .collect(),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use elp_ide_db::elp_base_db::FileRange;
     use stdx::trim_indent;
 
     use crate::fixture;
+    use crate::AnnotationConfig;
     use crate::AnnotationKind;
 
     #[track_caller]
     fn check(fixture: &str) {
+        check_with_config(AnnotationConfig::default(), fixture)
+    }
+
+    #[track_caller]
+    fn check_with_config(config: AnnotationConfig, fixture: &str) {
         let (analysis, pos, mut annotations) = fixture::annotations(trim_indent(fixture).as_str());
-        let actual_annotations = analysis.annotations(pos.file_id).unwrap();
+        let actual_annotations = analysis.annotations(&config, pos.file_id).unwrap();
         let mut actual = Vec::new();
         for annotation in actual_annotations {
-            match annotation.kind {
+            let range = annotation.range;
+            let count = |data: Option<Vec<FileRange>>| data.map_or(0, |data| data.len());
+            match analysis.resolve_annotation(annotation).unwrap().kind {
                 AnnotationKind::Runnable(runnable) => {
                     let file_id = runnable.nav.file_id;
                     let range = runnable.nav.focus_range.unwrap();
                     let text = runnable.nav.name;
                     actual.push((FileRange { file_id, range }, text.to_string()));
                 }
+                AnnotationKind::HasReferences { pos, data } => actual.push((
+                    FileRange {
+                        file_id: pos.file_id,
+                        range,
+                    },
+                    format!("{} references", count(data)),
+                )),
+                AnnotationKind::HasUsages { pos, data } => actual.push((
+                    FileRange {
+                        file_id: pos.file_id,
+                        range,
+                    },
+                    format!("{} usages", count(data)),
+                )),
+                AnnotationKind::HasImplementations { pos, data } => actual.push((
+                    FileRange {
+                        file_id: pos.file_id,
+                        range,
+                    },
+                    format!("{} implementations", count(data)),
+                )),
             }
         }
         let cmp = |(frange, text): &(FileRange, String)| {
@@ -98,4 +273,36 @@ main() ->
             "#,
         );
     }
+
+    #[test]
+    fn annotations_references() {
+        check_with_config(
+            AnnotationConfig {
+                references: true,
+                implementations: true,
+            },
+            r#"
+//- /src/main.erl
+    ~
+    -module(main).
+    -export([main/0, helper/1]).
+    -export_type([t/0]).
+    -callback init(term()) -> ok.
+ %%           ^^^^ 1 implementations
+    -type t() :: ok.
+ %%       ^ 2 usages
+    -type local() :: ok.
+    main() -> helper(1), helper(2).
+ %% ^^^^ 0 references
+    helper(X) -> X.
+ %% ^^^^^^ 2 references
+//- /src/impl.erl
+-module(impl).
+-behaviour(main).
+-export([init/1]).
+-spec init(main:t()) -> main:t().
+init(_) -> ok.
+            "#,
+        );
+    }
 }
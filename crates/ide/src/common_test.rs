@@ -102,7 +102,7 @@ pub fn unreachable_test(diagnostics: &mut Vec<Diagnostic>, sema: &Semantic, file
     }
 }
 
-fn runnable_names(sema: &Semantic, file_id: FileId) -> Result<FxHashSet<NameArity>, ()> {
+pub(crate) fn runnable_names(sema: &Semantic, file_id: FileId) -> Result<FxHashSet<NameArity>, ()> {
     runnables(sema, file_id).map(|runnables| {
         runnables
             .into_iter()
@@ -0,0 +1,177 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use elp_ide_db::elp_base_db::FileId;
+use elp_ide_db::elp_base_db::ProjectId;
+use elp_ide_db::elp_base_db::SourceDatabase;
+use elp_ide_db::AtomIndexDatabase;
+use elp_ide_db::RootDatabase;
+use fxhash::FxHashSet;
+use hir::db::MinDefDatabase;
+
+/// The files of the project whose diagnostics may change along with the
+/// given files, including those files: the files including them, directly
+/// or through other headers, the files mentioning the modules they define,
+/// and the modules they refer to. The modules they referred to before
+/// changing, which cannot be found from them any more, are given as
+/// `referenced`. Mentions are an over-approximation, narrowed down to the
+/// files using the module name from the project atom index.
+pub(crate) fn dependent_files(
+    db: &RootDatabase,
+    project_id: ProjectId,
+    changed: &[FileId],
+    referenced: &[FileId],
+) -> Vec<FileId> {
+    let module_index = db.module_index(project_id);
+    let project_atoms = db.project_atoms(project_id);
+    let includers = db.header_includers(project_id);
+    let mut res: FxHashSet<FileId> = changed.iter().copied().collect();
+    res.extend(referenced);
+    for &file_id in changed {
+        if let Some(module) = module_index.module_for_file(file_id) {
+            res.extend(project_atoms.files_with(module.as_str()));
+        }
+        res.extend(referenced_files(db, file_id));
+        res.extend(includers.get(&file_id).into_iter().flatten());
+    }
+
+    let mut res: Vec<FileId> = res.into_iter().collect();
+    res.sort();
+    res
+}
+
+/// The modules of the project the file refers to, whose diagnostics may
+/// depend on it, such as those about their unused exported functions.
+pub(crate) fn referenced_files(db: &RootDatabase, file_id: FileId) -> Vec<FileId> {
+    let project_id = match db.app_data(db.file_source_root(file_id)) {
+        Some(app_data) => app_data.project_id,
+        None => return vec![],
+    };
+    let module_index = db.module_index(project_id);
+    db.file_atoms(file_id)
+        .atoms()
+        .filter_map(|atom| module_index.file_for_module(atom.as_str()))
+        .filter(|&module_file_id| module_file_id != file_id)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use elp_ide_db::elp_base_db::fixture::WithFixture;
+    use expect_test::expect;
+    use expect_test::Expect;
+
+    use super::*;
+
+    #[track_caller]
+    fn check(fixture: &str, changed: &[&str], referenced: &[&str], expect: Expect) {
+        let (db, fixture) = RootDatabase::with_fixture(fixture);
+        let path = |file_id: FileId| {
+            db.source_root(db.file_source_root(file_id))
+                .path_for_file(&file_id)
+                .unwrap()
+                .to_string()
+        };
+        let files = |paths: &[&str]| -> Vec<FileId> {
+            fixture
+                .files
+                .iter()
+                .copied()
+                .filter(|file_id| paths.contains(&path(*file_id).as_str()))
+                .collect()
+        };
+        let changed = files(changed);
+        let project_id = db
+            .app_data(db.file_source_root(changed[0]))
+            .unwrap()
+            .project_id;
+        let actual = dependent_files(&db, project_id, &changed, &files(referenced))
+            .into_iter()
+            .map(|file_id| format!("{}\n", path(file_id)))
+            .collect::<String>();
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn module_users() {
+        check(
+            r#"
+//- /src/main.erl
+-module(main).
+-export([main/0]).
+main() -> other:go().
+//- /src/other.erl
+-module(other).
+-export([go/0]).
+go() -> ok.
+//- /src/unrelated.erl
+-module(unrelated).
+"#,
+            &["/src/other.erl"],
+            &[],
+            expect![[r#"
+                /src/main.erl
+                /src/other.erl
+            "#]],
+        );
+    }
+
+    #[test]
+    fn referenced_modules() {
+        check(
+            r#"
+//- /src/main.erl
+-module(main).
+-export([main/0]).
+main() -> other:go().
+//- /src/other.erl
+-module(other).
+-export([go/0]).
+go() -> ok.
+//- /src/previous.erl
+-module(previous).
+-export([go/0]).
+go() -> ok.
+//- /src/unrelated.erl
+-module(unrelated).
+"#,
+            &["/src/main.erl"],
+            &["/src/previous.erl"],
+            expect![[r#"
+                /src/main.erl
+                /src/other.erl
+                /src/previous.erl
+            "#]],
+        );
+    }
+
+    #[test]
+    fn header_includers() {
+        check(
+            r#"
+//- /src/main.erl
+-module(main).
+-include("outer.hrl").
+//- /src/outer.hrl
+-include("inner.hrl").
+//- /src/inner.hrl
+-define(X, 1).
+//- /src/unrelated.erl
+-module(unrelated).
+"#,
+            &["/src/inner.hrl"],
+            &[],
+            expect![[r#"
+                /src/main.erl
+                /src/outer.hrl
+                /src/inner.hrl
+            "#]],
+        );
+    }
+}
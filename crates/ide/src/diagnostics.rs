@@ -15,7 +15,10 @@ use elp_ide_assists::AssistId;
 use elp_ide_assists::AssistKind;
 use elp_ide_db::assists::Assist;
 use elp_ide_db::docs::DocDatabase;
+use elp_ide_db::elp_base_db::AppData;
 use elp_ide_db::elp_base_db::FileId;
+use elp_ide_db::elp_base_db::ProjectData;
+use elp_ide_db::elp_base_db::VfsPath;
 use elp_ide_db::erlang_service;
 use elp_ide_db::erlang_service::DiagnosticLocation;
 use elp_ide_db::erlang_service::Location;
@@ -27,6 +30,7 @@ use elp_ide_db::ErlAstDatabase;
 use elp_ide_db::LineCol;
 use elp_ide_db::LineIndex;
 use elp_ide_db::LineIndexDatabase;
+use elp_project_model::buck::ProjectDiagnosticsConfig;
 use elp_syntax::algo;
 use elp_syntax::ast;
 use elp_syntax::ast::AstNode;
@@ -54,6 +58,8 @@ use crate::RootDatabase;
 use crate::SourceDatabase;
 
 mod application_env;
+mod callback_return;
+mod deprecated_function;
 mod effect_free_statement;
 mod head_mismatch;
 // @fb-only: mod meta_only;
@@ -61,12 +67,18 @@ mod missing_compile_warn_missing_spec;
 mod misspelled_attribute;
 mod module_mismatch;
 mod mutable_variable;
+mod receive_in_callback;
 mod redundant_assignment;
 mod replace_call;
 mod trivial_match;
+mod undefined_function;
+mod unsafe_atom_creation;
+mod unused_exported_function;
+mod unused_function;
 mod unused_function_args;
 mod unused_include;
 mod unused_macro;
+mod unused_record_and_type;
 mod unused_record_field;
 
 #[derive(Debug, Clone)]
@@ -201,10 +213,11 @@ impl fmt::Display for Diagnostic {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Severity {
     Error,
     Warning,
+    Information,
     // `WeakWarning` maps onto a Notice warning when used in the LSP
     // environment, and in VS Code this means it does not show up in
     // the problems pane, has an unobtrusive underline, but does show
@@ -212,6 +225,35 @@ pub enum Severity {
     WeakWarning,
 }
 
+impl FromStr for Severity {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "error" => Ok(Severity::Error),
+            "warning" => Ok(Severity::Warning),
+            "info" => Ok(Severity::Information),
+            "hint" => Ok(Severity::WeakWarning),
+            _ => Err(format!("Unknown Severity: '{s}'")),
+        }
+    }
+}
+
+impl Severity {
+    fn level(&self) -> u8 {
+        match self {
+            Severity::Error => 3,
+            Severity::Warning => 2,
+            Severity::Information => 1,
+            Severity::WeakWarning => 0,
+        }
+    }
+
+    /// Whether this severity is the same as or more severe than `threshold`.
+    pub fn is_at_least(&self, threshold: Severity) -> bool {
+        self.level() >= threshold.level()
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Hash, EnumIter)]
 // pub struct DiagnosticCode(pub String);
 pub enum DiagnosticCode {
@@ -234,6 +276,18 @@ pub enum DiagnosticCode {
     ApplicationGetEnv,
     MissingCompileWarnMissingSpec,
     MisspelledAttribute,
+    DeprecatedFunction,
+    UnusedExportedFunction,
+    UndefinedModule,
+    UndefinedFunction,
+    UnexportedFunction,
+    UnusedFunction,
+    UnusedRecord,
+    UnusedType,
+    UnsafeAtomCreation,
+    InvalidCallbackReturn,
+    ReceiveWithoutAfter,
+    UnmatchedReceive,
 
     // Wrapper for erlang service diagnostic codes
     ErlangService(String),
@@ -270,6 +324,18 @@ impl DiagnosticCode {
             DiagnosticCode::ApplicationGetEnv => "W0011".to_string(),   // application_get_env
             DiagnosticCode::MissingCompileWarnMissingSpec => "W0012".to_string(),
             DiagnosticCode::MisspelledAttribute => "W0013".to_string(), // misspelled-attribute
+            DiagnosticCode::DeprecatedFunction => "W0014".to_string(),  // deprecated-function
+            DiagnosticCode::UnusedExportedFunction => "W0015".to_string(), // unused-exported-function
+            DiagnosticCode::UndefinedModule => "W0016".to_string(),        // undefined-module
+            DiagnosticCode::UndefinedFunction => "W0017".to_string(),      // undefined-function
+            DiagnosticCode::UnexportedFunction => "W0018".to_string(),     // unexported-function
+            DiagnosticCode::UnusedFunction => "W0019".to_string(),         // unused-function
+            DiagnosticCode::UnusedRecord => "W0020".to_string(),           // unused-record
+            DiagnosticCode::UnusedType => "W0021".to_string(),             // unused-type
+            DiagnosticCode::UnsafeAtomCreation => "W0022".to_string(),     // unsafe-atom-creation
+            DiagnosticCode::InvalidCallbackReturn => "W0023".to_string(), // invalid-callback-return
+            DiagnosticCode::ReceiveWithoutAfter => "W0024".to_string(),   // receive-without-after
+            DiagnosticCode::UnmatchedReceive => "W0025".to_string(),      // unmatched-receive
             DiagnosticCode::ErlangService(c) => c.to_string(),
             DiagnosticCode::AdHoc(c) => format!("ad-hoc: {c}").to_string(),
             // @fb-only: DiagnosticCode::MetaOnly(c) => c.as_code(),
@@ -300,12 +366,49 @@ impl DiagnosticCode {
             }
             DiagnosticCode::ApplicationGetEnv => "application_get_env".to_string(),
             DiagnosticCode::MisspelledAttribute => "misspelled_attribute".to_string(),
+            DiagnosticCode::DeprecatedFunction => "deprecated_function".to_string(),
+            DiagnosticCode::UnusedExportedFunction => "unused_exported_function".to_string(),
+            DiagnosticCode::UndefinedModule => "undefined_module".to_string(),
+            DiagnosticCode::UndefinedFunction => "undefined_function".to_string(),
+            DiagnosticCode::UnexportedFunction => "unexported_function".to_string(),
+            DiagnosticCode::UnusedFunction => "unused_function".to_string(),
+            DiagnosticCode::UnusedRecord => "unused_record".to_string(),
+            DiagnosticCode::UnusedType => "unused_type".to_string(),
+            DiagnosticCode::UnsafeAtomCreation => "unsafe_atom_creation".to_string(),
+            DiagnosticCode::InvalidCallbackReturn => "invalid_callback_return".to_string(),
+            DiagnosticCode::ReceiveWithoutAfter => "receive_without_after".to_string(),
+            DiagnosticCode::UnmatchedReceive => "unmatched_receive".to_string(),
             DiagnosticCode::ErlangService(c) => c.to_string(),
             DiagnosticCode::AdHoc(c) => format!("ad-hoc: {c}").to_string(),
             // @fb-only: DiagnosticCode::MetaOnly(c) => c.as_label(),
         }
     }
 
+    /// Whether the diagnostic is only computed when explicitly enabled.
+    /// The reason is given for each group of codes.
+    pub fn is_opt_in(&self) -> bool {
+        matches!(
+            self,
+            // Searches the whole project
+            DiagnosticCode::UnusedExportedFunction
+                // Modules generated at build time, or only loaded at
+                // runtime, are not in the project model and would be
+                // reported as undefined
+                | DiagnosticCode::UndefinedModule
+                | DiagnosticCode::UndefinedFunction
+                | DiagnosticCode::UnexportedFunction
+                // Also reported by the Erlang service, as L1230, L1260
+                // and L1296, which are left out once these are enabled
+                | DiagnosticCode::UnusedFunction
+                | DiagnosticCode::UnusedRecord
+                | DiagnosticCode::UnusedType
+                // Only relevant to processes which must keep serving
+                // requests, such as OTP behaviour callbacks
+                | DiagnosticCode::ReceiveWithoutAfter
+                | DiagnosticCode::UnmatchedReceive
+        )
+    }
+
     pub fn maybe_from_string(s: &String) -> Option<DiagnosticCode> {
         if let Some(r) = DIAGNOSTIC_CODE_LOOKUPS.get(s) {
             Some(r.clone())
@@ -386,6 +489,13 @@ impl<F> AdhocSemanticDiagnostics for F where
 pub struct DiagnosticsConfig<'a> {
     pub disable_experimental: bool,
     disabled: FxHashSet<DiagnosticCode>,
+    /// Diagnostics which are not computed unless explicitly enabled,
+    /// see `DiagnosticCode::is_opt_in`.
+    enabled: FxHashSet<DiagnosticCode>,
+    severity_overrides: FxHashMap<DiagnosticCode, Severity>,
+    /// Severity overrides for diagnostics whose codes are not ELP ones,
+    /// such as eqWAlizer's.
+    other_severity_overrides: FxHashMap<String, Severity>,
     pub adhoc_semantic_diagnostics: Vec<&'a dyn AdhocSemanticDiagnostics>,
 }
 
@@ -398,6 +508,9 @@ impl<'a> DiagnosticsConfig<'a> {
         DiagnosticsConfig {
             disable_experimental,
             disabled,
+            enabled: FxHashSet::default(),
+            severity_overrides: FxHashMap::default(),
+            other_severity_overrides: FxHashMap::default(),
             adhoc_semantic_diagnostics,
         }
     }
@@ -406,6 +519,111 @@ impl<'a> DiagnosticsConfig<'a> {
         self.disabled.insert(code);
         self
     }
+
+    pub fn enable(mut self, code: DiagnosticCode) -> DiagnosticsConfig<'a> {
+        self.enabled.insert(code);
+        self
+    }
+
+    /// Report diagnostics with the given code using `severity`,
+    /// rather than the one chosen by the diagnostic itself.
+    pub fn override_severity(
+        mut self,
+        code: DiagnosticCode,
+        severity: Severity,
+    ) -> DiagnosticsConfig<'a> {
+        self.severity_overrides.insert(code, severity);
+        self
+    }
+
+    /// Report diagnostics with the given code, which is not an ELP
+    /// one, using `severity`.
+    pub fn override_other_severity(
+        mut self,
+        code: String,
+        severity: Severity,
+    ) -> DiagnosticsConfig<'a> {
+        self.other_severity_overrides.insert(code, severity);
+        self
+    }
+
+    /// The severity configured for diagnostics with the given code,
+    /// whether an ELP one or not.
+    pub fn severity_override(&self, code: &str) -> Option<Severity> {
+        match DiagnosticCode::maybe_from_string(&code.to_string()) {
+            Some(code) => self.severity_overrides.get(&code).copied(),
+            None => self.other_severity_overrides.get(code).copied(),
+        }
+    }
+
+    /// The severity to report the diagnostic with, taking overrides
+    /// into account.
+    pub fn severity(&self, diagnostic: &Diagnostic) -> Severity {
+        self.severity_overrides
+            .get(&diagnostic.code)
+            .copied()
+            .unwrap_or(diagnostic.severity)
+    }
+
+    /// Whether diagnostics with the given code should be reported.
+    pub fn is_enabled(&self, code: &DiagnosticCode) -> bool {
+        !self.disabled.contains(code) && (!code.is_opt_in() || self.enabled.contains(code))
+    }
+
+    /// Whether the Erlang service diagnostic is also computed natively
+    /// for modules, when the corresponding native diagnostic is enabled.
+    pub fn is_computed_natively(&self, diagnostic: &Diagnostic) -> bool {
+        match &diagnostic.code {
+            DiagnosticCode::ErlangService(code) => match code.as_str() {
+                "L1230" => self.is_enabled(&DiagnosticCode::UnusedFunction),
+                "L1260" => self.is_enabled(&DiagnosticCode::UnusedRecord),
+                "L1296" => self.is_enabled(&DiagnosticCode::UnusedType),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Combine with the `[diagnostics]` section of the project
+    /// `.elp.toml`. Settings made here take precedence over the
+    /// project ones for severity overrides.
+    pub fn with_project_config(
+        mut self,
+        project_config: &ProjectDiagnosticsConfig,
+    ) -> DiagnosticsConfig<'a> {
+        if project_config.enable_experimental {
+            self.disable_experimental = false;
+        }
+        self.enabled.extend(
+            project_config
+                .enabled
+                .iter()
+                .filter_map(DiagnosticCode::maybe_from_string),
+        );
+        self.disabled.extend(
+            project_config
+                .disabled
+                .iter()
+                .filter_map(DiagnosticCode::maybe_from_string),
+        );
+        for (code, severity) in &project_config.severity {
+            match (
+                DiagnosticCode::maybe_from_string(code),
+                Severity::from_str(severity),
+            ) {
+                (Some(code), Ok(severity)) => {
+                    self.severity_overrides.entry(code).or_insert(severity);
+                }
+                (None, Ok(severity)) => {
+                    self.other_severity_overrides
+                        .entry(code.clone())
+                        .or_insert(severity);
+                }
+                (_, Err(_)) => log::warn!("Ignoring severity override {} = {}", code, severity),
+            }
+        }
+        self
+    }
 }
 
 pub fn diagnostics(
@@ -423,7 +641,10 @@ pub fn diagnostics(
     let path = root.path_for_file(&file_id).unwrap();
 
     let ext = path.name_and_extension().unwrap_or_default().1;
-    let report_diagnostics = EXTENSIONS.iter().any(|it| Some(it.as_str()) == ext);
+    let file_config = file_config(db, config, file_id);
+    let report_diagnostics =
+        EXTENSIONS.iter().any(|it| Some(it.as_str()) == ext) && file_config.is_some();
+    let config = &file_config.unwrap_or_else(|| config.clone());
 
     let mut res = Vec::new();
 
@@ -443,6 +664,32 @@ pub fn diagnostics(
             if is_test_suite {
                 common_test::unreachable_test(&mut res, &sema, file_id)
             }
+            if config.is_enabled(&DiagnosticCode::UnusedExportedFunction) {
+                unused_exported_function::unused_exported_function(&mut res, &sema, db, file_id);
+            }
+            if [
+                DiagnosticCode::UndefinedModule,
+                DiagnosticCode::UndefinedFunction,
+                DiagnosticCode::UnexportedFunction,
+            ]
+            .iter()
+            .any(|code| config.is_enabled(code))
+            {
+                undefined_function::undefined_function(&mut res, &sema, file_id);
+            }
+            if config.is_enabled(&DiagnosticCode::UnusedFunction) {
+                unused_function::unused_function(&mut res, &sema, file_id);
+            }
+            if config.is_enabled(&DiagnosticCode::ReceiveWithoutAfter)
+                || config.is_enabled(&DiagnosticCode::UnmatchedReceive)
+            {
+                receive_in_callback::receive_in_callback(&mut res, &sema, file_id);
+            }
+        }
+        if config.is_enabled(&DiagnosticCode::UnusedRecord)
+            || config.is_enabled(&DiagnosticCode::UnusedType)
+        {
+            unused_record_and_type::unused_record_and_type(&mut res, &sema, file_id, ext);
         }
 
         res.append(&mut form_missing_separator_diagnostics(&parse));
@@ -464,14 +711,54 @@ pub fn diagnostics(
     }
     let line_index = db.file_line_index(file_id);
     res.retain(|d| {
-        !config.disabled.contains(&d.code)
+        config.is_enabled(&d.code)
             && !(config.disable_experimental && d.experimental)
             && !d.should_be_ignored(&line_index, &parse.syntax_node())
     });
+    for d in res.iter_mut() {
+        d.severity = config.severity(d);
+    }
 
     res
 }
 
+/// The configuration to report the diagnostics of the file with, from
+/// whichever source, combined with the one of its project. `None` when
+/// the project excludes the file from diagnostics altogether.
+pub fn file_config<'a>(
+    db: &RootDatabase,
+    config: &DiagnosticsConfig<'a>,
+    file_id: FileId,
+) -> Option<DiagnosticsConfig<'a>> {
+    let root_id = db.file_source_root(file_id);
+    let app_data = match db.app_data(root_id) {
+        Some(app_data) => app_data,
+        None => return Some(config.clone()),
+    };
+    let project_data = db.project_data(app_data.project_id);
+    let root = db.source_root(root_id);
+    let path = root.path_for_file(&file_id)?;
+    if is_excluded(&project_data, &app_data, path) {
+        return None;
+    }
+    Some(
+        config
+            .clone()
+            .with_project_config(&project_data.diagnostics_config),
+    )
+}
+
+/// Whether the file is excluded from diagnostics by the project
+/// `.elp.toml`, either via its app or its path.
+fn is_excluded(project_data: &ProjectData, app_data: &AppData, path: &VfsPath) -> bool {
+    let project_config = &project_data.diagnostics_config;
+    project_config.is_app_excluded(&app_data.name)
+        || path
+            .as_path()
+            .and_then(|path| path.strip_prefix(&project_data.root_dir))
+            .map_or(false, |path| project_config.is_path_excluded(path.as_ref()))
+}
+
 pub fn semantic_diagnostics(
     res: &mut Vec<Diagnostic>,
     sema: &Semantic,
@@ -490,6 +777,9 @@ pub fn semantic_diagnostics(
     mutable_variable::mutable_variable_bug(res, sema, file_id);
     effect_free_statement::effect_free_statement(res, sema, file_id);
     application_env::application_env(res, sema, file_id);
+    deprecated_function::deprecated_function(res, sema, file_id);
+    unsafe_atom_creation::unsafe_atom_creation(res, sema, file_id);
+    callback_return::callback_return(res, sema, file_id);
     // @fb-only: meta_only::diagnostics(res, sema, file_id);
     missing_compile_warn_missing_spec::missing_compile_warn_missing_spec(res, sema, file_id);
 }
@@ -689,6 +979,27 @@ fn make_missing_diagnostic(range: TextRange, item: &'static str, code: String) -
     }
 }
 
+/// The candidate closest to the given name, if any is close enough to
+/// be a likely misspelling of it.
+pub(crate) fn closest_match<'a>(
+    name: &str,
+    candidates: impl Iterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let close_enough: usize = std::cmp::max(1, std::cmp::min(3, name.len() / 3));
+    let mut suggestions: Vec<(&str, f64)> = candidates
+        .filter(|candidate| *candidate != name)
+        .filter(|candidate| {
+            triple_accel::levenshtein::rdamerau(name.as_bytes(), candidate.as_bytes())
+                <= u32::try_from(close_enough).unwrap()
+        })
+        .map(|candidate| (candidate, strsim::jaro_winkler(name, candidate)))
+        .collect();
+    suggestions.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+    suggestions
+        .first()
+        .map(|(suggestion, _similarity)| *suggestion)
+}
+
 pub fn erlang_service_diagnostics(
     db: &RootDatabase,
     file_id: FileId,
@@ -1039,6 +1350,8 @@ fn location_range(location: Location, line_index: &LineIndex) -> TextRange {
 // cargo test --package elp_ide --lib
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
+
     use elp_syntax::ast;
     use expect_test::expect;
 
@@ -1227,6 +1540,8 @@ baz(1)->4.
         let mut config = DiagnosticsConfig {
             disable_experimental: false,
             disabled: FxHashSet::default(),
+            enabled: FxHashSet::default(),
+            severity_overrides: FxHashMap::default(),
             adhoc_semantic_diagnostics: vec![&|acc, sema, file_id, _ext| {
                 replace_call::replace_call_site(
                     &FunctionMatch::MFA(MFA {
@@ -1276,6 +1591,91 @@ baz(1)->4.
         )
     }
 
+    #[test]
+    fn project_config() {
+        let project_config = ProjectDiagnosticsConfig {
+            enabled: vec!["unused_exported_function".to_string()],
+            disabled: vec!["W0012".to_string()],
+            severity: BTreeMap::from([
+                ("W0015".to_string(), "error".to_string()),
+                ("W0008".to_string(), "not_a_severity".to_string()),
+            ]),
+            ..Default::default()
+        };
+        check_diagnostics_with_config(
+            DiagnosticsConfig::default().with_project_config(&project_config),
+            r#"
+//- /src/main.erl
+-module(main).
+-export([unused/0]).
+      %% ^^^^^^^^ 💡 error: Exported function unused/0 is never used
+unused() -> ok.
+"#,
+        );
+        check_diagnostics_with_config(
+            DiagnosticsConfig::default()
+                .override_severity(
+                    DiagnosticCode::UnusedExportedFunction,
+                    Severity::WeakWarning,
+                )
+                .with_project_config(&project_config),
+            r#"
+//- /src/main.erl
+-module(main).
+-export([unused/0]).
+      %% ^^^^^^^^ 💡 weak: Exported function unused/0 is never used
+unused() -> ok.
+"#,
+        );
+    }
+
+    #[test]
+    fn project_config_other_severity() {
+        let project_config = ProjectDiagnosticsConfig {
+            severity: BTreeMap::from([
+                ("incompatible_types".to_string(), "warning".to_string()),
+                ("W0015".to_string(), "error".to_string()),
+            ]),
+            ..Default::default()
+        };
+        let config = DiagnosticsConfig::default()
+            .override_other_severity("incompatible_types".to_string(), Severity::Error)
+            .with_project_config(&project_config);
+        assert_eq!(
+            config.severity_override("incompatible_types"),
+            Some(Severity::Error)
+        );
+        assert_eq!(config.severity_override("W0015"), Some(Severity::Error));
+        assert_eq!(config.severity_override("W0008"), None);
+        let config = DiagnosticsConfig::default().with_project_config(&project_config);
+        assert_eq!(
+            config.severity_override("incompatible_types"),
+            Some(Severity::Warning)
+        );
+    }
+
+    #[test]
+    fn severity_threshold() {
+        let severities = ["error", "warning", "info", "hint", "fatal"]
+            .iter()
+            .map(|s| Severity::from_str(s).ok())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            severities,
+            vec![
+                Some(Severity::Error),
+                Some(Severity::Warning),
+                Some(Severity::Information),
+                Some(Severity::WeakWarning),
+                None
+            ]
+        );
+        assert!(Severity::Error.is_at_least(Severity::Warning));
+        assert!(Severity::Warning.is_at_least(Severity::Warning));
+        assert!(!Severity::Information.is_at_least(Severity::Warning));
+        assert!(Severity::Information.is_at_least(Severity::WeakWarning));
+    }
+
     #[test]
     fn from_string_1() {
         let strings = vec!["W0008", "unreachable_test"];
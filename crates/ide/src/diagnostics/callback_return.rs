@@ -0,0 +1,249 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+// Diagnostic: invalid-callback-return
+//
+// Return a warning if a callback of a module implementing `gen_server`,
+// `gen_statem` or `gen_event` returns a tuple literal which is not part
+// of the contract of the behaviour, e.g. `{reply, Reply}` from
+// `handle_call/3`.
+//
+// Only tuples tagged with an atom are checked, in the last expression of
+// each clause of the callback, looking into `case`, `if`, `receive`,
+// `try` and `begin` blocks.
+
+use elp_ide_db::elp_base_db::FileId;
+use hir::Body;
+use hir::Expr;
+use hir::ExprId;
+use hir::FunctionDef;
+use hir::NameArity;
+use hir::Semantic;
+
+use crate::diagnostics::DiagnosticCode;
+use crate::Diagnostic;
+
+/// The tuples a callback may return, as their tag and allowed sizes.
+type Contract = &'static [(&'static str, &'static [usize])];
+
+const GEN_SERVER_INIT: Contract = &[("ok", &[2, 3]), ("stop", &[2]), ("error", &[2])];
+const GEN_SERVER_CALL: Contract = &[("reply", &[3, 4]), ("noreply", &[2, 3]), ("stop", &[3, 4])];
+const GEN_SERVER_CAST: Contract = &[("noreply", &[2, 3]), ("stop", &[3])];
+const GEN_SERVER_CODE_CHANGE: Contract = &[("ok", &[2]), ("error", &[2])];
+
+const GEN_STATEM_INIT: Contract = &[("ok", &[3, 4]), ("stop", &[2]), ("error", &[2])];
+const GEN_STATEM_EVENT: Contract = &[
+    ("next_state", &[3, 4]),
+    ("keep_state", &[2, 3]),
+    ("keep_state_and_data", &[2]),
+    ("repeat_state", &[2, 3]),
+    ("repeat_state_and_data", &[2]),
+    ("stop", &[2, 3]),
+    ("stop_and_reply", &[3, 4]),
+];
+
+const GEN_EVENT_INIT: Contract = &[("ok", &[2, 3]), ("error", &[2])];
+const GEN_EVENT_EVENT: Contract = &[("ok", &[2, 3]), ("swap_handler", &[5])];
+const GEN_EVENT_CALL: Contract = &[
+    ("ok", &[3, 4]),
+    ("swap_handler", &[6]),
+    ("remove_handler", &[2]),
+];
+const GEN_EVENT_CODE_CHANGE: Contract = &[("ok", &[2])];
+
+fn contract(behaviour: &str, name: &str, arity: u32) -> Option<Contract> {
+    match (behaviour, name, arity) {
+        ("gen_server", "init", 1) => Some(GEN_SERVER_INIT),
+        ("gen_server", "handle_call", 3) => Some(GEN_SERVER_CALL),
+        ("gen_server", "handle_cast" | "handle_info" | "handle_continue", 2) => {
+            Some(GEN_SERVER_CAST)
+        }
+        ("gen_server", "code_change", 3) => Some(GEN_SERVER_CODE_CHANGE),
+        ("gen_statem", "init", 1) => Some(GEN_STATEM_INIT),
+        ("gen_statem", "handle_event", 4) => Some(GEN_STATEM_EVENT),
+        ("gen_event", "init", 1) => Some(GEN_EVENT_INIT),
+        ("gen_event", "handle_event" | "handle_info", 2) => Some(GEN_EVENT_EVENT),
+        ("gen_event", "handle_call", 2) => Some(GEN_EVENT_CALL),
+        ("gen_event", "code_change", 3) => Some(GEN_EVENT_CODE_CHANGE),
+        _ => None,
+    }
+}
+
+pub(crate) fn callback_return(diags: &mut Vec<Diagnostic>, sema: &Semantic, file_id: FileId) {
+    let form_list = sema.db.file_form_list(file_id);
+    let def_map = sema.def_map(file_id);
+    for (_, behaviour) in form_list.behaviour_attributes() {
+        let behaviour = behaviour.name.as_str();
+        for (name, def) in def_map.get_functions() {
+            if def.file.file_id != file_id {
+                continue;
+            }
+            if let Some(contract) = contract(behaviour, name.name().as_str(), name.arity()) {
+                check_function(diags, sema, behaviour, name, def, contract);
+            }
+        }
+    }
+}
+
+fn check_function(
+    diags: &mut Vec<Diagnostic>,
+    sema: &Semantic,
+    behaviour: &str,
+    name: &NameArity,
+    def: &FunctionDef,
+    contract: Contract,
+) {
+    let def_fb = def.in_function_body(sema.db, def);
+    let body = def_fb.body();
+    let mut returned = Vec::new();
+    for (_, clause) in def_fb.clauses() {
+        if let Some(last) = clause.exprs.last() {
+            returned_exprs(&body, *last, &mut returned);
+        }
+    }
+    for expr_id in returned {
+        let exprs = match &body[expr_id] {
+            Expr::Tuple { exprs } => exprs,
+            _ => continue,
+        };
+        let tag = match exprs.first().and_then(|tag| body[*tag].as_atom()) {
+            Some(tag) => sema.db.lookup_atom(tag),
+            None => continue,
+        };
+        let message = match contract.iter().find(|(t, _)| *t == tag.as_str()) {
+            Some((_, sizes)) if sizes.contains(&exprs.len()) => continue,
+            Some((_, sizes)) => format!(
+                "Callback {name} of {behaviour} returns a tuple tagged '{tag}' of size {}, expected size {}",
+                exprs.len(),
+                sizes
+                    .iter()
+                    .map(|size| size.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" or ")
+            ),
+            None => format!(
+                "Callback {name} of {behaviour} returns a tuple tagged '{tag}', expected one of: {}",
+                contract
+                    .iter()
+                    .map(|(t, _)| *t)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        };
+        if let Some(range) = def_fb.range_for_expr(sema.db, expr_id) {
+            diags.push(Diagnostic::warning(
+                DiagnosticCode::InvalidCallbackReturn,
+                range,
+                message,
+            ));
+        }
+    }
+}
+
+/// The expressions whose value may be returned by the given expression,
+/// looking into the branches of conditional expressions.
+fn returned_exprs(body: &Body, expr_id: ExprId, acc: &mut Vec<ExprId>) {
+    let last = |exprs: &[ExprId], acc: &mut Vec<ExprId>| {
+        if let Some(last) = exprs.last() {
+            returned_exprs(body, *last, acc);
+        }
+    };
+    match &body[expr_id] {
+        Expr::Block { exprs } => last(exprs, acc),
+        Expr::Case { clauses, .. } => clauses.iter().for_each(|c| last(&c.exprs, acc)),
+        Expr::If { clauses } => clauses.iter().for_each(|c| last(&c.exprs, acc)),
+        Expr::Receive { clauses, after } => {
+            clauses.iter().for_each(|c| last(&c.exprs, acc));
+            if let Some(after) = after {
+                last(&after.exprs, acc);
+            }
+        }
+        Expr::Try {
+            exprs,
+            of_clauses,
+            catch_clauses,
+            ..
+        } => {
+            if of_clauses.is_empty() {
+                last(exprs, acc);
+            } else {
+                of_clauses.iter().for_each(|c| last(&c.exprs, acc));
+            }
+            catch_clauses.iter().for_each(|c| last(&c.exprs, acc));
+        }
+        Expr::Tuple { .. } => acc.push(expr_id),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::tests::check_diagnostics;
+
+    #[test]
+    fn gen_server_callbacks() {
+        check_diagnostics(
+            r#"
+//- /src/main.erl
+-module(main).
+-behaviour(gen_server).
+-export([init/1, handle_call/3, handle_cast/2, handle_info/2]).
+init(Args) -> {ok, Args}.
+handle_call(ping, _From, State) ->
+    {reply, pong};
+%%  ^^^^^^^^^^^^^ warning: Callback handle_call/3 of gen_server returns a tuple tagged 'reply' of size 2, expected size 3 or 4
+handle_call(stop, _From, State) ->
+    {stop, normal, ok, State};
+handle_call(Req, _From, State) ->
+    case Req of
+        {get, Key} -> {reply, maps:get(Key, State), State};
+        _ -> {ok, State}
+%%           ^^^^^^^^^^^ warning: Callback handle_call/3 of gen_server returns a tuple tagged 'ok', expected one of: reply, noreply, stop
+    end.
+handle_cast(_Msg, State) ->
+    {noreply, State, hibernate, extra}.
+%%  ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ warning: Callback handle_cast/2 of gen_server returns a tuple tagged 'noreply' of size 4, expected size 2 or 3
+handle_info(_Msg, State) ->
+    {noreply, State}.
+"#,
+        );
+    }
+
+    #[test]
+    fn gen_statem_callbacks() {
+        check_diagnostics(
+            r#"
+//- /src/main.erl
+-module(main).
+-behaviour(gen_statem).
+-export([init/1, callback_mode/0, handle_event/4]).
+callback_mode() -> handle_event_function.
+init(Data) -> {ok, Data}.
+%%            ^^^^^^^^^^ warning: Callback init/1 of gen_statem returns a tuple tagged 'ok' of size 2, expected size 3 or 4
+handle_event(_Type, go, State, Data) ->
+    {next_state, State, Data, []};
+handle_event(_Type, _Content, _State, Data) ->
+    {keep_state, Data}.
+"#,
+        );
+    }
+
+    #[test]
+    fn no_behaviour() {
+        check_diagnostics(
+            r#"
+//- /src/main.erl
+-module(main).
+-export([handle_call/3]).
+handle_call(_Req, _From, State) -> {reply, State}.
+"#,
+        );
+    }
+}
@@ -0,0 +1,421 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+// Diagnostic: deprecated-function
+//
+// Return a warning if a function marked as deprecated, either with a
+// `-deprecated` attribute or in OTP's `otp_internal` module, is called
+// or captured with `fun M:F/A`.
+
+use std::sync::Arc;
+
+use elp_ide_db::elp_base_db::FileId;
+use elp_ide_db::source_change::SourceChange;
+use elp_syntax::ast;
+use elp_syntax::AstNode;
+use elp_syntax::SyntaxNode;
+use elp_syntax::TextRange;
+use fxhash::FxHashMap;
+use hir::CallDef;
+use hir::DeprecatedAttribute;
+use hir::DeprecatedDesc;
+use hir::DeprecatedFa;
+use hir::FunctionDef;
+use hir::InFile;
+use hir::Name;
+use hir::NameArity;
+use hir::Semantic;
+use lazy_static::lazy_static;
+use regex::Regex;
+use text_edit::TextEdit;
+
+use crate::diagnostics::DiagnosticCode;
+use crate::fix;
+use crate::Diagnostic;
+
+pub(crate) fn deprecated_function(
+    acc: &mut Vec<Diagnostic>,
+    sema: &Semantic,
+    file_id: FileId,
+) -> Option<()> {
+    let source_file = sema.parse(file_id);
+    let caller = sema
+        .module_name(file_id)
+        .map(|module| Name::from_erlang_service(module.as_str()));
+    let mut otp_deprecations = None;
+    for node in source_file.value.syntax().descendants() {
+        let (def, range, target) = match function_reference(sema, file_id, &node) {
+            Some(reference) => reference,
+            None => continue,
+        };
+        let module = match sema.module_name(def.file.file_id) {
+            Some(module) => Name::from_erlang_service(module.as_str()),
+            None => continue,
+        };
+        let deprecation = if def.deprecated {
+            Some(local_deprecation(sema, &def))
+        } else {
+            otp_deprecations
+                .get_or_insert_with(|| otp_internal_deprecations(sema, file_id))
+                .get(&(module.clone(), def.function.name.clone()))
+                .map(|desc| Some(desc.clone()))
+        };
+        if let Some(description) = deprecation {
+            acc.push(make_diagnostic(
+                file_id,
+                &module,
+                &def.function.name,
+                description,
+                range,
+                target,
+                caller.as_ref(),
+            ));
+        }
+    }
+    Some(())
+}
+
+/// The target of a call or capture, which includes the module if present.
+struct Target {
+    range: TextRange,
+    qualified: bool,
+}
+
+/// The function called or captured by the given node, along with the
+/// range to report and the call target.
+fn function_reference(
+    sema: &Semantic,
+    file_id: FileId,
+    node: &SyntaxNode,
+) -> Option<(FunctionDef, TextRange, Target)> {
+    if let Some(call) = ast::Call::cast(node.clone()) {
+        let def = match sema.to_def(InFile::new(file_id, &call))? {
+            CallDef::Function(def) => def,
+            CallDef::Type(_) => return None,
+        };
+        let expr = call.expr()?;
+        let range = expr.syntax().text_range();
+        let qualified = matches!(expr, ast::Expr::Remote(_));
+        Some((def, range, Target { range, qualified }))
+    } else if let Some(fun) = ast::ExternalFun::cast(node.clone()) {
+        let def = sema.to_def(InFile::new(file_id, &fun))?;
+        let target_range = fun
+            .module()?
+            .syntax()
+            .text_range()
+            .cover(fun.fun()?.syntax().text_range());
+        let target = Target {
+            range: target_range,
+            qualified: true,
+        };
+        Some((def, fun.syntax().text_range(), target))
+    } else if let Some(fun) = ast::InternalFun::cast(node.clone()) {
+        let def = sema.to_def(InFile::new(file_id, &fun))?;
+        let target = Target {
+            range: fun.fun()?.syntax().text_range(),
+            qualified: false,
+        };
+        Some((def, fun.syntax().text_range(), target))
+    } else {
+        None
+    }
+}
+
+fn make_diagnostic(
+    file_id: FileId,
+    module: &Name,
+    function: &NameArity,
+    description: Option<String>,
+    range: TextRange,
+    target: Target,
+    caller: Option<&Name>,
+) -> Diagnostic {
+    let label = format!("{}:{}/{}", module, function.name(), function.arity());
+    let message = match &description {
+        Some(description) => format!("Function '{label}' is deprecated: {description}"),
+        None => format!("Function '{label}' is deprecated"),
+    };
+    let fixes = description
+        .as_deref()
+        .and_then(|description| replacement(module, description))
+        .filter(|(_, _, arity)| *arity == function.arity())
+        .map(|(module, name, _)| {
+            // Local references stay local when the replacement is
+            // defined in the calling module
+            let replacement = if !target.qualified && caller.map_or(false, |c| c.as_str() == module)
+            {
+                name
+            } else {
+                format!("{module}:{name}")
+            };
+            let mut builder = TextEdit::builder();
+            builder.replace(target.range, replacement.clone());
+            vec![fix(
+                "replace_deprecated_function",
+                &format!("Replace with '{replacement}'"),
+                SourceChange::from_text_edit(file_id, builder.finish()),
+                range,
+            )]
+        });
+    Diagnostic::warning(DiagnosticCode::DeprecatedFunction, range, message).with_fixes(fixes)
+}
+
+/// The description given in the `-deprecated` attribute of the module
+/// defining the function, if any. Functions of a module deprecated as a
+/// whole with `-deprecated(module)` are described as such.
+fn local_deprecation(sema: &Semantic, def: &FunctionDef) -> Option<String> {
+    let form_list = sema.db.file_form_list(def.file.file_id);
+    let matches = |fa: &DeprecatedFa| {
+        (fa.name == *def.function.name.name() || fa.name == "_")
+            && fa
+                .arity
+                .map_or(true, |arity| arity == def.function.name.arity())
+    };
+    form_list
+        .deprecated_attributes()
+        .find_map(|(_, attr)| match attr {
+            DeprecatedAttribute::Module { .. } => None,
+            DeprecatedAttribute::Fa { fa, .. } => Some(fa).filter(|fa| matches(fa)),
+            DeprecatedAttribute::Fas { fas, .. } => fas.iter().find(|fa| matches(fa)),
+        })
+        .and_then(|fa| fa.desc.as_ref())
+        .and_then(describe)
+        .or_else(|| {
+            form_list
+                .deprecated_attributes()
+                .any(|(_, attr)| matches!(attr, DeprecatedAttribute::Module { .. }))
+                .then(|| "the whole module is deprecated".to_string())
+        })
+}
+
+fn describe(desc: &DeprecatedDesc) -> Option<String> {
+    match desc {
+        DeprecatedDesc::Str(desc) => Some(desc.to_string()),
+        DeprecatedDesc::Atom(atom) => match atom.as_str() {
+            "next_version" | "next_major_release" => {
+                Some("it will be removed in the next major release".to_string())
+            }
+            "eventually" => Some("it will be removed in a future release".to_string()),
+            _ => None,
+        },
+    }
+}
+
+/// The deprecations of OTP functions, as described by the
+/// `otp_internal` module visible from the file.
+fn otp_internal_deprecations(
+    sema: &Semantic,
+    file_id: FileId,
+) -> Arc<FxHashMap<(Name, NameArity), String>> {
+    match sema.resolve_module_name(file_id, "otp_internal") {
+        Some(module) => sema.db.otp_internal_deprecations(module.file.file_id),
+        None => Arc::default(),
+    }
+}
+
+/// Looks for a function named in a deprecation description, such as
+/// "use rand:uniform/1 instead". Functions given without a module are
+/// taken to be in the module of the deprecated function.
+fn replacement(module: &Name, description: &str) -> Option<(String, String, u32)> {
+    lazy_static! {
+        static ref RE: Regex =
+            Regex::new(r"\b(?:([a-z][A-Za-z0-9_@]*):)?([a-z][A-Za-z0-9_@]*)/([0-9]+)").unwrap();
+    }
+    let captures = RE.captures(description)?;
+    let module = captures
+        .get(1)
+        .map_or_else(|| module.to_string(), |m| m.as_str().to_string());
+    let name = captures[2].to_string();
+    let arity = captures[3].parse().ok()?;
+    Some((module, name, arity))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::tests::check_diagnostics;
+    use crate::tests::check_fix;
+
+    #[test]
+    fn remote_call_to_deprecated_function() {
+        check_diagnostics(
+            r#"
+//- /src/main.erl
+-module(main).
+main() ->
+    other:old(),
+ %% ^^^^^^^^^ warning: Function 'other:old/0' is deprecated
+    other:new().
+//- /src/other.erl
+-module(other).
+-export([old/0, new/0]).
+-deprecated([{old, 0}]).
+old() -> ok.
+new() -> ok.
+"#,
+        );
+    }
+
+    #[test]
+    fn local_call_and_captures() {
+        check_diagnostics(
+            r#"
+-module(main).
+-export([old/1, main/0]).
+-deprecated({old, '_', "no longer needed"}).
+old(X) -> X.
+main() ->
+    old(1),
+ %% ^^^ warning: Function 'main:old/1' is deprecated: no longer needed
+    F = fun old/1,
+     %% ^^^^^^^^^ warning: Function 'main:old/1' is deprecated: no longer needed
+    G = fun main:old/1,
+     %% ^^^^^^^^^^^^^^ warning: Function 'main:old/1' is deprecated: no longer needed
+    {F, G}.
+"#,
+        );
+    }
+
+    #[test]
+    fn next_version_and_eventually() {
+        check_diagnostics(
+            r#"
+//- /src/main.erl
+-module(main).
+main() ->
+    other:a(),
+ %% ^^^^^^^ warning: Function 'other:a/0' is deprecated: it will be removed in the next major release
+    other:b().
+ %% ^^^^^^^ warning: Function 'other:b/0' is deprecated: it will be removed in a future release
+//- /src/other.erl
+-module(other).
+-export([a/0, b/0]).
+-deprecated([{a, 0, next_version}, {b, 0, eventually}]).
+a() -> ok.
+b() -> ok.
+"#,
+        );
+    }
+
+    #[test]
+    fn otp_internal_deprecation() {
+        check_diagnostics(
+            r#"
+//- /src/main.erl
+-module(main).
+main() ->
+    crypto:rand_uniform(1, 10),
+ %% ^^^^^^^^^^^^^^^^^^^ warning: Function 'crypto:rand_uniform/2' is deprecated: use rand:uniform/1 instead
+    crypto:strong_rand_bytes(4).
+//- /src/crypto.erl
+-module(crypto).
+-export([rand_uniform/2, strong_rand_bytes/1]).
+rand_uniform(_, _) -> ok.
+strong_rand_bytes(_) -> ok.
+//- /src/otp_internal.erl
+-module(otp_internal).
+-export([obsolete/3]).
+obsolete(crypto, rand_uniform, 2) ->
+    {deprecated, "use rand:uniform/1 instead"};
+obsolete(_, _, _) ->
+    no.
+"#,
+        );
+    }
+
+    #[test]
+    fn fix_replaces_call_target() {
+        check_fix(
+            r#"
+//- /src/main.erl
+-module(main).
+main() ->
+    oth~er:old(1).
+//- /src/other.erl
+-module(other).
+-export([old/1, new/1]).
+-deprecated([{old, 1, "use other:new/1 instead"}]).
+old(X) -> X.
+new(X) -> X.
+"#,
+            r#"
+-module(main).
+main() ->
+    other:new(1).
+"#,
+        );
+    }
+
+    #[test]
+    fn fix_for_local_replacement() {
+        check_fix(
+            r#"
+//- /src/main.erl
+-module(main).
+main() ->
+    F = fun other:o~ld/1,
+    F.
+//- /src/other.erl
+-module(other).
+-export([old/1, new/1]).
+-deprecated([{old, 1, "use new/1 instead"}]).
+old(X) -> X.
+new(X) -> X.
+"#,
+            r#"
+-module(main).
+main() ->
+    F = fun other:new/1,
+    F.
+"#,
+        );
+    }
+
+    #[test]
+    fn fix_keeps_local_call_local() {
+        check_fix(
+            r#"
+-module(main).
+-export([old/1, new/1, main/0]).
+-deprecated([{old, 1, "use new/1 instead"}]).
+old(X) -> X.
+new(X) -> X.
+main() ->
+    o~ld(1).
+"#,
+            r#"
+-module(main).
+-export([old/1, new/1, main/0]).
+-deprecated([{old, 1, "use new/1 instead"}]).
+old(X) -> X.
+new(X) -> X.
+main() ->
+    new(1).
+"#,
+        );
+    }
+
+    #[test]
+    fn deprecated_module() {
+        check_diagnostics(
+            r#"
+//- /src/main.erl
+-module(main).
+main() ->
+    other:old().
+ %% ^^^^^^^^^ warning: Function 'other:old/0' is deprecated: the whole module is deprecated
+//- /src/other.erl
+-module(other).
+-export([old/0]).
+-deprecated(module).
+old() -> ok.
+"#,
+        );
+    }
+}
@@ -15,6 +15,7 @@ use hir::db::MinDefDatabase;
 use hir::Attribute;
 use text_edit::TextEdit;
 
+use super::closest_match;
 use super::Diagnostic;
 use crate::diagnostics::RelatedInformation;
 use crate::fix;
@@ -77,21 +78,7 @@ const KNOWN_ATTRIBUTES: &[&str] = &[
 ];
 
 fn looks_like_misspelling(attr: &Attribute) -> Option<&str> {
-    let mut suggestions: Vec<(&str, f64)> = KNOWN_ATTRIBUTES
-        .iter()
-        .filter(|&known| &attr.name != known)
-        .filter(|&known| {
-            let close_enough: usize = std::cmp::max(1, std::cmp::min(3, attr.name.len() / 3));
-            triple_accel::levenshtein::rdamerau(attr.name.as_str().as_bytes(), known.as_bytes())
-                <= u32::try_from(close_enough).unwrap()
-        })
-        .map(|&known| (known, strsim::jaro_winkler(&attr.name, known)))
-        .collect::<Vec<(&str, f64)>>();
-    suggestions.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
-    suggestions
-        .first()
-        .map(|(suggestion, _similarity)| suggestion)
-        .copied()
+    closest_match(attr.name.as_str(), KNOWN_ATTRIBUTES.iter().copied())
 }
 
 fn make_diagnostic(
@@ -180,6 +167,8 @@ mod tests {
         let mut config = DiagnosticsConfig {
             disable_experimental: true,
             disabled: FxHashSet::default(),
+            enabled: FxHashSet::default(),
+            severity_overrides: FxHashMap::default(),
             adhoc_semantic_diagnostics: vec![],
         };
         config
@@ -208,6 +197,8 @@ mod tests {
         let mut config = DiagnosticsConfig {
             disable_experimental: true,
             disabled: FxHashSet::default(),
+            enabled: FxHashSet::default(),
+            severity_overrides: FxHashMap::default(),
             adhoc_semantic_diagnostics: vec![],
         };
         config
@@ -0,0 +1,488 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+// Diagnostic: receive-without-after, unmatched-receive
+//
+// Return a warning for a `receive` expression in a callback of a module
+// implementing `gen_server`, `gen_statem` or `gen_event`:
+//
+// - if it has no `after` clause, since the process can then block forever
+//   while the behaviour expects it to keep serving requests;
+// - for each clause whose pattern is tagged with an atom or a record
+//   which is never sent by the module, so it can only match a message sent
+//   from elsewhere, if at all.
+//
+// Receives in funs are not checked, as these typically run in a different
+// process. The second check is skipped if the module sends a message whose
+// tag cannot be determined.
+
+use elp_ide_db::elp_base_db::FileId;
+use elp_syntax::ast::BinaryOp;
+use elp_syntax::AstNode;
+use elp_syntax::TextRange;
+use elp_syntax::TextSize;
+use fxhash::FxHashSet;
+use hir::Atom;
+use hir::Body;
+use hir::Expr;
+use hir::ExprId;
+use hir::FunctionDef;
+use hir::Literal;
+use hir::NameArity;
+use hir::On;
+use hir::Pat;
+use hir::PatId;
+use hir::Semantic;
+use hir::Strategy;
+
+use crate::diagnostics::DiagnosticCode;
+use crate::diagnostics::RelatedInformation;
+use crate::Diagnostic;
+
+/// Tags of messages sent by the runtime or by common libraries, rather than
+/// by the code of the module.
+const SYSTEM_TAGS: &[&str] = &[
+    "EXIT",
+    "DOWN",
+    "ETS-TRANSFER",
+    "timeout",
+    "system",
+    "nodeup",
+    "nodedown",
+    "io_reply",
+    "inet_reply",
+    "gen_event_EXIT",
+    "tcp",
+    "tcp_closed",
+    "tcp_error",
+    "tcp_passive",
+    "udp",
+    "udp_passive",
+    "ssl",
+    "ssl_closed",
+    "ssl_error",
+    "ssl_passive",
+];
+
+/// The tag of a message, i.e. the atom itself, the first element of a
+/// tuple, or the name of a record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Tag {
+    Atom(Atom),
+    Record(Atom),
+}
+
+fn is_callback(behaviour: &str, name: &NameArity, exported: bool) -> bool {
+    let name_str = name.name().as_str();
+    match (behaviour, name_str, name.arity()) {
+        ("gen_server", "init", 1) => true,
+        ("gen_server", "handle_continue", 2) => true,
+        ("gen_server", "handle_call", 3) => true,
+        ("gen_server", "handle_cast" | "handle_info" | "terminate", 2) => true,
+        ("gen_server", "code_change", 3) => true,
+        ("gen_statem", "init", 1) => true,
+        ("gen_statem", "handle_event" | "code_change", 4) => true,
+        ("gen_statem", "terminate", 3) => true,
+        // State functions can have any name
+        ("gen_statem", _, 3) => exported,
+        ("gen_event", "init", 1) => true,
+        ("gen_event", "handle_event" | "handle_call" | "handle_info" | "terminate", 2) => true,
+        ("gen_event", "code_change", 3) => true,
+        _ => false,
+    }
+}
+
+pub(crate) fn receive_in_callback(diags: &mut Vec<Diagnostic>, sema: &Semantic, file_id: FileId) {
+    let form_list = sema.db.file_form_list(file_id);
+    let behaviours: Vec<_> = form_list
+        .behaviour_attributes()
+        .map(|(_, behaviour)| behaviour.name.clone())
+        .collect();
+    let def_map = sema.def_map(file_id);
+    let mut callbacks: Vec<_> = def_map
+        .get_functions()
+        .iter()
+        .filter(|(name, def)| {
+            def.file.file_id == file_id
+                && behaviours
+                    .iter()
+                    .any(|behaviour| is_callback(behaviour.as_str(), name, def.exported))
+        })
+        .collect();
+    if callbacks.is_empty() {
+        return;
+    }
+    callbacks.sort_by_key(|(name, _)| (*name).clone());
+
+    // Computed lazily, only if a receive is found.
+    let mut sent_tags = None;
+    let mut diagnostics = Vec::new();
+    for (name, def) in callbacks {
+        let receives = receives_in_function(sema, def);
+        if receives.is_empty() {
+            continue;
+        }
+        let sent_tags = sent_tags.get_or_insert_with(|| sent_tags_in_file(sema, file_id));
+        check_receives(
+            &mut diagnostics,
+            sema,
+            name,
+            def,
+            &receives,
+            sent_tags.as_ref(),
+        );
+    }
+    diagnostics.sort_by_key(|d| d.range.start());
+    diags.extend(diagnostics);
+}
+
+/// The receive expressions in the function, outside of funs.
+fn receives_in_function(sema: &Semantic, def: &FunctionDef) -> Vec<ExprId> {
+    let def_fb = def.in_function_body(sema.db, def);
+    let (receives, _) = def_fb.fold_function_with_macros(
+        Strategy::Both,
+        (Vec::new(), 0),
+        &mut |(mut acc, closure_depth), _, ctx| match ctx.expr {
+            Expr::Closure { .. } if ctx.on == On::Entry => (acc, closure_depth + 1),
+            Expr::Closure { .. } => (acc, closure_depth - 1),
+            Expr::Receive { .. } if ctx.on == On::Entry && closure_depth == 0 => {
+                acc.push(ctx.expr_id);
+                (acc, closure_depth)
+            }
+            _ => (acc, closure_depth),
+        },
+        &mut |acc, _, _| acc,
+    );
+    receives
+}
+
+fn check_receives(
+    diags: &mut Vec<Diagnostic>,
+    sema: &Semantic,
+    name: &NameArity,
+    def: &FunctionDef,
+    receives: &[ExprId],
+    sent_tags: Option<&FxHashSet<Tag>>,
+) {
+    let related_info = def.source(sema.db.upcast()).name().map(|fun_name| {
+        vec![RelatedInformation {
+            range: fun_name.syntax().text_range(),
+            message: format!("Enclosing callback {name}"),
+        }]
+    });
+    let mut def_fb = def.in_function_body(sema.db, def);
+    let body = def_fb.body();
+    for receive in receives {
+        let (clauses, after) = match &body[*receive] {
+            Expr::Receive { clauses, after } => (clauses.clone(), after.is_some()),
+            _ => continue,
+        };
+        if !after {
+            if let Some(range) = def_fb.range_for_expr(sema.db, *receive) {
+                let range = TextRange::at(range.start(), TextSize::of("receive"));
+                diags.push(
+                    Diagnostic::warning(
+                        DiagnosticCode::ReceiveWithoutAfter,
+                        range,
+                        format!(
+                            "Receive without an 'after' clause in callback {name}, which can block the process forever"
+                        ),
+                    )
+                    .with_related(related_info.clone()),
+                );
+            }
+        }
+        let sent_tags = match sent_tags {
+            Some(sent_tags) => sent_tags,
+            None => continue,
+        };
+        for clause in clauses {
+            let tag = match pat_tag(&body, clause.pat) {
+                Some(tag) => tag,
+                None => continue,
+            };
+            if sent_tags.contains(&tag) || is_system_tag(sema, tag) {
+                continue;
+            }
+            if let Some(range) = def_fb.range_for_pat(sema.db, clause.pat) {
+                diags.push(
+                    Diagnostic::warning(
+                        DiagnosticCode::UnmatchedReceive,
+                        range,
+                        format!(
+                            "No message tagged '{}' is sent from this module, so this clause may never match",
+                            tag_name(sema, tag)
+                        ),
+                    )
+                    .with_related(related_info.clone()),
+                );
+            }
+        }
+    }
+}
+
+/// The tags of all the messages sent from the file, or `None` if the tag of
+/// any of them cannot be determined.
+fn sent_tags_in_file(sema: &Semantic, file_id: FileId) -> Option<FxHashSet<Tag>> {
+    let mut tags = FxHashSet::default();
+    for (_, def) in sema.def_map(file_id).get_functions() {
+        if def.file.file_id != file_id {
+            continue;
+        }
+        let def_fb = def.in_function_body(sema.db, def);
+        let body = def_fb.body();
+        let complete = def_fb.fold_function(
+            true,
+            &mut |complete, _, ctx| {
+                let message = match ctx.expr {
+                    Expr::BinaryOp {
+                        rhs,
+                        op: BinaryOp::Send,
+                        ..
+                    } => rhs,
+                    Expr::Call { target, args } => {
+                        let label = target.label(args.len() as u32, sema, &body);
+                        match label.as_deref().and_then(message_arg) {
+                            Some(idx) => match args.get(idx) {
+                                Some(message) => *message,
+                                None => return complete,
+                            },
+                            None => return complete,
+                        }
+                    }
+                    _ => return complete,
+                };
+                match expr_tag(&body, message) {
+                    Some(tag) => {
+                        tags.insert(tag);
+                        complete
+                    }
+                    None => false,
+                }
+            },
+            &mut |complete, _, _| complete,
+        );
+        if !complete {
+            return None;
+        }
+    }
+    Some(tags)
+}
+
+/// The position of the message in the arguments of a function sending it.
+fn message_arg(label: &str) -> Option<usize> {
+    match label {
+        "erlang:send/2" | "erlang:send/3" => Some(1),
+        "erlang:send_after/3" | "erlang:send_after/4" => Some(2),
+        "timer:send_after/2" | "timer:send_interval/2" => Some(1),
+        "timer:send_after/3" | "timer:send_interval/3" => Some(2),
+        _ => None,
+    }
+}
+
+fn expr_tag(body: &Body, expr_id: ExprId) -> Option<Tag> {
+    match &body[expr_id] {
+        Expr::Literal(Literal::Atom(atom)) => Some(Tag::Atom(*atom)),
+        Expr::Tuple { exprs } => exprs
+            .first()
+            .and_then(|first| body[*first].as_atom())
+            .map(Tag::Atom),
+        Expr::Record { name, .. } => Some(Tag::Record(*name)),
+        Expr::MacroCall { expansion, .. } => expr_tag(body, *expansion),
+        _ => None,
+    }
+}
+
+fn pat_tag(body: &Body, pat_id: PatId) -> Option<Tag> {
+    match &body[pat_id] {
+        Pat::Literal(Literal::Atom(atom)) => Some(Tag::Atom(*atom)),
+        Pat::Tuple { pats } => match pats.first().map(|first| &body[*first]) {
+            Some(Pat::Literal(Literal::Atom(atom))) => Some(Tag::Atom(*atom)),
+            _ => None,
+        },
+        Pat::Record { name, .. } => Some(Tag::Record(*name)),
+        Pat::MacroCall { expansion, .. } => pat_tag(body, *expansion),
+        _ => None,
+    }
+}
+
+fn is_system_tag(sema: &Semantic, tag: Tag) -> bool {
+    match tag {
+        Tag::Atom(atom) => SYSTEM_TAGS.contains(&sema.db.lookup_atom(atom).as_str()),
+        Tag::Record(_) => false,
+    }
+}
+
+fn tag_name(sema: &Semantic, tag: Tag) -> String {
+    match tag {
+        Tag::Atom(atom) => sema.db.lookup_atom(atom).to_string(),
+        Tag::Record(name) => format!("#{}", sema.db.lookup_atom(name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use elp_syntax::TextRange;
+    use elp_syntax::TextSize;
+
+    use crate::diagnostics::DiagnosticCode;
+    use crate::diagnostics::DiagnosticsConfig;
+    use crate::fixture;
+    use crate::tests::check_diagnostics_with_config;
+
+    fn config() -> DiagnosticsConfig<'static> {
+        DiagnosticsConfig::default()
+            .disable(DiagnosticCode::MissingCompileWarnMissingSpec)
+            .enable(DiagnosticCode::ReceiveWithoutAfter)
+            .enable(DiagnosticCode::UnmatchedReceive)
+    }
+
+    #[track_caller]
+    fn check_diagnostics(fixture: &str) {
+        check_diagnostics_with_config(config(), fixture)
+    }
+
+    #[test]
+    fn receive_without_after() {
+        check_diagnostics(
+            r#"
+//- /src/main.erl
+-module(main).
+-behaviour(gen_server).
+-export([init/1, handle_call/3, handle_cast/2]).
+init(Args) -> {ok, Args}.
+handle_call(wait, _From, State) ->
+    Reply = receive
+        %%  ^^^^^^^ warning: Receive without an 'after' clause in callback handle_call/3, which can block the process forever
+                {done, R} -> R
+            end,
+    {reply, Reply, State};
+handle_call(poll, _From, State) ->
+    Reply = receive
+                {done, R} -> R
+            after 100 -> timeout
+            end,
+    {reply, Reply, State}.
+handle_cast(go, State) ->
+    spawn(fun() -> receive {done, _} -> ok end end),
+    self() ! {done, ok},
+    {noreply, State}.
+"#,
+        );
+    }
+
+    #[test]
+    fn unmatched_receive() {
+        check_diagnostics(
+            r#"
+//- /src/main.erl
+-module(main).
+-behaviour(gen_server).
+-export([init/1, handle_call/3, handle_cast/2]).
+-record(ack, {id}).
+init(Args) -> {ok, Args}.
+handle_call(wait, _From, State) ->
+    Reply = receive
+                {done, R} -> R;
+                {never_sent, R} -> R;
+            %%  ^^^^^^^^^^^^^^^ warning: No message tagged 'never_sent' is sent from this module, so this clause may never match
+                #ack{id = Id} -> Id;
+                {'DOWN', _, _, _, Reason} -> Reason;
+                stop -> stop
+            %%  ^^^^ warning: No message tagged 'stop' is sent from this module, so this clause may never match
+            after 100 -> timeout
+            end,
+    {reply, Reply, State}.
+handle_cast(go, State) ->
+    self() ! {done, ok},
+    erlang:send_after(10, self(), #ack{id = 1}),
+    {noreply, State}.
+"#,
+        );
+    }
+
+    #[test]
+    fn unknown_message_disables_unmatched_receive() {
+        check_diagnostics(
+            r#"
+//- /src/main.erl
+-module(main).
+-behaviour(gen_server).
+-export([init/1, handle_call/3, handle_cast/2]).
+init(Args) -> {ok, Args}.
+handle_call(wait, _From, State) ->
+    Reply = receive
+                {never_sent, R} -> R
+            after 100 -> timeout
+            end,
+    {reply, Reply, State}.
+handle_cast(Msg, State) ->
+    self() ! Msg,
+    {noreply, State}.
+"#,
+        );
+    }
+
+    #[test]
+    fn not_a_callback_module() {
+        check_diagnostics(
+            r#"
+//- /src/main.erl
+-module(main).
+-export([handle_call/3]).
+handle_call(_Req, _From, State) ->
+    receive {never_sent, R} -> {reply, R, State} end.
+"#,
+        );
+    }
+
+    #[test]
+    fn callback_name_with_other_arity() {
+        check_diagnostics(
+            r#"
+//- /src/main.erl
+-module(main).
+-behaviour(gen_server).
+-export([init/1, init/2, handle_continue/1]).
+init(Args) -> {ok, Args}.
+init(_Args, _Extra) ->
+    receive {done, R} -> R end.
+handle_continue(_Continue) ->
+    receive {done, R} -> R end.
+"#,
+        );
+    }
+
+    #[test]
+    fn related_information_points_to_callback() {
+        let (analysis, file_id) = fixture::single_file(
+            r#"
+-module(main).
+-behaviour(gen_server).
+-export([init/1]).
+init(Args) ->
+    receive go -> {ok, Args} end.
+"#,
+        );
+        let text = analysis.file_text(file_id).unwrap();
+        let start = TextSize::from(text.find("init(Args)").unwrap() as u32);
+        let diagnostics = analysis.diagnostics(&config(), file_id, true).unwrap();
+        assert_eq!(diagnostics.len(), 2);
+        for diagnostic in diagnostics {
+            let related_info = diagnostic.related_info.expect("related information");
+            assert_eq!(related_info.len(), 1);
+            assert_eq!(related_info[0].message, "Enclosing callback init/1");
+            assert_eq!(
+                related_info[0].range,
+                TextRange::at(start, TextSize::of("init"))
+            );
+        }
+    }
+}
@@ -15,7 +15,9 @@
 
 use elp_ide_db::elp_base_db::FileId;
 use elp_ide_db::source_change::SourceChange;
+use elp_syntax::algo;
 use elp_syntax::ast;
+use elp_syntax::AstNode;
 use elp_syntax::TextRange;
 use hir::Expr;
 use hir::ExprId;
@@ -107,9 +109,12 @@ pub fn replace_call_site_if_args_match(
 pub enum Replacement {
     UseOk,
     UseCallArg(u32),
+    /// Keep the call and its arguments, but call the function with
+    /// the given name instead, in the same module.
+    RenameCall(&'static str),
 }
 
-fn replace_call(
+pub(crate) fn replace_call(
     replacement: Replacement,
     sema: &Semantic,
     def_fb: &mut InFunctionBody<&FunctionDef>,
@@ -117,8 +122,8 @@ fn replace_call(
     args: &[ExprId],
     call_loc: &TextRange,
 ) -> Option<TextEdit> {
-    let opt_replacement_str = match replacement {
-        Replacement::UseOk => Some("ok".to_string()),
+    let (range, replacement_str) = match replacement {
+        Replacement::UseOk => (*call_loc, "ok".to_string()),
         Replacement::UseCallArg(n) => {
             let &nth = args.get(n as usize)?;
 
@@ -126,14 +131,22 @@ fn replace_call(
             let source_file = sema.parse(file_id);
 
             let nth_str = body_map.expr(nth)?.to_node(&source_file)?.to_string();
-            Some(nth_str)
+            (*call_loc, nth_str)
+        }
+        Replacement::RenameCall(name) => {
+            let source_file = sema.parse(file_id);
+            let call =
+                algo::find_node_at_range::<ast::Call>(source_file.value.syntax(), *call_loc)?;
+            let fun_range = match call.expr()? {
+                ast::Expr::Remote(remote) => remote.fun()?.syntax().text_range(),
+                expr => expr.syntax().text_range(),
+            };
+            (fun_range, name.to_string())
         }
     };
-    opt_replacement_str.map(|replacement_str| {
-        let mut edit_builder = TextEdit::builder();
-        edit_builder.replace(*call_loc, replacement_str);
-        edit_builder.finish()
-    })
+    let mut edit_builder = TextEdit::builder();
+    edit_builder.replace(range, replacement_str);
+    Some(edit_builder.finish())
 }
 
 #[allow(dead_code)]
@@ -0,0 +1,483 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+// Diagnostic: undefined-function
+//
+// Return a warning if a remote call, or a `fun M:F/A` capture, refers to
+// a module that does not exist, to a function that is not defined in the
+// module, or to a function that is defined but not exported. Where a
+// similar module or function exists, it is suggested as a fix.
+
+use elp_ide_assists::Assist;
+use elp_ide_db::elp_base_db::FileId;
+use elp_ide_db::source_change::SourceChange;
+use elp_syntax::ast;
+use elp_syntax::AstNode;
+use elp_syntax::SourceFile;
+use elp_syntax::TextRange;
+use elp_syntax::TextSize;
+use hir::BodySourceMap;
+use hir::CallTarget;
+use hir::Expr;
+use hir::ExprId;
+use hir::FunctionDef;
+use hir::InFile;
+use hir::InFunctionBody;
+use hir::Literal;
+use hir::Name;
+use hir::NameArity;
+use hir::Semantic;
+use text_edit::TextEdit;
+
+use crate::diagnostics::closest_match;
+use crate::diagnostics::DiagnosticCode;
+use crate::fix;
+use crate::Diagnostic;
+
+pub(crate) fn undefined_function(acc: &mut Vec<Diagnostic>, sema: &Semantic, file_id: FileId) {
+    sema.def_map(file_id)
+        .get_functions()
+        .iter()
+        .for_each(|(_arity, def)| {
+            if def.file.file_id == file_id {
+                check_function(acc, sema, def)
+            }
+        });
+}
+
+fn check_function(acc: &mut Vec<Diagnostic>, sema: &Semantic, def: &FunctionDef) {
+    let def_fb = def.in_function_body(sema.db, def);
+    let body_map = def_fb.get_body_map(sema.db);
+    let source_file = sema.parse(def.file.file_id);
+    let ctx = CheckCtx {
+        sema,
+        def_fb: &def_fb,
+        body_map: &body_map,
+        source_file: &source_file,
+    };
+
+    def_fb.fold_function(
+        (),
+        &mut |_acc, _, fold_ctx| match fold_ctx.expr {
+            Expr::Call {
+                target: CallTarget::Remote { module, name },
+                args,
+            } => {
+                let arity = Arity::Call(fold_ctx.expr_id);
+                acc.extend(ctx.check_target(module, name, args.len() as u32, arity));
+            }
+            Expr::CaptureFun {
+                target: CallTarget::Remote { module, name },
+                arity,
+            } => {
+                if let Expr::Literal(Literal::Integer(value)) = &def_fb[arity] {
+                    if let Ok(value) = u32::try_from(*value) {
+                        let arity = Arity::Capture(arity);
+                        acc.extend(ctx.check_target(module, name, value, arity));
+                    }
+                }
+            }
+            _ => {}
+        },
+        &mut |_acc, _, _| (),
+    );
+}
+
+/// Where the arity of a call target comes from.
+#[derive(Debug, Clone, Copy)]
+enum Arity {
+    /// The arguments of the call
+    Call(ExprId),
+    /// The arity of a `fun M:F/A` capture
+    Capture(ExprId),
+}
+
+struct CheckCtx<'a> {
+    sema: &'a Semantic<'a>,
+    def_fb: &'a InFunctionBody<&'a FunctionDef>,
+    body_map: &'a BodySourceMap,
+    source_file: &'a InFile<SourceFile>,
+}
+
+impl<'a> CheckCtx<'a> {
+    fn file_id(&self) -> FileId {
+        self.source_file.file_id
+    }
+
+    /// The name and range of an expression written as a literal atom in
+    /// the source. Atoms coming from macro expansions are not considered.
+    fn atom(&self, expr: ExprId) -> Option<(Name, TextRange)> {
+        let name = self.def_fb.as_atom_name(self.sema.db, &expr)?;
+        match self.body_map.expr(expr)?.to_node(self.source_file)? {
+            ast::Expr::ExprMax(ast::ExprMax::Atom(atom)) => {
+                Some((name, atom.syntax().text_range()))
+            }
+            _ => None,
+        }
+    }
+
+    fn check_target(
+        &self,
+        module: ExprId,
+        name: ExprId,
+        arity: u32,
+        arity_expr: Arity,
+    ) -> Option<Diagnostic> {
+        let (module, module_range) = self.atom(module)?;
+        let (name, name_range) = self.atom(name)?;
+        let range = module_range.cover(name_range);
+        let target = match self
+            .sema
+            .resolve_module_name(self.file_id(), module.as_str())
+        {
+            Some(target) => target,
+            None => return Some(self.undefined_module(&module, module_range)),
+        };
+        let function = NameArity::new(name, arity);
+        if is_module_info(&function) {
+            return None;
+        }
+        let def_map = self.sema.def_map(target.file.file_id);
+        match def_map.get_function(&function) {
+            Some(def) if def.exported => None,
+            Some(_) => Some(Diagnostic::warning(
+                DiagnosticCode::UnexportedFunction,
+                range,
+                format!("Function '{module}:{function}' is not exported"),
+            )),
+            None => {
+                let exported: Vec<&NameArity> = def_map
+                    .get_functions()
+                    .iter()
+                    .filter(|(_, def)| def.exported)
+                    .map(|(name, _)| name)
+                    .collect();
+                Some(self.undefined_function(
+                    &module, &function, &exported, range, name_range, arity_expr,
+                ))
+            }
+        }
+    }
+
+    fn undefined_module(&self, module: &Name, range: TextRange) -> Diagnostic {
+        let modules = self
+            .sema
+            .db
+            .app_data(self.sema.db.file_source_root(self.file_id()))
+            .map(|app_data| self.sema.db.module_index(app_data.project_id).all_modules())
+            .unwrap_or_default();
+        let fixes =
+            closest_match(module.as_str(), modules.iter().map(|m| m.as_str())).map(|suggestion| {
+                let replacement = Name::from_erlang_service(suggestion).to_quoted_string();
+                vec![self.replace_fix(range, replacement, range)]
+            });
+        Diagnostic::warning(
+            DiagnosticCode::UndefinedModule,
+            range,
+            format!("Module '{module}' does not exist"),
+        )
+        .with_fixes(fixes)
+    }
+
+    fn undefined_function(
+        &self,
+        module: &Name,
+        function: &NameArity,
+        exported: &[&NameArity],
+        range: TextRange,
+        name_range: TextRange,
+        arity_expr: Arity,
+    ) -> Diagnostic {
+        let mut fixes = Vec::new();
+
+        // A function with a similar name and the same arity
+        let same_arity = exported
+            .iter()
+            .filter(|candidate| candidate.arity() == function.arity())
+            .map(|candidate| candidate.name().as_str());
+        if let Some(suggestion) = closest_match(function.name().as_str(), same_arity) {
+            let replacement = Name::from_erlang_service(suggestion).to_quoted_string();
+            fixes.push(self.replace_fix(name_range, replacement, range));
+        }
+
+        // A function with the same name and the nearest arity
+        let nearest_arity = exported
+            .iter()
+            .filter(|candidate| candidate.name() == function.name())
+            .min_by_key(|candidate| (candidate.arity() as i64 - function.arity() as i64).abs());
+        let message = match nearest_arity {
+            Some(candidate) => {
+                if let Some(edit) = self.arity_edit(arity_expr, candidate.arity()) {
+                    fixes.push(fix(
+                        "change_undefined_call_arity",
+                        &format!("Use '{module}:{candidate}'"),
+                        SourceChange::from_text_edit(self.file_id(), edit),
+                        range,
+                    ));
+                }
+                format!("Function '{module}:{function}' is undefined, did you mean '{module}:{candidate}'?")
+            }
+            None => format!("Function '{module}:{function}' is undefined"),
+        };
+
+        Diagnostic::warning(DiagnosticCode::UndefinedFunction, range, message)
+            .with_fixes(if fixes.is_empty() { None } else { Some(fixes) })
+    }
+
+    /// An edit changing the arity of a call target. Extra arguments of
+    /// a call are dropped, and missing ones added as placeholders.
+    fn arity_edit(&self, arity_expr: Arity, arity: u32) -> Option<TextEdit> {
+        let call = match arity_expr {
+            Arity::Capture(expr) => {
+                let range = self.def_fb.range_for_expr(self.sema.db, expr)?;
+                return Some(TextEdit::replace(range, arity.to_string()));
+            }
+            Arity::Call(expr) => match self.body_map.expr(expr)?.to_node(self.source_file)? {
+                ast::Expr::Call(call) => call,
+                _ => return None,
+            },
+        };
+        let arg_list = call.args()?;
+        let args: Vec<ast::Expr> = arg_list.args().collect();
+        let arity = arity as usize;
+        let mut builder = TextEdit::builder();
+        if arity < args.len() {
+            let start = match arity {
+                0 => args[0].syntax().text_range().start(),
+                _ => args[arity - 1].syntax().text_range().end(),
+            };
+            let end = args.last()?.syntax().text_range().end();
+            builder.delete(TextRange::new(start, end));
+        } else {
+            let (offset, separator) = match args.last() {
+                Some(arg) => (arg.syntax().text_range().end(), ", "),
+                None => (
+                    arg_list.syntax().text_range().start() + TextSize::of('('),
+                    "",
+                ),
+            };
+            let placeholders = (args.len() + 1..=arity)
+                .map(|idx| format!("Arg{idx}"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            builder.insert(offset, format!("{separator}{placeholders}"));
+        }
+        Some(builder.finish())
+    }
+
+    fn replace_fix(&self, range: TextRange, replacement: String, target: TextRange) -> Assist {
+        let mut builder = TextEdit::builder();
+        builder.replace(range, replacement.clone());
+        fix(
+            "replace_undefined_call_target",
+            &format!("Replace with '{replacement}'"),
+            SourceChange::from_text_edit(self.file_id(), builder.finish()),
+            target,
+        )
+    }
+}
+
+/// `module_info/0,1` are exported by every module, without being defined.
+fn is_module_info(function: &NameArity) -> bool {
+    function.name().as_str() == "module_info" && function.arity() <= 1
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::diagnostics::DiagnosticCode;
+    use crate::diagnostics::DiagnosticsConfig;
+    use crate::tests::check_diagnostics_with_config;
+    use crate::tests::check_fix_with_config;
+
+    fn config() -> DiagnosticsConfig<'static> {
+        DiagnosticsConfig::default()
+            .disable(DiagnosticCode::MissingCompileWarnMissingSpec)
+            .enable(DiagnosticCode::UndefinedModule)
+            .enable(DiagnosticCode::UndefinedFunction)
+            .enable(DiagnosticCode::UnexportedFunction)
+    }
+
+    #[track_caller]
+    fn check_diagnostics(fixture: &str) {
+        check_diagnostics_with_config(config(), fixture)
+    }
+
+    #[track_caller]
+    fn check_fix(fixture_before: &str, fixture_after: &str) {
+        check_fix_with_config(config(), fixture_before, fixture_after)
+    }
+
+    #[test]
+    fn undefined_module() {
+        check_diagnostics(
+            r#"
+//- /src/main.erl
+-module(main).
+main() ->
+    listz:map(fun(X) -> X end, []),
+ %% ^^^^^ 💡 warning: Module 'listz' does not exist
+    other:f().
+ %% ^^^^^ warning: Module 'other' does not exist
+//- /src/lists.erl
+-module(lists).
+-export([map/2]).
+map(F, L) -> [F(X) || X <- L].
+"#,
+        );
+    }
+
+    #[test]
+    fn undefined_and_unexported_functions() {
+        check_diagnostics(
+            r#"
+//- /src/main.erl
+-module(main).
+main() ->
+    lists:mapp(fun(X) -> X end, []),
+ %% ^^^^^^^^^^ 💡 warning: Function 'lists:mapp/2' is undefined
+    lists:map([]),
+ %% ^^^^^^^^^ 💡 warning: Function 'lists:map/1' is undefined, did you mean 'lists:map/2'?
+    lists:helper(),
+ %% ^^^^^^^^^^^^ warning: Function 'lists:helper/0' is not exported
+    lists:module_info(),
+    lists:map(fun(X) -> X end, []).
+//- /src/lists.erl
+-module(lists).
+-export([map/2]).
+map(F, L) -> [F(X) || X <- L].
+helper() -> ok.
+"#,
+        );
+    }
+
+    #[test]
+    fn captures() {
+        check_diagnostics(
+            r#"
+//- /src/main.erl
+-module(main).
+main() ->
+    F = fun other:foo/2,
+         %% ^^^^^^^^^ 💡 warning: Function 'other:foo/2' is undefined, did you mean 'other:foo/1'?
+    G = fun other:foo/1,
+    {F, G}.
+//- /src/other.erl
+-module(other).
+-export([foo/1]).
+foo(X) -> X.
+"#,
+        );
+    }
+
+    #[test]
+    fn fix_function_name() {
+        check_fix(
+            r#"
+//- /src/main.erl
+-module(main).
+main() ->
+    lists:ma~pp(fun(X) -> X end, []).
+//- /src/lists.erl
+-module(lists).
+-export([map/2]).
+map(F, L) -> [F(X) || X <- L].
+"#,
+            r#"
+-module(main).
+main() ->
+    lists:map(fun(X) -> X end, []).
+"#,
+        );
+    }
+
+    #[test]
+    fn fix_module_name() {
+        check_fix(
+            r#"
+//- /src/main.erl
+-module(main).
+main() ->
+    list~z:map(fun(X) -> X end, []).
+//- /src/lists.erl
+-module(lists).
+-export([map/2]).
+map(F, L) -> [F(X) || X <- L].
+"#,
+            r#"
+-module(main).
+main() ->
+    lists:map(fun(X) -> X end, []).
+"#,
+        );
+    }
+
+    #[test]
+    fn fix_capture_arity() {
+        check_fix(
+            r#"
+//- /src/main.erl
+-module(main).
+main() ->
+    fun other:f~oo/2.
+//- /src/other.erl
+-module(other).
+-export([foo/1]).
+foo(X) -> X.
+"#,
+            r#"
+-module(main).
+main() ->
+    fun other:foo/1.
+"#,
+        );
+    }
+
+    #[test]
+    fn fix_call_arity() {
+        check_fix(
+            r#"
+//- /src/main.erl
+-module(main).
+main() ->
+    other:f~oo(1, 2).
+//- /src/other.erl
+-module(other).
+-export([foo/1]).
+foo(X) -> X.
+"#,
+            r#"
+-module(main).
+main() ->
+    other:foo(1).
+"#,
+        );
+    }
+
+    #[test]
+    fn fix_call_missing_arguments() {
+        check_fix(
+            r#"
+//- /src/main.erl
+-module(main).
+main() ->
+    other:f~oo().
+//- /src/other.erl
+-module(other).
+-export([foo/2]).
+foo(X, Y) -> {X, Y}.
+"#,
+            r#"
+-module(main).
+main() ->
+    other:foo(Arg1, Arg2).
+"#,
+        );
+    }
+}
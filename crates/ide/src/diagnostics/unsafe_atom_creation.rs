@@ -0,0 +1,231 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+// Diagnostic: unsafe-atom-creation
+//
+// Return a warning if `list_to_atom/1` or `binary_to_atom/1,2` is called
+// with an argument which is not a literal, or a variable bound to one.
+// Atoms are never garbage collected, so creating them from external
+// input can exhaust the atom table and bring down the node.
+
+use elp_ide_db::elp_base_db::FileId;
+use elp_ide_db::source_change::SourceChange;
+use hir::Expr;
+use hir::ExprId;
+use hir::FunctionDef;
+use hir::InFunctionBody;
+use hir::Pat;
+use hir::Semantic;
+use hir::Var;
+
+use super::Diagnostic;
+use crate::codemod_helpers::find_call_in_function;
+use crate::codemod_helpers::FunctionMatch;
+use crate::diagnostics::replace_call::replace_call;
+use crate::diagnostics::replace_call::Replacement;
+use crate::diagnostics::DiagnosticCode;
+use crate::fix;
+
+/// How many variable bindings are followed to find a literal.
+const MAX_BINDING_DEPTH: usize = 5;
+
+pub(crate) fn unsafe_atom_creation(diags: &mut Vec<Diagnostic>, sema: &Semantic, file_id: FileId) {
+    let mfas = vec![
+        FunctionMatch::mfa("erlang", "list_to_atom", 1),
+        FunctionMatch::mfa("erlang", "binary_to_atom", 1),
+        FunctionMatch::mfa("erlang", "binary_to_atom", 2),
+    ];
+    let calls = mfas.iter().map(|mfa| (mfa, ())).collect::<Vec<_>>();
+    sema.def_map(file_id)
+        .get_functions()
+        .iter()
+        .filter(|(_, def)| def.file.file_id == file_id)
+        .for_each(|(_, def)| check_function(diags, sema, file_id, def, &calls));
+}
+
+fn check_function(
+    diags: &mut Vec<Diagnostic>,
+    sema: &Semantic,
+    file_id: FileId,
+    def: &FunctionDef,
+    calls: &[(&FunctionMatch, ())],
+) {
+    find_call_in_function(
+        diags,
+        sema,
+        def,
+        calls,
+        &move |mfa, _, _target, args, def_fb| {
+            let arg = args.first()?;
+            if is_literal(def_fb, *arg, 0) {
+                None
+            } else {
+                match mfa {
+                    FunctionMatch::MFA(mfa) => Some(mfa.name.clone()),
+                    _ => None,
+                }
+            }
+        },
+        move |sema, def_fb, _target, args, name, range| {
+            let existing = existing_atom_variant(name)?;
+            let diag = Diagnostic::warning(
+                DiagnosticCode::UnsafeAtomCreation,
+                range,
+                format!(
+                    "Atom created from a non-literal value in '{name}', use '{existing}' instead"
+                ),
+            );
+            let fixes = replace_call(
+                Replacement::RenameCall(existing),
+                sema,
+                def_fb,
+                file_id,
+                args,
+                &range,
+            )
+            .map(|edit| {
+                vec![fix(
+                    "use_existing_atom",
+                    &format!("Use '{existing}'"),
+                    SourceChange::from_text_edit(file_id, edit),
+                    range,
+                )]
+            });
+            Some(diag.with_fixes(fixes))
+        },
+    );
+}
+
+fn existing_atom_variant(name: &str) -> Option<&'static str> {
+    match name {
+        "list_to_atom" => Some("list_to_existing_atom"),
+        "binary_to_atom" => Some("binary_to_existing_atom"),
+        _ => None,
+    }
+}
+
+/// Whether the expression is a literal, such as a string or a binary
+/// with only literal segments, or a variable only ever bound to one.
+fn is_literal(def_fb: &InFunctionBody<&FunctionDef>, expr_id: ExprId, depth: usize) -> bool {
+    match &def_fb[expr_id] {
+        Expr::Literal(_) => true,
+        Expr::List { exprs, tail } => {
+            exprs.iter().all(|expr| is_literal(def_fb, *expr, depth))
+                && tail.map_or(true, |tail| is_literal(def_fb, tail, depth))
+        }
+        Expr::Binary { segs } => segs.iter().all(|seg| is_literal(def_fb, seg.elem, depth)),
+        Expr::MacroCall { expansion, .. } => is_literal(def_fb, *expansion, depth),
+        Expr::Var(var) if depth < MAX_BINDING_DEPTH => is_bound_to_literal(def_fb, var, depth + 1),
+        _ => false,
+    }
+}
+
+/// Whether every binding of the variable in the function is a match
+/// against a literal, e.g. `Name = "foo"`.
+fn is_bound_to_literal(def_fb: &InFunctionBody<&FunctionDef>, var: &Var, depth: usize) -> bool {
+    let literal_bindings = def_fb.clone().fold_function(
+        0,
+        &mut |acc, _, ctx| match ctx.expr {
+            Expr::Match { lhs, rhs } => match &def_fb[lhs] {
+                Pat::Var(bound) if bound == var && is_literal(def_fb, rhs, depth) => acc + 1,
+                _ => acc,
+            },
+            _ => acc,
+        },
+        &mut |acc, _, _| acc,
+    );
+    let bindings = def_fb
+        .clone()
+        .fold_function(0, &mut |acc, _, _| acc, &mut |acc, _, ctx| match ctx.pat {
+            Pat::Var(bound) if &bound == var => acc + 1,
+            _ => acc,
+        });
+    literal_bindings > 0 && literal_bindings == bindings
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::tests::check_diagnostics;
+    use crate::tests::check_fix;
+
+    #[test]
+    fn unsafe_atom_creation() {
+        check_diagnostics(
+            r#"
+//- /src/main.erl
+-module(main).
+-export([main/2]).
+main(Name, Bin) ->
+    list_to_atom(Name),
+%%  ^^^^^^^^^^^^^^^^^^ 💡 warning: Atom created from a non-literal value in 'list_to_atom', use 'list_to_existing_atom' instead
+    erlang:binary_to_atom(Bin, utf8),
+%%  ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ 💡 warning: Atom created from a non-literal value in 'binary_to_atom', use 'binary_to_existing_atom' instead
+    binary_to_atom(<<Bin/binary, "_suffix">>).
+%%  ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ 💡 warning: Atom created from a non-literal value in 'binary_to_atom', use 'binary_to_existing_atom' instead
+"#,
+        );
+    }
+
+    #[test]
+    fn literal_arguments_are_safe() {
+        check_diagnostics(
+            r#"
+//- /src/main.erl
+-module(main).
+-export([main/0]).
+-define(NAME, "name").
+main() ->
+    Name = "foo",
+    Other = Name,
+    list_to_atom("foo"),
+    list_to_atom(?NAME),
+    list_to_atom(Name),
+    list_to_atom(Other),
+    binary_to_atom(<<"foo">>, utf8),
+    erlang:list_to_atom([$a, $b]).
+"#,
+        );
+    }
+
+    #[test]
+    fn rebound_variable_is_unsafe() {
+        check_diagnostics(
+            r#"
+//- /src/main.erl
+-module(main).
+-export([main/1]).
+main(X) ->
+    case X of
+        {ok, Name} -> list_to_atom(Name);
+%%                    ^^^^^^^^^^^^^^^^^^ 💡 warning: Atom created from a non-literal value in 'list_to_atom', use 'list_to_existing_atom' instead
+        _ -> Name = "default", Name
+    end.
+"#,
+        );
+    }
+
+    #[test]
+    fn fix_uses_existing_atom() {
+        check_fix(
+            r#"
+-module(main).
+-export([main/1]).
+main(Bin) ->
+    erlang:binary_to_~atom(Bin, utf8).
+"#,
+            r#"
+-module(main).
+-export([main/1]).
+main(Bin) ->
+    erlang:binary_to_existing_atom(Bin, utf8).
+"#,
+        );
+    }
+}
@@ -0,0 +1,459 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+// Diagnostic: unused-exported-function
+//
+// Return a warning if a function listed in an `-export` attribute is never
+// used outside of its own definition anywhere in the project.
+//
+// Functions that are called implicitly are considered used: callbacks of
+// the behaviours implemented by the module, the `-on_load` function, the
+// callbacks of a Common Test suite and its test cases, well-known OTP
+// callbacks and functions referenced by a literal `{M, F, Args}` triple,
+// as used in `apply/3`, `spawn/3` and similar.
+//
+// As it searches the whole project, it is only computed when enabled. The
+// search is narrowed down to the files mentioning the function name, from
+// the project atom index.
+
+use elp_ide_assists::helpers::function_ranges;
+use elp_ide_assists::Assist;
+use elp_ide_db::elp_base_db::FileId;
+use elp_ide_db::source_change::SourceChange;
+use elp_ide_db::AtomIndexDatabase;
+use elp_ide_db::RootDatabase;
+use elp_ide_db::SearchScope;
+use elp_ide_db::SymbolDefinition;
+use elp_syntax::ast;
+use elp_syntax::AstNode;
+use elp_syntax::SyntaxKind;
+use elp_syntax::TextRange;
+use elp_syntax::TextSize;
+use fxhash::FxHashSet;
+use hir::FunctionDef;
+use hir::NameArity;
+use hir::Semantic;
+use lazy_static::lazy_static;
+use text_edit::TextEdit;
+
+use crate::common_test;
+use crate::diagnostics::DiagnosticCode;
+use crate::fix;
+use crate::Diagnostic;
+
+pub(crate) fn unused_exported_function(
+    acc: &mut Vec<Diagnostic>,
+    sema: &Semantic,
+    db: &RootDatabase,
+    file_id: FileId,
+) -> Option<()> {
+    let module = sema.module_name(file_id)?;
+    let module = module.as_str();
+    let project_id = sema
+        .db
+        .app_data(sema.db.file_source_root(file_id))?
+        .project_id;
+    let project_atoms = db.project_atoms(project_id);
+    let source_file = sema.parse(file_id);
+    let form_list = sema.db.file_form_list(file_id);
+    let def_map = sema.def_map(file_id);
+    let implicit = implicitly_used(sema, file_id, module)?;
+
+    for (_, export) in form_list.exports() {
+        let ast_export = export.form_id.get(&source_file.value);
+        let funs: Vec<ast::Fa> = ast_export.funs().collect();
+        for entry_id in export.entries.clone() {
+            let entry = &form_list[entry_id];
+            if funs.get(entry.idx as usize).is_none() {
+                continue;
+            }
+            let def = match def_map.get_function(&entry.name) {
+                Some(def) if def.file.file_id == file_id => def,
+                _ => continue,
+            };
+            if implicit.contains(&entry.name) || project_atoms.is_mfa_target(module, &entry.name) {
+                continue;
+            }
+            let files = project_atoms.files_with(entry.name.name().as_str());
+            let scope = SearchScope::files(files.iter().copied());
+            if is_used(sema, def, Some(&scope)) {
+                continue;
+            }
+            acc.push(make_diagnostic(
+                sema,
+                file_id,
+                &ast_export,
+                &funs,
+                entry.idx as usize,
+                def,
+            ));
+        }
+    }
+    Some(())
+}
+
+lazy_static! {
+    /// Well-known OTP callbacks, which are live even when the behaviour
+    /// itself cannot be resolved.
+    static ref OTP_CALLBACKS: FxHashSet<(&'static str, u32)> = {
+        vec![
+            ("init", 1),
+            ("handle_call", 3),
+            ("handle_cast", 2),
+            ("handle_info", 2),
+            ("handle_continue", 2),
+            ("handle_event", 4),
+            ("terminate", 2),
+            ("terminate", 3),
+            ("code_change", 3),
+            ("code_change", 4),
+            ("format_status", 1),
+            ("format_status", 2),
+            ("callback_mode", 0),
+            ("start", 2),
+            ("stop", 1),
+        ]
+        .into_iter()
+        .collect()
+    };
+
+    /// Functions of a Common Test suite called by the framework.
+    static ref CT_CALLBACKS: FxHashSet<(&'static str, u32)> = {
+        vec![
+            ("all", 0),
+            ("groups", 0),
+            ("suite", 0),
+            ("group", 1),
+            ("init_per_suite", 1),
+            ("end_per_suite", 1),
+            ("init_per_group", 2),
+            ("end_per_group", 2),
+            ("init_per_testcase", 2),
+            ("end_per_testcase", 2),
+        ]
+        .into_iter()
+        .collect()
+    };
+}
+
+/// The exported functions of the module which are called implicitly, by
+/// the runtime or by a framework. Returns `None` if this cannot be
+/// determined, in which case no diagnostics should be reported.
+fn implicitly_used(sema: &Semantic, file_id: FileId, module: &str) -> Option<FxHashSet<NameArity>> {
+    let def_map = sema.def_map(file_id);
+    let form_list = sema.db.file_form_list(file_id);
+    let mut res = FxHashSet::default();
+
+    for name in def_map.get_exported_functions() {
+        if OTP_CALLBACKS.contains(&(name.name().as_str(), name.arity())) {
+            res.insert(name.clone());
+        }
+    }
+
+    for (_, behaviour) in form_list.behaviour_attributes() {
+        if let Some(behaviour) = sema.resolve_module_name(file_id, behaviour.name.as_str()) {
+            let behaviour_def_map = sema.def_map(behaviour.file.file_id);
+            res.extend(behaviour_def_map.get_callbacks().keys().cloned());
+        }
+    }
+
+    res.extend(on_load_functions(sema, file_id));
+
+    if module.ends_with("_SUITE") {
+        let runnables = common_test::runnable_names(sema, file_id).ok()?;
+        for name in def_map.get_exported_functions() {
+            let is_test_info =
+                name.arity() == 0 && runnables.contains(&NameArity::new(name.name().clone(), 1));
+            if CT_CALLBACKS.contains(&(name.name().as_str(), name.arity()))
+                || runnables.contains(name)
+                || is_test_info
+            {
+                res.insert(name.clone());
+            }
+        }
+    }
+
+    Some(res)
+}
+
+/// The functions named in an `-on_load` attribute, which are called by the
+/// runtime when the module is loaded.
+pub(super) fn on_load_functions(sema: &Semantic, file_id: FileId) -> FxHashSet<NameArity> {
+    let def_map = sema.def_map(file_id);
+    let form_list = sema.db.file_form_list(file_id);
+    let mut res = FxHashSet::default();
+    for (_, attribute) in form_list.attributes() {
+        if attribute.name.as_str() == "on_load" {
+            let ast_attribute = attribute.form_id.get(&sema.parse(file_id).value);
+            if let Some(value) = ast_attribute.value() {
+                let text: String = value
+                    .syntax()
+                    .text()
+                    .to_string()
+                    .chars()
+                    .filter(|c| !c.is_whitespace())
+                    .collect();
+                res.extend(
+                    def_map
+                        .get_functions()
+                        .keys()
+                        .filter(|name| text == format!("{}/{}", name.name(), name.arity()))
+                        .cloned(),
+                );
+            }
+        }
+    }
+    res
+}
+
+/// Whether the function is called or referenced anywhere in the given
+/// scope, or in its default search scope, other than from its own body.
+/// References from `-export` attributes and from specs are not direct, so
+/// are not considered.
+pub(super) fn is_used(sema: &Semantic, def: &FunctionDef, scope: Option<&SearchScope>) -> bool {
+    let own_range = def.source(sema.db.upcast()).syntax().text_range();
+    let mut usages = SymbolDefinition::Function(def.clone())
+        .usages(sema)
+        .direct_only();
+    if let Some(scope) = scope {
+        usages.set_scope(scope);
+    }
+    let usages = usages.all();
+    usages.iter().any(|(file_id, names)| {
+        file_id != def.file.file_id
+            || names
+                .iter()
+                .any(|name| !own_range.contains_range(name.syntax().text_range()))
+    })
+}
+
+fn make_diagnostic(
+    sema: &Semantic,
+    file_id: FileId,
+    export: &ast::ExportAttribute,
+    funs: &[ast::Fa],
+    idx: usize,
+    def: &FunctionDef,
+) -> Diagnostic {
+    let name = &def.function.name;
+    let range = funs[idx].syntax().text_range();
+    let export_range = export_entry_range(export, funs, idx);
+
+    let mut builder = TextEdit::builder();
+    builder.delete(export_range);
+    let remove_export = fix(
+        "remove_unused_export",
+        &format!("Remove export of {name}"),
+        SourceChange::from_text_edit(file_id, builder.finish()),
+        range,
+    );
+
+    let fixes = vec![
+        remove_export,
+        remove_export_and_function(sema, file_id, export_range, range, def),
+    ];
+
+    Diagnostic::warning(
+        DiagnosticCode::UnusedExportedFunction,
+        range,
+        format!("Exported function {name} is never used"),
+    )
+    .with_fixes(Some(fixes))
+}
+
+fn remove_export_and_function(
+    sema: &Semantic,
+    file_id: FileId,
+    export_range: TextRange,
+    range: TextRange,
+    def: &FunctionDef,
+) -> Assist {
+    let name = &def.function.name;
+    let ast_fun = def.source(sema.db.upcast());
+    let function_ranges = function_ranges(sema, file_id, def, &ast_fun);
+    let mut builder = TextEdit::builder();
+    builder.delete(export_range);
+    function_ranges.ranges().for_each(|range| {
+        builder.delete(range);
+    });
+    fix(
+        "remove_unused_exported_function",
+        &format!("Remove export of {name} and the function"),
+        SourceChange::from_text_edit(file_id, builder.finish()),
+        range,
+    )
+}
+
+/// The range to delete to remove the given entry from the export list,
+/// including a separating comma. If it is the only entry, the whole
+/// attribute is removed.
+fn export_entry_range(export: &ast::ExportAttribute, funs: &[ast::Fa], idx: usize) -> TextRange {
+    let fa_range = funs[idx].syntax().text_range();
+    if funs.len() == 1 {
+        let syntax = export.syntax();
+        let range = syntax.text_range();
+        match syntax.last_token().and_then(|token| token.next_token()) {
+            Some(next)
+                if next.kind() == SyntaxKind::WHITESPACE && next.text().starts_with('\n') =>
+            {
+                TextRange::new(range.start(), range.end() + TextSize::from(1))
+            }
+            _ => range,
+        }
+    } else if let Some(next) = funs.get(idx + 1) {
+        TextRange::new(fa_range.start(), next.syntax().text_range().start())
+    } else {
+        TextRange::new(funs[idx - 1].syntax().text_range().end(), fa_range.end())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::diagnostics::DiagnosticCode;
+    use crate::diagnostics::DiagnosticsConfig;
+    use crate::tests::check_diagnostics_with_config;
+    use crate::tests::check_fix_with_config;
+    use crate::tests::check_nth_fix;
+
+    fn config() -> DiagnosticsConfig<'static> {
+        DiagnosticsConfig::default()
+            .disable(DiagnosticCode::MissingCompileWarnMissingSpec)
+            .enable(DiagnosticCode::UnusedExportedFunction)
+    }
+
+    #[track_caller]
+    fn check_diagnostics(fixture: &str) {
+        check_diagnostics_with_config(config(), fixture)
+    }
+
+    #[track_caller]
+    fn check_fix(fixture_before: &str, fixture_after: &str) {
+        check_fix_with_config(config(), fixture_before, fixture_after)
+    }
+
+    #[test]
+    fn unused_export() {
+        check_diagnostics(
+            r#"
+//- /src/main.erl
+-module(main).
+-export([used/0, unused/0]).
+              %% ^^^^^^^^ 💡 warning: Exported function unused/0 is never used
+used() -> ok.
+unused() -> unused().
+//- /src/other.erl
+-module(other).
+-export([main/0]).
+      %% ^^^^^^ 💡 warning: Exported function main/0 is never used
+main() -> main:used().
+"#,
+        );
+    }
+
+    #[test]
+    fn behaviour_callbacks_and_on_load_are_used() {
+        check_diagnostics(
+            r#"
+//- /src/main.erl
+-module(main).
+-behaviour(my_behaviour).
+-on_load(load/0).
+-export([my_callback/1, load/0, init/1]).
+my_callback(X) -> X.
+load() -> ok.
+init(X) -> {ok, X}.
+//- /src/my_behaviour.erl
+-module(my_behaviour).
+-callback my_callback(term()) -> term().
+"#,
+        );
+    }
+
+    #[test]
+    fn literal_mfa_is_used() {
+        check_diagnostics(
+            r#"
+//- /src/main.erl
+-module(main).
+-export([a/1, b/0, c/2]).
+                %% ^^^ 💡 warning: Exported function c/2 is never used
+a(_) -> ok.
+b() -> ok.
+c(_, _) -> ok.
+//- /src/other.erl
+-module(other).
+-export([main/0]).
+      %% ^^^^^^ 💡 warning: Exported function main/0 is never used
+main() ->
+    apply(main, a, [1]),
+    spawn(main, c, [1]),
+    {main, b, []}.
+"#,
+        );
+    }
+
+    #[test]
+    fn common_test_suite() {
+        check_diagnostics(
+            r#"
+//- /src/main_SUITE.erl
+-module(main_SUITE).
+-export([all/0, init_per_suite/1, a/1, a/0, helper/0]).
+                                         %% ^^^^^^^^ 💡 warning: Exported function helper/0 is never used
+all() -> [a].
+init_per_suite(Config) -> Config.
+a() -> [{timetrap, 10}].
+a(_Config) -> ok.
+helper() -> ok.
+"#,
+        );
+    }
+
+    #[test]
+    fn fix_removes_export() {
+        check_fix(
+            r#"
+-module(main).
+-export([main/0, unu~sed/0]).
+main() -> used().
+used() -> ok.
+unused() -> ok.
+"#,
+            r#"
+-module(main).
+-export([main/0]).
+main() -> used().
+used() -> ok.
+unused() -> ok.
+"#,
+        );
+    }
+
+    #[test]
+    fn fix_removes_export_and_function() {
+        check_nth_fix(
+            1,
+            r#"
+-module(main).
+-export([main/0, unu~sed/0]).
+main() -> ok.
+%% @doc Not used anywhere.
+-spec unused() -> ok.
+unused() -> ok.
+"#,
+            r#"
+-module(main).
+-export([main/0]).
+main() -> ok.
+"#,
+            config(),
+        );
+    }
+}
@@ -0,0 +1,312 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+// Diagnostic: unused-function
+//
+// Return a warning if a function which is not exported is never called or
+// referenced, e.g. via `fun f/1`, other than from its own body.
+//
+// This is the native equivalent of the Erlang compiler warning L1230, so
+// it is updated as the file is edited. Functions exported via
+// `-compile(export_all)`, the `-on_load` function and functions listed in
+// a `nowarn_unused_function` compile option are not reported.
+
+use std::hash::Hash;
+
+use elp_ide_assists::helpers::function_ranges;
+use elp_ide_assists::helpers::ExportBuilder;
+use elp_ide_assists::Assist;
+use elp_ide_db::elp_base_db::FileId;
+use elp_ide_db::source_change::SourceChange;
+use elp_ide_db::source_change::SourceChangeBuilder;
+use elp_syntax::AstNode;
+use elp_syntax::TextRange;
+use fxhash::FxHashSet;
+use hir::known;
+use hir::Body;
+use hir::FunctionDef;
+use hir::InFile;
+use hir::Literal;
+use hir::Name;
+use hir::NameArity;
+use hir::Semantic;
+use hir::Term;
+use hir::TermId;
+use text_edit::TextEdit;
+
+use super::unused_exported_function::is_used;
+use super::unused_exported_function::on_load_functions;
+use crate::diagnostics::DiagnosticCode;
+use crate::fix;
+use crate::Diagnostic;
+
+pub(crate) fn unused_function(
+    acc: &mut Vec<Diagnostic>,
+    sema: &Semantic,
+    file_id: FileId,
+) -> Option<()> {
+    let suppressed = suppressed_functions(sema, file_id)?;
+    let on_load = on_load_functions(sema, file_id);
+    let def_map = sema.def_map(file_id);
+
+    let mut diagnostics: Vec<Diagnostic> = def_map
+        .get_functions()
+        .iter()
+        .filter(|(name, def)| {
+            def.file.file_id == file_id
+                && !def.exported
+                && !suppressed.contains(name)
+                && !on_load.contains(name)
+                && !is_used(sema, def, None)
+        })
+        .filter_map(|(_, def)| make_diagnostic(sema, file_id, def))
+        .collect();
+    diagnostics.sort_by_key(|d| d.range.start());
+    acc.extend(diagnostics);
+    Some(())
+}
+
+/// The functions listed in `nowarn_unused_function` compile options.
+/// Returns `None` if the option is given without a list of functions, in
+/// which case no function is reported.
+fn suppressed_functions(sema: &Semantic, file_id: FileId) -> Option<FxHashSet<NameArity>> {
+    nowarn_option(
+        sema,
+        file_id,
+        &known::nowarn_unused_function,
+        |body, term, acc| collect_name_arities(sema, body, term, acc),
+    )
+}
+
+/// The items given to the `option` compile option, e.g. `nowarn_unused_type`
+/// in `{nowarn_unused_type, [t/0]}`, as gathered by `collect`. Returns
+/// `None` if the option is given on its own, applying to the whole module.
+pub(super) fn nowarn_option<T: Eq + Hash>(
+    sema: &Semantic,
+    file_id: FileId,
+    option: &Name,
+    collect: impl Fn(&Body, TermId, &mut FxHashSet<T>),
+) -> Option<FxHashSet<T>> {
+    let form_list = sema.db.file_form_list(file_id);
+    let mut res = FxHashSet::default();
+    for (idx, _) in form_list.compile_attributes() {
+        let co = sema.db.compile_body(InFile::new(file_id, idx));
+        let options = match &co.body[co.value] {
+            Term::List { exprs, .. } => exprs.clone(),
+            _ => vec![co.value],
+        };
+        for term in options {
+            match &co.body[term] {
+                Term::Literal(Literal::Atom(atom)) if &sema.db.lookup_atom(*atom) == option => {
+                    return None;
+                }
+                Term::Tuple { exprs } if exprs.len() == 2 => {
+                    if is_atom(sema, &co.body, exprs[0], option) {
+                        collect(&co.body, exprs[1], &mut res);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    Some(res)
+}
+
+/// Collect a single `f/N` or `{f, N}`, or a list of them.
+pub(super) fn collect_name_arities(
+    sema: &Semantic,
+    body: &Body,
+    term: TermId,
+    acc: &mut FxHashSet<NameArity>,
+) {
+    match &body[term] {
+        Term::List { exprs, .. } => exprs
+            .iter()
+            .for_each(|expr| collect_name_arities(sema, body, *expr, acc)),
+        Term::Tuple { exprs } if exprs.len() == 2 => {
+            if let (Term::Literal(Literal::Atom(name)), Term::Literal(Literal::Integer(arity))) =
+                (&body[exprs[0]], &body[exprs[1]])
+            {
+                if let Ok(arity) = u32::try_from(*arity) {
+                    acc.insert(NameArity::new(sema.db.lookup_atom(*name), arity));
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn is_atom(sema: &Semantic, body: &Body, term: TermId, expected: &Name) -> bool {
+    match &body[term] {
+        Term::Literal(Literal::Atom(atom)) => &sema.db.lookup_atom(*atom) == expected,
+        _ => false,
+    }
+}
+
+fn make_diagnostic(sema: &Semantic, file_id: FileId, def: &FunctionDef) -> Option<Diagnostic> {
+    let name = &def.function.name;
+    let ast_fun = def.source(sema.db.upcast());
+    let range = ast_fun.name()?.syntax().text_range();
+
+    let mut builder = TextEdit::builder();
+    function_ranges(sema, file_id, def, &ast_fun)
+        .ranges()
+        .for_each(|range| builder.delete(range));
+    let remove_function = fix(
+        "delete_function",
+        &format!("Remove the unused function `{name}`"),
+        SourceChange::from_text_edit(file_id, builder.finish()),
+        range,
+    );
+
+    let fixes = vec![remove_function, export_function(sema, file_id, name, range)];
+
+    Some(
+        Diagnostic::warning(
+            DiagnosticCode::UnusedFunction,
+            range,
+            format!("Function {name} is unused"),
+        )
+        .with_fixes(Some(fixes)),
+    )
+}
+
+fn export_function(sema: &Semantic, file_id: FileId, name: &NameArity, range: TextRange) -> Assist {
+    let funs = [name.clone()];
+    let mut builder = SourceChangeBuilder::new(file_id);
+    ExportBuilder::new(sema, file_id, &funs, &mut builder).finish();
+    fix(
+        "export_function",
+        &format!("Export the function `{name}`"),
+        builder.finish(),
+        range,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::diagnostics::DiagnosticCode;
+    use crate::diagnostics::DiagnosticsConfig;
+    use crate::tests::check_diagnostics_with_config;
+    use crate::tests::check_fix_with_config;
+    use crate::tests::check_nth_fix;
+
+    fn config() -> DiagnosticsConfig<'static> {
+        DiagnosticsConfig::default()
+            .disable(DiagnosticCode::MissingCompileWarnMissingSpec)
+            .enable(DiagnosticCode::UnusedFunction)
+    }
+
+    #[track_caller]
+    fn check_diagnostics(fixture: &str) {
+        check_diagnostics_with_config(config(), fixture)
+    }
+
+    #[test]
+    fn unused_function() {
+        check_diagnostics(
+            r#"
+    -module(main).
+    -export([main/0]).
+    main() -> used(1), lists:map(fun referenced/1, []).
+    used(X) -> X.
+    referenced(X) -> X.
+    unused(X) -> X.
+ %% ^^^^^^ 💡 warning: Function unused/1 is unused
+    recursive(0) -> ok;
+ %% ^^^^^^^^^ 💡 warning: Function recursive/1 is unused
+    recursive(N) -> recursive(N - 1).
+"#,
+        );
+    }
+
+    #[test]
+    fn export_all_and_on_load() {
+        check_diagnostics(
+            r#"
+-module(main).
+-compile([export_all]).
+-on_load(load/0).
+load() -> ok.
+foo() -> ok.
+"#,
+        );
+        check_diagnostics(
+            r#"
+-module(main).
+-on_load(load/0).
+load() -> ok.
+"#,
+        );
+    }
+
+    #[test]
+    fn nowarn_unused_function() {
+        check_diagnostics(
+            r#"
+    -module(main).
+    -compile({nowarn_unused_function, [foo/0, {bar, 1}]}).
+    foo() -> ok.
+    bar(_) -> ok.
+    baz() -> ok.
+ %% ^^^ 💡 warning: Function baz/0 is unused
+"#,
+        );
+        check_diagnostics(
+            r#"
+-module(main).
+-compile([nowarn_unused_function]).
+foo() -> ok.
+"#,
+        );
+    }
+
+    #[test]
+    fn fix_removes_function() {
+        check_fix_with_config(
+            config(),
+            r#"
+-module(main).
+-export([main/0]).
+
+-spec unused() -> ok.
+un~used() -> ok.
+
+main() -> ok.
+"#,
+            r#"
+-module(main).
+-export([main/0]).
+
+main() -> ok.
+"#,
+        );
+    }
+
+    #[test]
+    fn fix_exports_function() {
+        check_nth_fix(
+            1,
+            r#"
+-module(main).
+-export([main/0]).
+main() -> ok.
+un~used() -> ok.
+"#,
+            r#"
+-module(main).
+-export([main/0, unused/0]).
+main() -> ok.
+unused() -> ok.
+"#,
+            config(),
+        );
+    }
+}
@@ -0,0 +1,379 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+// Diagnostic: unused-record, unused-type
+//
+// Return a warning if a record or a type which is not exported is never
+// used, other than from its own definition.
+//
+// For a module, this is the native equivalent of the Erlang compiler
+// warnings L1260 and L1296. For a header, the definitions are checked
+// against every module and header which includes it, directly or
+// transitively. Headers which are not included anywhere in the project
+// are not checked, as they may be used from outside of it. Records and
+// types silenced by a `nowarn_unused_record` or `nowarn_unused_type`
+// compile option are not reported.
+
+use elp_ide_assists::helpers::extend_form_range_for_delete;
+use elp_ide_assists::Assist;
+use elp_ide_db::elp_base_db::FileId;
+use elp_ide_db::source_change::SourceChange;
+use elp_ide_db::SearchScope;
+use elp_ide_db::SymbolDefinition;
+use elp_syntax::AstNode;
+use elp_syntax::SyntaxNode;
+use elp_syntax::TextRange;
+use fxhash::FxHashSet;
+use hir::known;
+use hir::Body;
+use hir::File;
+use hir::Literal;
+use hir::Name;
+use hir::Semantic;
+use hir::Term;
+use hir::TermId;
+use text_edit::TextEdit;
+
+use super::unused_function::collect_name_arities;
+use super::unused_function::nowarn_option;
+use crate::diagnostics::DiagnosticCode;
+use crate::fix;
+use crate::Diagnostic;
+
+pub(crate) fn unused_record_and_type(
+    acc: &mut Vec<Diagnostic>,
+    sema: &Semantic,
+    file_id: FileId,
+    ext: Option<&str>,
+) -> Option<()> {
+    match ext {
+        Some("erl") => {}
+        Some("hrl") => {
+            if !SymbolDefinition::Header(File { file_id })
+                .usages(sema)
+                .at_least_one()
+            {
+                return Some(());
+            }
+        }
+        _ => return Some(()),
+    }
+    let suppressed_records = nowarn_option(
+        sema,
+        file_id,
+        &known::nowarn_unused_record,
+        |body, term, acc| collect_names(sema, body, term, acc),
+    );
+    let suppressed_types = nowarn_option(
+        sema,
+        file_id,
+        &known::nowarn_unused_type,
+        |body, term, acc| collect_name_arities(sema, body, term, acc),
+    );
+    let def_map = sema.def_map(file_id);
+    // All the definitions local to the file share the same search scope,
+    // which for a header can be expensive to compute.
+    let mut scope = None;
+    let mut diagnostics = Vec::new();
+
+    for (name, def) in def_map.get_records() {
+        if def.file.file_id != file_id
+            || suppressed_records
+                .as_ref()
+                .map_or(true, |suppressed| suppressed.contains(name))
+        {
+            continue;
+        }
+        let source = def.source(sema.db.upcast());
+        let symbol = SymbolDefinition::Record(def.clone());
+        if !is_used(sema, symbol, source.syntax(), &mut scope) {
+            if let Some(name_range) = source.name().map(|name| name.syntax().text_range()) {
+                diagnostics.push(make_diagnostic(
+                    file_id,
+                    DiagnosticCode::UnusedRecord,
+                    source.syntax(),
+                    name_range,
+                    &name.to_string(),
+                ));
+            }
+        }
+    }
+
+    for (name, def) in def_map.get_types() {
+        if def.file.file_id != file_id
+            || def.exported
+            || suppressed_types
+                .as_ref()
+                .map_or(true, |suppressed| suppressed.contains(name))
+        {
+            continue;
+        }
+        let source = def.source(sema.db.upcast());
+        let symbol = SymbolDefinition::Type(def.clone());
+        if !is_used(sema, symbol, source.syntax(), &mut scope) {
+            if let Some(name_range) = source
+                .type_name()
+                .and_then(|type_name| type_name.name())
+                .map(|name| name.syntax().text_range())
+            {
+                diagnostics.push(make_diagnostic(
+                    file_id,
+                    DiagnosticCode::UnusedType,
+                    source.syntax(),
+                    name_range,
+                    &name.to_string(),
+                ));
+            }
+        }
+    }
+
+    diagnostics.sort_by_key(|d| d.range.start());
+    acc.extend(diagnostics);
+    Some(())
+}
+
+/// Collect a single record name, or a list of them.
+fn collect_names(sema: &Semantic, body: &Body, term: TermId, acc: &mut FxHashSet<Name>) {
+    match &body[term] {
+        Term::List { exprs, .. } => exprs
+            .iter()
+            .for_each(|expr| collect_names(sema, body, *expr, acc)),
+        Term::Literal(Literal::Atom(atom)) => {
+            acc.insert(sema.db.lookup_atom(*atom));
+        }
+        _ => {}
+    }
+}
+
+/// Whether the definition is used anywhere in its search scope, other
+/// than from the definition itself.
+fn is_used(
+    sema: &Semantic,
+    symbol: SymbolDefinition,
+    definition: &SyntaxNode,
+    scope: &mut Option<SearchScope>,
+) -> bool {
+    let file_id = symbol.file().file_id;
+    let own_range = definition.text_range();
+    let scope = scope.get_or_insert_with(|| symbol.search_scope(sema));
+    let mut usages = symbol.usages(sema);
+    usages.set_scope(scope);
+    usages.all().iter().any(|(usage_file_id, names)| {
+        usage_file_id != file_id
+            || names
+                .iter()
+                .any(|name| !own_range.contains_range(name.syntax().text_range()))
+    })
+}
+
+fn make_diagnostic(
+    file_id: FileId,
+    code: DiagnosticCode,
+    definition: &SyntaxNode,
+    name_range: TextRange,
+    name: &str,
+) -> Diagnostic {
+    let (kind, fix_id) = match code {
+        DiagnosticCode::UnusedRecord => ("Record", "delete_unused_record"),
+        _ => ("Type", "delete_unused_type"),
+    };
+    Diagnostic::warning(code, name_range, format!("{kind} {name} is unused")).with_fixes(Some(
+        vec![delete_definition(
+            file_id,
+            definition,
+            name_range,
+            fix_id,
+            &format!("Remove the unused {} {name}", kind.to_lowercase()),
+        )],
+    ))
+}
+
+fn delete_definition(
+    file_id: FileId,
+    definition: &SyntaxNode,
+    target: TextRange,
+    id: &'static str,
+    label: &str,
+) -> Assist {
+    let mut builder = TextEdit::builder();
+    builder.delete(extend_form_range_for_delete(definition));
+    fix(
+        id,
+        label,
+        SourceChange::from_text_edit(file_id, builder.finish()),
+        target,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::diagnostics::DiagnosticCode;
+    use crate::diagnostics::DiagnosticsConfig;
+    use crate::tests::check_diagnostics_with_config;
+    use crate::tests::check_fix_with_config;
+
+    fn config() -> DiagnosticsConfig<'static> {
+        DiagnosticsConfig::default()
+            .disable(DiagnosticCode::MissingCompileWarnMissingSpec)
+            .disable(DiagnosticCode::UnusedRecordField)
+            .enable(DiagnosticCode::UnusedRecord)
+            .enable(DiagnosticCode::UnusedType)
+    }
+
+    #[track_caller]
+    fn check_diagnostics(fixture: &str) {
+        check_diagnostics_with_config(config(), fixture)
+    }
+
+    #[track_caller]
+    fn check_fix(fixture_before: &str, fixture_after: &str) {
+        check_fix_with_config(config(), fixture_before, fixture_after)
+    }
+
+    #[test]
+    fn unused_record_and_type_in_module() {
+        check_diagnostics(
+            r#"
+-module(main).
+-export([main/1]).
+-export_type([exported/0]).
+-record(used, {a :: used_type()}).
+-record(unused, {a}).
+     %% ^^^^^^ 💡 warning: Record unused is unused
+-type used_type() :: integer().
+-type unused_type() :: integer().
+   %% ^^^^^^^^^^^ 💡 warning: Type unused_type/0 is unused
+-type tree() :: leaf | {tree(), tree()}.
+   %% ^^^^ 💡 warning: Type tree/0 is unused
+-type exported() :: ok.
+-spec main(#used{}) -> ok.
+main(_) -> ok.
+"#,
+        );
+    }
+
+    #[test]
+    fn nowarn_compile_options() {
+        check_diagnostics(
+            r#"
+-module(main).
+-compile([{nowarn_unused_record, [silenced]}, {nowarn_unused_type, [silenced/0]}]).
+-record(silenced, {a}).
+-record(unused, {a}).
+     %% ^^^^^^ 💡 warning: Record unused is unused
+-type silenced() :: integer().
+-type unused_type() :: integer().
+   %% ^^^^^^^^^^^ 💡 warning: Type unused_type/0 is unused
+"#,
+        );
+    }
+
+    #[test]
+    fn nowarn_compile_options_for_module() {
+        check_diagnostics(
+            r#"
+-module(main).
+-compile(nowarn_unused_record).
+-compile([nowarn_unused_type]).
+-record(unused, {a}).
+-type unused_type() :: integer().
+"#,
+        );
+    }
+
+    #[test]
+    fn unused_in_header() {
+        check_diagnostics(
+            r#"
+//- /include/main.hrl include_path:/include
+-record(used, {a}).
+-record(unused, {a}).
+     %% ^^^^^^ 💡 warning: Record unused is unused
+-type used() :: ok.
+-type unused() :: ok.
+   %% ^^^^^^ 💡 warning: Type unused/0 is unused
+//- /src/main.erl
+-module(main).
+-include("main.hrl").
+-export([main/1]).
+-spec main(used()) -> #used{}.
+main(_) -> #used{}.
+"#,
+        );
+    }
+
+    #[test]
+    fn used_from_other_includer() {
+        check_diagnostics(
+            r#"
+//- /include/main.hrl include_path:/include
+-record(used_in_a, {a}).
+-record(used_in_b, {a}).
+//- /src/a.erl
+-module(a).
+-include("main.hrl").
+-export([main/0]).
+main() -> #used_in_a{}.
+//- /src/b.erl
+-module(b).
+-include("main.hrl").
+-export([main/0]).
+main() -> #used_in_b{}.
+"#,
+        );
+    }
+
+    #[test]
+    fn header_not_included() {
+        check_diagnostics(
+            r#"
+//- /include/main.hrl
+-record(unused, {a}).
+-type unused() :: ok.
+"#,
+        );
+    }
+
+    #[test]
+    fn fix_removes_record() {
+        check_fix(
+            r#"
+-module(main).
+-record(un~used, {a}).
+-record(used, {a}).
+-export([main/0]).
+main() -> #used{}.
+"#,
+            r#"
+-module(main).
+-record(used, {a}).
+-export([main/0]).
+main() -> #used{}.
+"#,
+        );
+    }
+
+    #[test]
+    fn fix_removes_type() {
+        check_fix(
+            r#"
+-module(main).
+-type un~used() :: ok.
+-export([main/0]).
+main() -> ok.
+"#,
+            r#"
+-module(main).
+-export([main/0]).
+main() -> ok.
+"#,
+        );
+    }
+}
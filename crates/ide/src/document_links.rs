@@ -0,0 +1,240 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use elp_ide_db::elp_base_db::FileId;
+use elp_ide_db::elp_base_db::SourceDatabaseExt;
+use elp_ide_db::RootDatabase;
+use elp_syntax::ast;
+use elp_syntax::AstNode;
+use elp_syntax::TextRange;
+use elp_syntax::TextSize;
+use hir::db::MinDefDatabase;
+use hir::InFile;
+use hir::IncludeAttribute;
+use hir::Semantic;
+use lazy_static::lazy_static;
+use regex::Regex;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DocumentLinkTarget {
+    File(FileId),
+    Url(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentLink {
+    pub range: TextRange,
+    pub target: DocumentLinkTarget,
+}
+
+// Feature: Document Links
+//
+// Makes clickable the file names in `-include` and `-include_lib`
+// attributes, the modules in `-behaviour` and `-import` attributes and
+// in `parse_transform` compile options, and the URLs in edoc comments.
+pub(crate) fn document_links(db: &RootDatabase, file_id: FileId) -> Vec<DocumentLink> {
+    let sema = Semantic::new(db);
+    let source = sema.parse(file_id).value;
+    let form_list = db.file_form_list(file_id);
+    let mut links = Vec::new();
+
+    let mut add_module_link = |range: TextRange, module: &str| {
+        if let Some(module) = sema.resolve_module_name(file_id, module) {
+            links.push(DocumentLink {
+                range,
+                target: DocumentLinkTarget::File(module.file.file_id),
+            });
+        }
+    };
+
+    for (_, behaviour) in form_list.behaviour_attributes() {
+        if let Some(name) = behaviour.form_id.get(&source).name() {
+            add_module_link(name.syntax().text_range(), behaviour.name.as_str());
+        }
+    }
+    for (_, import) in form_list.imports() {
+        if let Some(module) = import.form_id.get(&source).module() {
+            add_module_link(module.syntax().text_range(), import.from.as_str());
+        }
+    }
+    for (_, attr) in form_list.compile_attributes() {
+        if let Some(options) = attr.form_id.get(&source).options() {
+            for (range, module) in parse_transforms(&options) {
+                add_module_link(range, &module);
+            }
+        }
+    }
+
+    for (idx, include) in form_list.includes() {
+        let details = match include {
+            IncludeAttribute::Include { form_id, .. } => form_id.get(&source).file(),
+            IncludeAttribute::IncludeLib { form_id, .. } => form_id.get(&source).file(),
+        };
+        let range = details
+            .map(|detail| detail.syntax().text_range())
+            .reduce(|acc, range| acc.cover(range));
+        if let (Some(range), Some(target)) = (range, db.resolve_include(InFile::new(file_id, idx)))
+        {
+            links.push(DocumentLink {
+                range,
+                target: DocumentLinkTarget::File(target),
+            });
+        }
+    }
+
+    if let Some(edoc) = db.file_edoc_comments(file_id) {
+        let text = db.file_text(file_id);
+        for header in edoc.values() {
+            for comment in header.text_ranges() {
+                links.extend(urls(&text[comment], comment.start()));
+            }
+        }
+    }
+
+    links.sort_by_key(|link| link.range.start());
+    links
+}
+
+/// The modules given in `{parse_transform, Module}` tuples, anywhere in
+/// the options of a `-compile` attribute.
+fn parse_transforms(options: &ast::Expr) -> Vec<(TextRange, String)> {
+    options
+        .syntax()
+        .descendants()
+        .filter_map(ast::Tuple::cast)
+        .filter_map(|tuple| {
+            let mut exprs = tuple.expr();
+            match (exprs.next(), exprs.next(), exprs.next()) {
+                (
+                    Some(ast::Expr::ExprMax(ast::ExprMax::Atom(key))),
+                    Some(ast::Expr::ExprMax(ast::ExprMax::Atom(module))),
+                    None,
+                ) if key.text().as_deref() == Some("parse_transform") => {
+                    Some((module.syntax().text_range(), module.text()?))
+                }
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+fn urls(comment: &str, offset: TextSize) -> impl Iterator<Item = DocumentLink> + '_ {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r#"https?://[^\s<>"'`()\[\]{}]+"#).unwrap();
+    }
+    RE.find_iter(comment).map(move |m| {
+        // Punctuation ending a sentence is not part of the URL
+        let url = m
+            .as_str()
+            .trim_end_matches(&['.', ',', ';', ':', '!', '?'][..]);
+        let start = offset + TextSize::from(m.start() as u32);
+        DocumentLink {
+            range: TextRange::at(start, TextSize::of(url)),
+            target: DocumentLinkTarget::Url(url.to_string()),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use elp_ide_db::elp_base_db::fixture::WithFixture;
+    use elp_ide_db::elp_base_db::SourceDatabase;
+    use expect_test::expect;
+    use expect_test::Expect;
+
+    use super::*;
+
+    #[track_caller]
+    fn check(fixture: &str, expect: Expect) {
+        let (db, fixture) = RootDatabase::with_fixture(fixture);
+        let file_id = fixture.files[0];
+        let text = db.file_text(file_id);
+        let actual = document_links(&db, file_id)
+            .into_iter()
+            .map(|link| {
+                let target = match link.target {
+                    DocumentLinkTarget::File(target) => db
+                        .source_root(db.file_source_root(target))
+                        .path_for_file(&target)
+                        .unwrap()
+                        .to_string(),
+                    DocumentLinkTarget::Url(url) => url,
+                };
+                format!("{} => {}\n", &text[link.range], target)
+            })
+            .collect::<String>();
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn includes() {
+        check(
+            r#"
+//- /main/src/main.erl app:main
+-module(main).
+-include("header.hrl").
+-include_lib("another/include/lib.hrl").
+-include("missing.hrl").
+//- /main/src/header.hrl app:main
+//- /another-app/include/lib.hrl app:another
+"#,
+            expect![[r#"
+                "header.hrl" => /main/src/header.hrl
+                "another/include/lib.hrl" => /another-app/include/lib.hrl
+            "#]],
+        );
+    }
+
+    #[test]
+    fn modules() {
+        check(
+            r#"
+//- /src/main.erl
+-module(main).
+-behaviour(my_behaviour).
+-behaviour(missing).
+-import(lists_ext, [foo/1]).
+-compile([debug_info, {parse_transform, my_transform}]).
+-compile({parse_transform, missing}).
+//- /src/my_behaviour.erl
+-module(my_behaviour).
+//- /src/lists_ext.erl
+-module(lists_ext).
+//- /src/my_transform.erl
+-module(my_transform).
+"#,
+            expect![[r#"
+                my_behaviour => /src/my_behaviour.erl
+                lists_ext => /src/lists_ext.erl
+                my_transform => /src/my_transform.erl
+            "#]],
+        );
+    }
+
+    #[test]
+    fn edoc_urls() {
+        check(
+            r#"
+//- /src/main.erl
+%% @doc The main module, see https://www.erlang.org/doc/.
+-module(main).
+-export([main/0]).
+
+%% A plain comment with http://example.com is not edoc.
+%% @doc Entry point, following
+%% <a href="http://example.com/spec?x=1#y">the spec</a>.
+main() -> ok.
+"#,
+            expect![[r#"
+                https://www.erlang.org/doc/ => https://www.erlang.org/doc/
+                http://example.com/spec?x=1#y => http://example.com/spec?x=1#y
+            "#]],
+        );
+    }
+}
@@ -0,0 +1,830 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::ops::Range;
+
+use elp_ide_db::elp_base_db::FileId;
+use elp_ide_db::elp_base_db::SourceDatabase;
+use elp_ide_db::RootDatabase;
+use elp_syntax::ast;
+use elp_syntax::AstNode;
+use elp_syntax::NodeOrToken;
+use elp_syntax::Parse;
+use elp_syntax::SyntaxKind;
+use elp_syntax::SyntaxKind::*;
+use elp_syntax::SyntaxNode;
+use elp_syntax::SyntaxToken;
+use elp_syntax::TextRange;
+use elp_syntax::TextSize;
+use fxhash::FxHashMap;
+use fxhash::FxHashSet;
+use text_edit::TextEdit;
+
+const INDENT: usize = 4;
+/// The line width of erlfmt, beyond which containers get broken.
+const MAX_WIDTH: usize = 100;
+
+// Feature: Format Document
+//
+// Formats an Erlang file following the layout used by erlfmt: four
+// space indentation based on the nesting of the syntax tree, single
+// spaces around binary operators and after separators, no padding
+// inside brackets, and at most one consecutive blank line.
+// Lists, tuples, maps, binaries and call arguments get one element per
+// line, with the closing bracket on a line of its own, when their first
+// element is on a new line or when they do not fit in 100 columns.
+// Otherwise they are kept on a single line. The outermost containers
+// of a line are broken first.
+// Comments are indented like the code that follows them, and the
+// bodies of macro definitions are left untouched.
+//
+// Only whitespace is changed. Files with syntax errors are not
+// formatted.
+pub(crate) fn format(
+    db: &RootDatabase,
+    file_id: FileId,
+    range: Option<TextRange>,
+) -> Option<TextEdit> {
+    let parse = db.parse(file_id);
+    format_parse(&parse, range)
+}
+
+/// Formats the given text, returning `None` if it cannot be parsed.
+pub fn format_text(text: &str) -> Option<String> {
+    let parse = ast::SourceFile::parse_text(text);
+    let edit = format_parse(&parse, None)?;
+    let mut text = text.to_string();
+    edit.apply(&mut text);
+    Some(text)
+}
+
+fn format_parse(parse: &Parse<ast::SourceFile>, range: Option<TextRange>) -> Option<TextEdit> {
+    if !parse.errors().is_empty() {
+        return None;
+    }
+    let root = parse.syntax_node();
+    let text = root.text().to_string();
+    let tokens: Vec<SyntaxToken> = root
+        .descendants_with_tokens()
+        .filter_map(|it| it.into_token())
+        .filter(|it| it.kind() != WHITESPACE)
+        .collect();
+
+    // Break the containers of the lines that do not fit one at a time,
+    // from the outermost, until every line fits or nothing is left to break
+    let mut broken = FxHashSet::default();
+    let edits = loop {
+        let (edits, overflows) = Formatter::new(&text, &broken).run(&tokens);
+        let open = overflows
+            .into_iter()
+            .find_map(|line| container_to_break(&text, &broken, &tokens[line]));
+        match open {
+            Some(open) => {
+                broken.insert(open.text_range().start());
+            }
+            None => break edits,
+        }
+    };
+
+    let mut builder = TextEdit::builder();
+    for (edit_range, replacement) in edits {
+        if range.map_or(true, |range| overlaps(edit_range, range)) {
+            builder.replace(edit_range, replacement);
+        }
+    }
+    Some(builder.finish())
+}
+
+/// Whether the edit falls within the range, rather than only touching
+/// one of its ends. Insertions are kept when strictly inside the range.
+fn overlaps(edit: TextRange, range: TextRange) -> bool {
+    edit.start() < range.end() && range.start() < edit.end()
+}
+
+struct Formatter<'a> {
+    text: &'a str,
+    /// Opening brackets of the containers broken to fit the line width.
+    broken: &'a FxHashSet<TextSize>,
+    /// Indentation of the line each token ends up on, keyed by the
+    /// offset of the token in the original text.
+    indents: FxHashMap<TextSize, usize>,
+    edits: Vec<(TextRange, String)>,
+}
+
+impl<'a> Formatter<'a> {
+    fn new(text: &'a str, broken: &'a FxHashSet<TextSize>) -> Formatter<'a> {
+        Formatter {
+            text,
+            broken,
+            indents: FxHashMap::default(),
+            edits: Vec::new(),
+        }
+    }
+
+    /// Computes the edits, along with the lines wider than `MAX_WIDTH`, as
+    /// the ranges of tokens from the start of each line to the first token
+    /// that does not fit.
+    fn run(mut self, tokens: &[SyntaxToken]) -> (Vec<(TextRange, String)>, Vec<Range<usize>>) {
+        let mut overflows = Vec::new();
+        let mut line_start = 0;
+        let mut line_indent = 0;
+        let mut column = 0;
+        let mut prev: Option<&SyntaxToken> = None;
+        for (idx, token) in tokens.iter().enumerate() {
+            let start = prev.map_or(0.into(), |it| it.text_range().end());
+            let gap = TextRange::new(start, token.text_range().start());
+            let gap_text = &self.text[gap];
+
+            let replacement = match prev {
+                Some(prev)
+                    if !self
+                        .container_layout(prev, token)
+                        .unwrap_or_else(|| gap_text.contains('\n')) =>
+                {
+                    self.spacing(prev, token, gap_text)
+                }
+                _ => {
+                    let newlines = match prev {
+                        Some(_) => gap_text.matches('\n').count().clamp(1, 2),
+                        None => 0,
+                    };
+                    let indent = if in_macro_body(token) {
+                        gap_text.rsplit('\n').next().unwrap_or_default().to_string()
+                    } else {
+                        " ".repeat(self.indent(token))
+                    };
+                    line_start = idx;
+                    line_indent = indent.len();
+                    column = 0;
+                    format!("{}{}", "\n".repeat(newlines), indent)
+                }
+            };
+            column += replacement.chars().count();
+            self.edit(gap, replacement);
+            self.indents.insert(token.text_range().start(), line_indent);
+
+            let text = if token.kind() == COMMENT {
+                let trimmed = token.text().trim_end();
+                if trimmed.len() != token.text().len() {
+                    let end = token.text_range().end();
+                    let start = token.text_range().start() + TextSize::of(trimmed);
+                    self.edit(TextRange::new(start, end), String::new());
+                }
+                trimmed
+            } else {
+                token.text()
+            };
+            match text.rsplit_once('\n') {
+                // The next tokens are on the last line of a multi-line token
+                Some((_, last)) => {
+                    line_start = idx;
+                    line_indent = last.chars().take_while(|c| *c == ' ' || *c == '\t').count();
+                    column = last.chars().count();
+                }
+                None => column += text.chars().count(),
+            }
+
+            if column > MAX_WIDTH
+                && token.kind() != COMMENT
+                && overflows
+                    .last()
+                    .map_or(true, |line: &Range<usize>| line.start != line_start)
+            {
+                overflows.push(line_start..idx + 1);
+            }
+            prev = Some(token);
+        }
+
+        if let Some(last) = prev {
+            let gap = TextRange::new(last.text_range().end(), TextSize::of(self.text));
+            self.edit(gap, "\n".to_string());
+        }
+        (self.edits, overflows)
+    }
+
+    fn edit(&mut self, range: TextRange, replacement: String) {
+        if self.text[range] != replacement {
+            self.edits.push((range, replacement));
+        }
+    }
+
+    /// Indentation of the line the token is on, in the formatted output.
+    fn line_indent(&self, token: &SyntaxToken) -> usize {
+        self.indents
+            .get(&token.text_range().start())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Whether the two tokens are on separate lines, when they delimit
+    /// the elements of a container. As in erlfmt, a container whose first
+    /// element is on a new line, or that does not fit the line width, has
+    /// each element on its own line and its closing bracket on a line of
+    /// its own. Other containers are kept on a single line, unless
+    /// comments are in the way.
+    fn container_layout(&self, prev: &SyntaxToken, next: &SyntaxToken) -> Option<bool> {
+        if next.kind() == COMMENT || in_macro_body(next) {
+            return None;
+        }
+        let node = if is_opening(prev.kind()) || prev.kind() == ANON_COMMA {
+            prev.parent()?
+        } else if is_closing(next.kind()) || next.kind() == ANON_PIPE_PIPE {
+            next.parent()?
+        } else {
+            return None;
+        };
+        let (open, close) = container(&node)?;
+        if (is_opening(prev.kind()) && *prev != open) || (is_closing(next.kind()) && *next != close)
+        {
+            return None;
+        }
+        if is_broken(self.text, self.broken, &open, &close) {
+            Some(true)
+        } else if node
+            .descendants_with_tokens()
+            .any(|it| it.kind() == COMMENT)
+        {
+            None
+        } else {
+            Some(false)
+        }
+    }
+
+    /// Indentation for a token that is the first one on its line.
+    fn indent(&self, token: &SyntaxToken) -> usize {
+        if token.kind() == COMMENT {
+            // Comments are indented like the code they precede, as
+            // if it was not closing the enclosing construct.
+            return match next_code_token(token) {
+                Some(next) => self.indent_for(&next, false),
+                None => 0,
+            };
+        }
+        self.indent_for(token, true)
+    }
+
+    fn indent_for(&self, token: &SyntaxToken, dedent: bool) -> usize {
+        // Find the innermost enclosing node that starts on an earlier
+        // line, that is before the token, as it starts a line
+        let mut child: NodeOrToken<SyntaxNode, SyntaxToken> = NodeOrToken::Token(token.clone());
+        let mut parent = token.parent();
+        let enclosing = loop {
+            let node = match parent {
+                Some(node) => node,
+                None => return 0,
+            };
+            if node.kind() == SOURCE_FILE {
+                return 0;
+            }
+            if !is_transparent(node.kind()) {
+                if let Some(start) = first_code_token(&node) {
+                    if start.text_range().start() < token.text_range().start() {
+                        break node;
+                    }
+                }
+            }
+            parent = node.parent();
+            child = NodeOrToken::Node(node);
+        };
+
+        // Chains of binary operators are indented as a single block
+        let mut anchor = enclosing.clone();
+        if anchor.kind() == BINARY_OP_EXPR {
+            while let Some(parent) = anchor.parent().filter(|it| it.kind() == BINARY_OP_EXPR) {
+                anchor = parent;
+            }
+        }
+        let base = match first_code_token(&anchor) {
+            Some(start) => self.line_indent(&start),
+            None => 0,
+        };
+
+        let starts_child = match &child {
+            NodeOrToken::Token(_) => true,
+            NodeOrToken::Node(node) => first_code_token(node).as_ref() == Some(token),
+        };
+        if starts_child {
+            if enclosing.kind() == FUN_DECL {
+                // Function clauses line up with each other
+                return base;
+            }
+            if dedent && is_dedented(enclosing.kind(), token.kind()) {
+                return base;
+            }
+            if dedent && token.kind() == ANON_PIPE_PIPE {
+                // The generators of a comprehension line up with its
+                // expression, as in erlfmt
+                return base + 1;
+            }
+        }
+        base + INDENT
+    }
+
+    /// Whitespace between two tokens on the same line.
+    fn spacing(&self, prev: &SyntaxToken, next: &SyntaxToken, gap: &str) -> String {
+        if in_macro_body(next) {
+            return gap.to_string();
+        }
+        let space = if next.kind() == COMMENT {
+            true
+        } else if matches!(next.kind(), ANON_COMMA | ANON_SEMI | ANON_DOT) {
+            false
+        } else if matches!(prev.kind(), ANON_COMMA | ANON_SEMI) {
+            true
+        } else if is_opening(prev.kind()) || is_closing(next.kind()) {
+            false
+        } else if is_spaced_operator(prev) || is_spaced_operator(next) {
+            true
+        } else {
+            !gap.is_empty()
+        };
+        if space || needs_separator(prev.text(), next.text()) {
+            " ".to_string()
+        } else {
+            String::new()
+        }
+    }
+}
+
+fn first_code_token(node: &SyntaxNode) -> Option<SyntaxToken> {
+    node.descendants_with_tokens()
+        .filter_map(|it| it.into_token())
+        .find(|it| !it.kind().is_trivia())
+}
+
+fn next_code_token(token: &SyntaxToken) -> Option<SyntaxToken> {
+    std::iter::successors(token.next_token(), |it| it.next_token())
+        .find(|it| !it.kind().is_trivia())
+}
+
+/// The brackets of a node made of a sequence of elements between
+/// brackets, such as a list, a tuple, a map or the arguments of a call.
+fn container(node: &SyntaxNode) -> Option<(SyntaxToken, SyntaxToken)> {
+    let open = node
+        .children_with_tokens()
+        .filter_map(|it| it.into_token())
+        .find(|it| is_opening(it.kind()))?;
+    let close = node
+        .children_with_tokens()
+        .filter(|it| !it.kind().is_trivia())
+        .last()?
+        .into_token()?;
+    let matching = matches!(
+        (open.kind(), close.kind()),
+        (ANON_LPAREN, ANON_RPAREN)
+            | (ANON_LBRACK, ANON_RBRACK)
+            | (ANON_LBRACE, ANON_RRACE)
+            | (ANON_LT_LT, ANON_GT_GT)
+    );
+    matching.then(|| (open, close))
+}
+
+fn has_elements(open: &SyntaxToken, close: &SyntaxToken) -> bool {
+    next_code_token(open).as_ref() != Some(close)
+}
+
+/// Whether the container has each element on its own line: when it was
+/// broken to fit the line width, or its first element is on a new line.
+fn is_broken(
+    text: &str,
+    broken: &FxHashSet<TextSize>,
+    open: &SyntaxToken,
+    close: &SyntaxToken,
+) -> bool {
+    if broken.contains(&open.text_range().start()) {
+        return true;
+    }
+    if !has_elements(open, close) {
+        return false;
+    }
+    std::iter::successors(open.next_token(), |it| it.next_token())
+        .find(|it| it.kind() != WHITESPACE)
+        .map_or(false, |next| {
+            text[TextRange::new(open.text_range().end(), next.text_range().start())].contains('\n')
+        })
+}
+
+/// The outermost container opened on the line, and still on a single
+/// line, that encloses the last token of the line, which does not fit.
+fn container_to_break(
+    text: &str,
+    broken: &FxHashSet<TextSize>,
+    line: &[SyntaxToken],
+) -> Option<SyntaxToken> {
+    let overflowing = line.last()?;
+    line.iter()
+        .find(|token| {
+            if !is_opening(token.kind()) || in_macro_body(token) {
+                return false;
+            }
+            match token.parent().as_ref().and_then(container) {
+                Some((open, close)) => {
+                    open == **token
+                        && close.text_range().start() >= overflowing.text_range().start()
+                        && has_elements(&open, &close)
+                        && !is_broken(text, broken, &open, &close)
+                }
+                None => false,
+            }
+        })
+        .cloned()
+}
+
+/// Whether the token is part of a `-define`, other than its leading `-`.
+fn in_macro_body(token: &SyntaxToken) -> bool {
+    token
+        .parent_ancestors()
+        .find(|it| it.kind() == PP_DEFINE)
+        .map_or(false, |define| {
+            first_code_token(&define).as_ref() != Some(token)
+        })
+}
+
+/// Nodes that only group their children, and do not introduce a new
+/// level of indentation.
+fn is_transparent(kind: SyntaxKind) -> bool {
+    matches!(kind, CLAUSE_BODY | LC_EXPRS | MULTI_STRING)
+}
+
+fn is_opening(kind: SyntaxKind) -> bool {
+    matches!(kind, ANON_LPAREN | ANON_LBRACK | ANON_LBRACE | ANON_LT_LT)
+}
+
+fn is_closing(kind: SyntaxKind) -> bool {
+    matches!(kind, ANON_RPAREN | ANON_RBRACK | ANON_RRACE | ANON_GT_GT)
+}
+
+/// Tokens that line up with the start of the construct they belong to.
+fn is_dedented(parent: SyntaxKind, kind: SyntaxKind) -> bool {
+    match kind {
+        ANON_END => true,
+        ANON_OF => matches!(parent, CASE_EXPR | TRY_EXPR),
+        ANON_CATCH => parent == TRY_EXPR,
+        ANON_AFTER => matches!(parent, RECEIVE_EXPR | TRY_EXPR),
+        ANON_ELSE => parent == MAYBE_EXPR,
+        _ => is_closing(kind),
+    }
+}
+
+fn is_spaced_operator(token: &SyntaxToken) -> bool {
+    match token.kind() {
+        ANON_EQ | ANON_DASH_GT | ANON_LT_DASH | ANON_LT_EQ | ANON_PIPE_PIPE | ANON_COLON_COLON
+        | ANON_EQ_GT | ANON_COLON_EQ | ANON_QMARK_EQ | ANON_PIPE => true,
+        ANON_PLUS | ANON_DASH | ANON_STAR | ANON_SLASH | ANON_DIV | ANON_REM | ANON_BAND
+        | ANON_BOR | ANON_BXOR | ANON_BSL | ANON_BSR | ANON_AND | ANON_OR | ANON_XOR
+        | ANON_ANDALSO | ANON_ORELSE | ANON_PLUS_PLUS | ANON_DASH_DASH | ANON_EQ_EQ
+        | ANON_SLASH_EQ | ANON_EQ_COLON_EQ | ANON_EQ_SLASH_EQ | ANON_LT | ANON_GT | ANON_EQ_LT
+        | ANON_GT_EQ | ANON_BANG => token
+            .parent()
+            .map_or(false, |it| it.kind() == BINARY_OP_EXPR),
+        _ => false,
+    }
+}
+
+/// Whether removing the whitespace between the two tokens would make
+/// them lex differently.
+fn needs_separator(prev: &str, next: &str) -> bool {
+    const OPERATOR_CHARS: &str = "<>=/:+-*|!#.?";
+    match (prev.chars().last(), next.chars().next()) {
+        (Some(a), Some(b)) => {
+            let word = |c: char| c.is_alphanumeric() || c == '_' || c == '@' || c == '\'';
+            (word(a) && word(b)) || (OPERATOR_CHARS.contains(a) && OPERATOR_CHARS.contains(b))
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use elp_ide_db::elp_base_db::fixture::WithFixture;
+    use elp_ide_db::RootDatabase;
+    use elp_syntax::TextRange;
+    use expect_test::expect;
+    use expect_test::Expect;
+
+    use super::*;
+
+    fn check(before: &str, expect: Expect) {
+        let actual = format_text(before).expect("formatting failed");
+        expect.assert_eq(&actual);
+        assert_eq!(
+            format_text(&actual).as_deref(),
+            Some(actual.as_str()),
+            "formatting is not idempotent"
+        );
+    }
+
+    fn check_range(before: &str, range: TextRange, expect: Expect) {
+        let (db, file_id) = RootDatabase::with_single_file(before);
+        let edit = format(&db, file_id, Some(range)).expect("formatting failed");
+        let mut actual = before.to_string();
+        edit.apply(&mut actual);
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn already_formatted() {
+        check(
+            r#"
+-module(main).
+-export([foo/1]).
+
+foo(X) ->
+    X + 1.
+"#
+            .trim_start(),
+            expect![[r#"
+                -module(main).
+                -export([foo/1]).
+
+                foo(X) ->
+                    X + 1.
+            "#]],
+        );
+    }
+
+    #[test]
+    fn spacing() {
+        check(
+            "foo( X,Y )->X+Y   .\nbar([H|T])->#{a=>H , b:=T}.\n",
+            expect![[r#"
+                foo(X, Y) -> X + Y.
+                bar([H | T]) -> #{a => H, b := T}.
+            "#]],
+        );
+    }
+
+    #[test]
+    fn indentation() {
+        check(
+            r#"
+foo(X) ->
+  case X of
+     1 ->
+   ok;
+  _ ->
+          error
+        end.
+"#,
+            expect![[r#"
+                foo(X) ->
+                    case X of
+                        1 ->
+                            ok;
+                        _ ->
+                            error
+                    end.
+            "#]],
+        );
+    }
+
+    #[test]
+    fn multiple_clauses() {
+        check(
+            r#"
+foo(1) ->
+      one;
+  foo(_) ->
+      other.
+"#,
+            expect![[r#"
+                foo(1) ->
+                    one;
+                foo(_) ->
+                    other.
+            "#]],
+        );
+    }
+
+    #[test]
+    fn brackets() {
+        check(
+            r#"
+foo() ->
+    bar(
+  a,
+        b
+        ),
+    [
+    1,
+    2
+  ].
+"#,
+            expect![[r#"
+                foo() ->
+                    bar(
+                        a,
+                        b
+                    ),
+                    [
+                        1,
+                        2
+                    ].
+            "#]],
+        );
+    }
+
+    #[test]
+    fn containers_on_one_line() {
+        check(
+            r#"
+foo() ->
+    bar(a,
+        b),
+    {x,
+  y}.
+"#,
+            expect![[r#"
+                foo() ->
+                    bar(a, b),
+                    {x, y}.
+            "#]],
+        );
+    }
+
+    #[test]
+    fn containers_with_first_element_on_new_line() {
+        check(
+            r#"
+foo() ->
+    #{
+    a => 1, b => 2},
+    [
+        1, % one
+        2].
+"#,
+            expect![[r#"
+                foo() ->
+                    #{
+                        a => 1,
+                        b => 2
+                    },
+                    [
+                        1, % one
+                        2
+                    ].
+            "#]],
+        );
+    }
+
+    #[test]
+    fn containers_wider_than_the_line() {
+        check(
+            r#"
+foo() ->
+    some_module:some_function(first_argument, second_argument, third_argument, fourth_argument, fifth).
+"#,
+            expect![[r#"
+                foo() ->
+                    some_module:some_function(
+                        first_argument,
+                        second_argument,
+                        third_argument,
+                        fourth_argument,
+                        fifth
+                    ).
+            "#]],
+        );
+    }
+
+    #[test]
+    fn comprehensions() {
+        check(
+            r#"
+foo(L) ->
+    [
+  X || X <- L].
+"#,
+            expect![[r#"
+                foo(L) ->
+                    [
+                        X
+                     || X <- L
+                    ].
+            "#]],
+        );
+    }
+
+    #[test]
+    fn comments() {
+        check(
+            r#"
+%% Top level comment
+foo() ->
+        %% Leading comment
+  ok.   % trailing
+    %% Between functions
+bar() -> ok.
+"#,
+            expect![[r#"
+                %% Top level comment
+                foo() ->
+                    %% Leading comment
+                    ok. % trailing
+                %% Between functions
+                bar() -> ok.
+            "#]],
+        );
+    }
+
+    #[test]
+    fn blank_lines() {
+        check(
+            "\n\n-module(main).\n\n\n\nfoo() -> ok.",
+            expect![[r#"
+                -module(main).
+
+                foo() -> ok.
+            "#]],
+        );
+    }
+
+    #[test]
+    fn macros() {
+        check(
+            r#"
+-define(ADD(A,B),   A+B).
+foo()->?ADD(1,2).
+"#,
+            expect![[r#"
+                -define(ADD(A,B),   A+B).
+                foo() -> ?ADD(1, 2).
+            "#]],
+        );
+    }
+
+    #[test]
+    fn receive_and_try() {
+        check(
+            r#"
+foo() ->
+    receive
+    Msg -> Msg
+      after 100 ->
+    timeout
+ end.
+bar() ->
+    try baz() of
+   ok -> ok
+  catch
+  _:_ -> error
+      after
+  done
+    end.
+"#,
+            expect![[r#"
+                foo() ->
+                    receive
+                        Msg -> Msg
+                    after 100 ->
+                        timeout
+                    end.
+                bar() ->
+                    try baz() of
+                        ok -> ok
+                    catch
+                        _:_ -> error
+                    after
+                        done
+                    end.
+            "#]],
+        );
+    }
+
+    #[test]
+    fn syntax_errors() {
+        assert_eq!(format_text("foo( -> ok."), None);
+    }
+
+    #[test]
+    fn range() {
+        let text = "foo()->ok.\nbar()->ok.\n";
+        check_range(
+            text,
+            TextRange::new(11.into(), 21.into()),
+            expect![[r#"
+                foo()->ok.
+                bar() -> ok.
+            "#]],
+        );
+    }
+
+    #[test]
+    fn range_excludes_edits_at_its_ends() {
+        let text = "foo()->ok.\nbar()->ok.\n";
+        check_range(
+            text,
+            TextRange::new(0.into(), 5.into()),
+            expect![[r#"
+                foo()->ok.
+                bar()->ok.
+            "#]],
+        );
+    }
+}
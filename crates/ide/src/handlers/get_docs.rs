@@ -10,8 +10,12 @@
 use elp_ide_db::docs::Doc;
 use elp_ide_db::elp_base_db::FilePosition;
 use elp_ide_db::elp_base_db::FileRange;
+use elp_ide_db::eqwalizer;
 use elp_ide_db::find_best_token;
 use elp_ide_db::RootDatabase;
+use elp_syntax::SyntaxKind;
+use elp_syntax::SyntaxToken;
+use hir::InFile;
 use hir::Semantic;
 
 pub(crate) fn get_doc_at_position(
@@ -26,6 +30,24 @@ pub(crate) fn get_doc_at_position(
         file_id: token.file_id,
         range: token.value.text_range(),
     };
-    let doc = Doc::from_reference(&docs, &token);
+    let doc = Doc::from_reference(&docs, &token).or_else(|| eqwalizer_type_doc(db, &token, range));
     doc.map(|d| (d, range))
 }
+
+/// The type of the variable, if eqWAlizer already inferred it for the type
+/// hints of the file.
+fn eqwalizer_type_doc(
+    db: &RootDatabase,
+    token: &InFile<SyntaxToken>,
+    range: FileRange,
+) -> Option<Doc> {
+    if token.value.kind() != SyntaxKind::VAR {
+        return None;
+    }
+    let info = eqwalizer::type_at_range(db, range)?;
+    Some(Doc::new(format!(
+        "```erlang\n{} :: {}\n```",
+        token.value.text(),
+        info.ty
+    )))
+}
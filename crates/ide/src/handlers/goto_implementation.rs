@@ -0,0 +1,212 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use elp_ide_db::elp_base_db::FileId;
+use elp_ide_db::elp_base_db::FilePosition;
+use elp_ide_db::find_best_token;
+use elp_ide_db::RootDatabase;
+use elp_ide_db::SymbolClass;
+use elp_ide_db::SymbolDefinition;
+use hir::Module;
+use hir::Semantic;
+
+use crate::navigation_target::NavigationTarget;
+use crate::navigation_target::ToNav;
+use crate::RangeInfo;
+
+// Feature: Go to Implementation
+//
+// Navigates from a callback to the functions implementing it, and
+// from a behaviour to the modules implementing it.
+//
+// |===
+// | Editor  | Shortcut
+//
+// | VS Code | kbd:[Ctrl+F12]
+// |===
+pub(crate) fn goto_implementation(
+    db: &RootDatabase,
+    position: FilePosition,
+) -> Option<RangeInfo<Vec<NavigationTarget>>> {
+    let sema = Semantic::new(db);
+    let token = find_best_token(&sema, position)?;
+    let targets: Vec<_> = SymbolClass::classify(&sema, token.clone())?
+        .into_iter()
+        .flat_map(|def| implementations(&sema, position.file_id, def))
+        .map(|def| def.to_nav(db))
+        .collect();
+    if targets.is_empty() {
+        return None;
+    }
+    Some(RangeInfo::new(token.value.text_range(), targets))
+}
+
+fn implementations(
+    sema: &Semantic,
+    file_id: FileId,
+    def: SymbolDefinition,
+) -> Vec<SymbolDefinition> {
+    match def {
+        SymbolDefinition::Callback(callback) => {
+            let behaviour = Module {
+                file: callback.file,
+            };
+            sema.behaviour_implementors(file_id, &behaviour)
+                .into_iter()
+                .filter_map(|module| {
+                    sema.def_map(module.file.file_id)
+                        .get_function(&callback.callback.name)
+                        .cloned()
+                })
+                .map(SymbolDefinition::Function)
+                .collect()
+        }
+        // Only modules defining callbacks are behaviours
+        SymbolDefinition::Module(module)
+            if !sema.def_map(module.file.file_id).get_callbacks().is_empty() =>
+        {
+            sema.behaviour_implementors(file_id, &module)
+                .into_iter()
+                .map(SymbolDefinition::Module)
+                .collect()
+        }
+        _ => Vec::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture;
+    use crate::tests::check_navs;
+
+    #[track_caller]
+    fn check(fixture: &str) {
+        let (analysis, position, expected) = fixture::annotations(fixture);
+        let navs = analysis
+            .goto_implementation(position)
+            .unwrap()
+            .expect("no implementation found")
+            .info;
+        check_navs(navs, expected);
+    }
+
+    #[test]
+    fn callback_implementations() {
+        check(
+            r#"
+//- /src/my_behaviour.erl
+-module(my_behaviour).
+-callback fo~o(integer()) -> ok.
+-callback foo() -> ok.
+
+//- /src/impl_a.erl
+-module(impl_a).
+-behaviour(my_behaviour).
+-export([foo/1]).
+  foo(_) -> ok.
+%%^^^
+
+//- /src/impl_b.erl
+-module(impl_b).
+-behaviour(my_behaviour).
+foo() -> ok.
+  foo(X) -> foo().
+%%^^^
+
+//- /src/not_impl.erl
+-module(not_impl).
+foo(_) -> ok.
+"#,
+        );
+    }
+
+    #[test]
+    fn behaviour_implementors() {
+        check(
+            r#"
+//- /src/my_behaviour.erl
+-module(my_behaviour).
+-callback foo() -> ok.
+
+//- /src/impl_a.erl
+  -module(impl_a).
+%%^^^^^^^^^^^^^^^^
+-behaviour(my_behav~iour).
+
+//- /src/impl_b.erl
+  -module(impl_b).
+%%^^^^^^^^^^^^^^^^
+-behavior(my_behaviour).
+
+//- /src/not_impl.erl
+-module(not_impl).
+"#,
+        );
+    }
+
+    #[test]
+    fn behaviour_implementors_from_definition() {
+        check(
+            r#"
+//- /src/my_behaviour.erl
+-module(my_beh~aviour).
+-callback foo() -> ok.
+
+//- /src/impl_a.erl
+  -module(impl_a).
+%%^^^^^^^^^^^^^^^^
+-behaviour(my_behaviour).
+"#,
+        );
+    }
+
+    #[track_caller]
+    fn check_none(fixture: &str) {
+        let (analysis, position) = fixture::position(fixture);
+        let navs = analysis.goto_implementation(position).unwrap();
+        assert!(navs.is_none(), "expected no implementation, got {navs:?}");
+    }
+
+    #[test]
+    fn no_implementations() {
+        check_none(
+            r#"
+-module(main).
+fo~o() -> ok.
+"#,
+        );
+    }
+
+    #[test]
+    fn callback_without_implementations() {
+        check_none(
+            r#"
+//- /src/my_behaviour.erl
+-module(my_behaviour).
+-callback fo~o() -> ok.
+"#,
+        );
+    }
+
+    #[test]
+    fn module_is_not_a_behaviour() {
+        check_none(
+            r#"
+//- /src/main.erl
+-module(main).
+-behaviour(other).
+bar() -> oth~er:foo().
+//- /src/other.erl
+-module(other).
+-export([foo/0]).
+foo() -> ok.
+"#,
+        );
+    }
+}
@@ -0,0 +1,345 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use elp_ide_db::elp_base_db::FileId;
+use elp_ide_db::elp_base_db::FilePosition;
+use elp_ide_db::find_best_token;
+use elp_ide_db::RootDatabase;
+use elp_ide_db::SymbolClass;
+use elp_ide_db::SymbolDefinition;
+use elp_syntax::ast;
+use elp_syntax::AstNode;
+use hir::Body;
+use hir::CallDef;
+use hir::CallTarget;
+use hir::FormIdx;
+use hir::FunType;
+use hir::InFile;
+use hir::ListType;
+use hir::NameArity;
+use hir::RecordFieldDef;
+use hir::Semantic;
+use hir::SpecSig;
+use hir::TypeExpr;
+use hir::TypeExprId;
+use hir::Var;
+use hir::VarDef;
+
+use crate::navigation_target::NavigationTarget;
+use crate::navigation_target::ToNav;
+use crate::RangeInfo;
+
+// Feature: Go to Type Definition
+//
+// Navigates from a call, a variable or a record field to the `-type`,
+// `-opaque` or `-record` describing its value. The types of calls and of
+// function parameters come from the `-spec` of the function, the type of
+// a record field from the record declaration.
+//
+// |===
+// | Editor  | Action Name
+//
+// | VS Code | **Go to Type Definition**
+// |===
+pub(crate) fn goto_type_definition(
+    db: &RootDatabase,
+    position: FilePosition,
+) -> Option<RangeInfo<Vec<NavigationTarget>>> {
+    let sema = Semantic::new(db);
+    let token = find_best_token(&sema, position)?;
+    let mut targets = Vec::new();
+    for def in SymbolClass::classify(&sema, token.clone())?.into_iter() {
+        for nav in type_definitions(&sema, def).map(|def| def.to_nav(db)) {
+            if !targets.contains(&nav) {
+                targets.push(nav);
+            }
+        }
+    }
+    Some(RangeInfo::new(token.value.text_range(), targets))
+}
+
+fn type_definitions(
+    sema: &Semantic,
+    def: SymbolDefinition,
+) -> impl Iterator<Item = SymbolDefinition> {
+    let defs = match def {
+        SymbolDefinition::Function(fun) => {
+            spec_types(sema, fun.file.file_id, &fun.function.name, |sig| {
+                Some(sig.result)
+            })
+        }
+        SymbolDefinition::RecordField(field) => record_field_types(sema, &field),
+        SymbolDefinition::Var(var) => var_types(sema, &var),
+        SymbolDefinition::Type(_) | SymbolDefinition::Record(_) => Some(vec![def]),
+        _ => None,
+    };
+    defs.unwrap_or_default().into_iter()
+}
+
+/// The types in the `-spec` of the function, in the type expression
+/// selected from each of its signatures.
+fn spec_types(
+    sema: &Semantic,
+    file_id: FileId,
+    function: &NameArity,
+    select: impl Fn(&SpecSig) -> Option<TypeExprId>,
+) -> Option<Vec<SymbolDefinition>> {
+    let def_map = sema.def_map(file_id);
+    let spec = def_map.get_spec(function)?;
+    let spec_body = sema
+        .db
+        .spec_body(InFile::new(spec.file.file_id, spec.spec_id));
+    let mut acc = Vec::new();
+    for sig in &spec_body.sigs {
+        if let Some(type_expr) = select(sig) {
+            TypeCollector {
+                sema,
+                file_id: spec.file.file_id,
+                body: &spec_body.body,
+                guards: &sig.guards,
+            }
+            .collect(type_expr, &mut acc);
+        }
+    }
+    Some(acc)
+}
+
+fn record_field_types(sema: &Semantic, field: &RecordFieldDef) -> Option<Vec<SymbolDefinition>> {
+    let file_id = field.record.file.file_id;
+    let form_list = sema.db.file_form_list(file_id);
+    let source = ast::Form::RecordDecl(field.record.source(sema.db.upcast()));
+    let record_id = match form_list.find_form(&source)? {
+        FormIdx::Record(record_id) => record_id,
+        _ => return None,
+    };
+    let record_body = sema.db.record_body(InFile::new(file_id, record_id));
+    let type_expr = record_body.fields.get(field.field.idx as usize)?.ty?;
+    let mut acc = Vec::new();
+    TypeCollector {
+        sema,
+        file_id,
+        body: &record_body.body,
+        guards: &[],
+    }
+    .collect(type_expr, &mut acc);
+    Some(acc)
+}
+
+/// The types of a variable bound as a parameter of a function, from
+/// its `-spec`, or by matching the result of a call, e.g. `X = foo()`.
+fn var_types(sema: &Semantic, var: &VarDef) -> Option<Vec<SymbolDefinition>> {
+    let file_id = var.file.file_id;
+    let source = var.source(sema.db.upcast());
+    let syntax = source.syntax();
+    let parent = syntax.parent()?;
+    if let Some(args) = ast::ExprArgs::cast(parent.clone()) {
+        ast::FunctionClause::cast(args.syntax().parent()?)?;
+        let idx = args.args().position(|arg| arg.syntax() == syntax)?;
+        let function_id = sema.find_enclosing_function(file_id, syntax)?;
+        let function = &sema.db.file_form_list(file_id)[function_id];
+        return spec_types(sema, file_id, &function.name, |sig| {
+            sig.args.get(idx).copied()
+        });
+    }
+    let match_expr = ast::MatchExpr::cast(parent)?;
+    if match_expr.lhs()?.syntax() != syntax {
+        return None;
+    }
+    match match_expr.rhs()? {
+        ast::Expr::Call(call) => match sema.to_def(InFile::new(file_id, &call))? {
+            CallDef::Function(fun) => {
+                spec_types(sema, fun.file.file_id, &fun.function.name, |sig| {
+                    Some(sig.result)
+                })
+            }
+            CallDef::Type(_) => None,
+        },
+        _ => None,
+    }
+}
+
+/// Collects the user-defined types and records found in a type
+/// expression, looking into unions, containers and the arguments of
+/// type calls, and into the `when` constraints of type variables.
+struct TypeCollector<'a> {
+    sema: &'a Semantic<'a>,
+    file_id: FileId,
+    body: &'a Body,
+    guards: &'a [(Var, TypeExprId)],
+}
+
+impl<'a> TypeCollector<'a> {
+    fn collect(&self, type_expr: TypeExprId, acc: &mut Vec<SymbolDefinition>) {
+        match &self.body[type_expr] {
+            TypeExpr::AnnType { ty, .. } => self.collect(*ty, acc),
+            TypeExpr::Call { target, args } => {
+                if let Some(def) = self.resolve_type(target, args.len() as u32) {
+                    acc.push(def);
+                }
+                args.iter().for_each(|arg| self.collect(*arg, acc));
+            }
+            TypeExpr::Fun(FunType::AnyArgs { result }) => self.collect(*result, acc),
+            TypeExpr::Fun(FunType::Full { params, result }) => {
+                params.iter().for_each(|param| self.collect(*param, acc));
+                self.collect(*result, acc);
+            }
+            TypeExpr::List(ListType::Regular(ty) | ListType::NonEmpty(ty)) => {
+                self.collect(*ty, acc)
+            }
+            TypeExpr::Map { fields } => fields.iter().for_each(|(key, _, value)| {
+                self.collect(*key, acc);
+                self.collect(*value, acc);
+            }),
+            TypeExpr::Union { types } => types.iter().for_each(|ty| self.collect(*ty, acc)),
+            TypeExpr::Tuple { args } => args.iter().for_each(|arg| self.collect(*arg, acc)),
+            TypeExpr::Record { name, .. } => {
+                let name = self.sema.db.lookup_atom(*name);
+                if let Some(record) = self.sema.def_map(self.file_id).get_record(&name) {
+                    acc.push(SymbolDefinition::Record(record.clone()));
+                }
+            }
+            TypeExpr::Var(var) => self
+                .guards
+                .iter()
+                .filter(|(guard, _)| guard == var)
+                .for_each(|(_, ty)| self.collect(*ty, acc)),
+            TypeExpr::MacroCall { expansion, .. } => self.collect(*expansion, acc),
+            TypeExpr::Fun(FunType::Any)
+            | TypeExpr::List(ListType::Empty)
+            | TypeExpr::BinaryOp { .. }
+            | TypeExpr::Literal(_)
+            | TypeExpr::Missing
+            | TypeExpr::Range { .. }
+            | TypeExpr::UnaryOp { .. } => {}
+        }
+    }
+
+    fn resolve_type(
+        &self,
+        target: &CallTarget<TypeExprId>,
+        arity: u32,
+    ) -> Option<SymbolDefinition> {
+        let (file_id, name) = match target {
+            CallTarget::Local { name } => (self.file_id, *name),
+            CallTarget::Remote { module, name } => {
+                let module = self.sema.db.lookup_atom(self.body[*module].as_atom()?);
+                let module = self
+                    .sema
+                    .resolve_module_name(self.file_id, module.as_str())?;
+                (module.file.file_id, *name)
+            }
+        };
+        let name = self.sema.db.lookup_atom(self.body[name].as_atom()?);
+        self.sema
+            .def_map(file_id)
+            .get_type(&NameArity::new(name, arity))
+            .cloned()
+            .map(SymbolDefinition::Type)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture;
+    use crate::tests::check_navs;
+
+    #[track_caller]
+    fn check(fixture: &str) {
+        let (analysis, position, expected) = fixture::annotations(fixture);
+        let navs = analysis
+            .goto_type_definition(position)
+            .unwrap()
+            .expect("no type definition found")
+            .info;
+        check_navs(navs, expected);
+    }
+
+    #[test]
+    fn call_result() {
+        check(
+            r#"
+//- /src/main.erl
+-module(main).
+-type result() :: {ok, value()} | error.
+%%    ^^^^^^^^
+-type value() :: integer().
+%%    ^^^^^^^
+-spec foo() -> result().
+foo() -> error.
+bar() -> f~oo().
+"#,
+        );
+    }
+
+    #[test]
+    fn remote_call_result() {
+        check(
+            r#"
+//- /src/main.erl
+-module(main).
+bar() -> another:f~oo().
+//- /src/another.erl
+-module(another).
+-export([foo/0]).
+-opaque handle() :: reference().
+%%      ^^^^^^^^
+-spec foo() -> handle().
+foo() -> make_ref().
+"#,
+        );
+    }
+
+    #[test]
+    fn parameter() {
+        check(
+            r#"
+//- /src/main.erl
+-module(main).
+-record(state, {count}).
+%%      ^^^^^
+-spec foo(integer(), S) -> ok when S :: #state{}.
+foo(_N, St~ate) -> State#state.count.
+"#,
+        );
+    }
+
+    #[test]
+    fn variable_bound_to_call() {
+        check(
+            r#"
+//- /src/main.erl
+-module(main).
+-type name() :: binary().
+%%    ^^^^^^
+-spec name() -> [name()].
+name() -> [].
+bar() ->
+    Names = name(),
+    Na~mes.
+"#,
+        );
+    }
+
+    #[test]
+    fn record_field() {
+        check(
+            r#"
+//- /src/main.erl
+-module(main).
+-record(state, {handle :: another:handle() | undefined}).
+bar(S) -> S#state.ha~ndle.
+//- /src/another.erl
+-module(another).
+-export_type([handle/0]).
+-type handle() :: reference().
+%%    ^^^^^^^^
+"#,
+        );
+    }
+}
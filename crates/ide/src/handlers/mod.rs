@@ -9,4 +9,6 @@
 
 pub mod get_docs;
 pub mod goto_definition;
+pub mod goto_implementation;
+pub mod goto_type_definition;
 pub mod references;
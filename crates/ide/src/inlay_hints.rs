@@ -18,15 +18,18 @@ use itertools::Itertools;
 use smallvec::smallvec;
 use smallvec::SmallVec;
 mod param_name;
+mod type_hints;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct InlayHintsConfig {
     pub parameter_hints: bool,
+    pub type_hints: bool,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum InlayKind {
     Parameter,
+    Type,
 }
 
 #[derive(Debug)]
@@ -190,6 +193,7 @@ impl fmt::Debug for InlayHintLabelPart {
 // Available hints are:
 //
 // * names of function arguments
+// * types of variables bound by a match, as inferred by eqWAlizer
 pub(crate) fn inlay_hints(
     db: &RootDatabase,
     file_id: FileId,
@@ -202,6 +206,7 @@ pub(crate) fn inlay_hints(
     let mut acc = Vec::new();
 
     param_name::hints(&mut acc, &sema, config, file_id, range_limit);
+    type_hints::hints(&mut acc, db, &sema, config, file_id, range_limit);
 
     acc
 }
@@ -216,6 +221,7 @@ mod tests {
 
     pub(super) const DISABLED_CONFIG: InlayHintsConfig = InlayHintsConfig {
         parameter_hints: false,
+        type_hints: false,
     };
 
     #[track_caller]
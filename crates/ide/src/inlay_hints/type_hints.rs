@@ -0,0 +1,188 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use elp_ide_db::elp_base_db::FileId;
+use elp_ide_db::EqwalizerDatabase;
+use elp_ide_db::EqwalizerTypeInfo;
+use elp_ide_db::RootDatabase;
+use elp_syntax::TextRange;
+use hir::Expr;
+use hir::InFile;
+use hir::On;
+use hir::Pat;
+use hir::Semantic;
+use hir::Strategy;
+
+use crate::InlayHint;
+use crate::InlayHintLabel;
+use crate::InlayHintsConfig;
+use crate::InlayKind;
+
+pub(super) fn hints(
+    res: &mut Vec<InlayHint>,
+    db: &RootDatabase,
+    sema: &Semantic,
+    config: &InlayHintsConfig,
+    file_id: FileId,
+    range_limit: Option<TextRange>,
+) -> Option<()> {
+    if !config.type_hints {
+        return None;
+    }
+    let project_id = sema
+        .db
+        .app_data(sema.db.file_source_root(file_id))?
+        .project_id;
+    let type_info = db.eqwalizer_type_info(project_id, file_id)?;
+    hints_from_type_info(res, sema, file_id, &type_info, range_limit);
+    Some(())
+}
+
+/// Annotates the variables bound by a match, e.g. `X = foo()`, with the
+/// type eqWAlizer inferred for them.
+fn hints_from_type_info(
+    res: &mut Vec<InlayHint>,
+    sema: &Semantic,
+    file_id: FileId,
+    type_info: &[EqwalizerTypeInfo],
+    range_limit: Option<TextRange>,
+) {
+    let def_map = sema.def_map(file_id);
+    for (_name, def) in def_map.get_functions() {
+        if def.file.file_id != file_id {
+            continue;
+        }
+        let function_id = InFile::new(file_id, def.function_id);
+        let mut function_body = sema.to_function_body(function_id);
+        let mut macro_depth = 0;
+        let bindings = function_body.fold_function_with_macros(
+            Strategy::Both,
+            Vec::new(),
+            &mut |mut acc, _clause_id, ctx| {
+                match ctx.expr {
+                    // Do not produce hints if inside a macro
+                    Expr::Match { lhs, .. } if ctx.on == On::Entry && macro_depth == 0 => {
+                        if let Pat::Var(_) = function_body[lhs] {
+                            acc.push(lhs);
+                        }
+                    }
+                    Expr::MacroCall { .. } => match ctx.on {
+                        On::Entry => macro_depth += 1,
+                        On::Exit => macro_depth -= 1,
+                    },
+                    _ => {}
+                }
+                acc
+            },
+            &mut |acc, _, _| acc,
+        );
+        for pat_id in bindings {
+            let range = match function_body.range_for_pat(sema.db, pat_id) {
+                Some(range) => range,
+                None => continue,
+            };
+            if range_limit.map_or(false, |limit| !limit.contains_range(range)) {
+                continue;
+            }
+            if let Some(info) = type_info.iter().find(|info| info.range == range) {
+                res.push(InlayHint {
+                    range,
+                    kind: InlayKind::Type,
+                    label: InlayHintLabel::simple(info.ty.as_str(), None, None),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use elp_ide_db::elp_base_db::fixture::extract_annotations;
+    use elp_ide_db::elp_base_db::fixture::WithFixture;
+    use elp_ide_db::elp_base_db::SourceDatabaseExt;
+    use elp_syntax::AstNode;
+    use elp_syntax::SyntaxKind;
+
+    use super::*;
+
+    /// Every variable in the fixture is given the type `term()`, except
+    /// those annotated with a type, and only the annotated ones bound by
+    /// a match are expected to be hinted.
+    #[track_caller]
+    fn check(fixture: &str) {
+        let (db, file_id) = RootDatabase::with_single_file(fixture);
+        let sema = Semantic::new(&db);
+        let mut expected = extract_annotations(&db.file_text(file_id));
+        let mut type_info: Vec<_> = expected
+            .iter()
+            .map(|(range, ty)| EqwalizerTypeInfo {
+                range: *range,
+                ty: ty.clone(),
+            })
+            .collect();
+        let vars = sema
+            .parse(file_id)
+            .value
+            .syntax()
+            .descendants_with_tokens()
+            .filter_map(|element| element.into_token())
+            .filter(|token| token.kind() == SyntaxKind::VAR)
+            .map(|token| EqwalizerTypeInfo {
+                range: token.text_range(),
+                ty: "term()".to_string(),
+            })
+            .collect::<Vec<_>>();
+        type_info.extend(vars);
+
+        let mut hints = Vec::new();
+        hints_from_type_info(&mut hints, &sema, file_id, &type_info, None);
+        let mut actual = hints
+            .into_iter()
+            .map(|hint| (hint.range, hint.label.to_string()))
+            .collect::<Vec<_>>();
+        actual.sort_by_key(|(range, _)| range.start());
+        expected.sort_by_key(|(range, _)| range.start());
+
+        assert_eq!(
+            expected, actual,
+            "\nExpected:\n{expected:#?}\n\nActual:\n{actual:#?}"
+        );
+    }
+
+    #[test]
+    fn type_hints_match_bindings() {
+        check(
+            r#"
+-module(main).
+-export([main/1]).
+main(Arg) ->
+    X = length(Arg),
+ %% ^ integer()
+    {ok, Y} = {ok, X},
+    Z = Y,
+ %% ^ number()
+    Z.
+"#,
+        );
+    }
+
+    #[test]
+    fn type_hints_skip_macros() {
+        check(
+            r#"
+-module(main).
+-export([main/0]).
+-define(BIND(V), V = 1).
+main() ->
+    ?BIND(X),
+    X.
+"#,
+        );
+    }
+}
@@ -39,6 +39,7 @@ use elp_ide_db::erlang_service::ParseResult;
 use elp_ide_db::label::Label;
 use elp_ide_db::rename::RenameError;
 use elp_ide_db::source_change::SourceChange;
+use elp_ide_db::symbol_index;
 use elp_ide_db::Eqwalizer;
 use elp_ide_db::EqwalizerDatabase;
 use elp_ide_db::EqwalizerDiagnostics;
@@ -56,23 +57,27 @@ use elp_syntax::AstNode;
 use expand_macro::ExpandedMacro;
 use handlers::get_docs;
 use handlers::goto_definition;
+use handlers::goto_implementation;
+use handlers::goto_type_definition;
 use handlers::references;
 use hir::db::MinDefDatabase;
 use hir::DefMap;
-use hir::File;
-use hir::Module;
 use hir::Semantic;
 use navigation_target::ToNav;
+use text_edit::TextEdit;
 
 mod annotations;
 mod call_hierarchy;
 mod codemod_helpers;
 mod common_test;
+mod dependent_files;
 mod doc_links;
+mod document_links;
 mod document_symbols;
 mod expand_macro;
 mod extend_selection;
 mod folding_ranges;
+mod formatting;
 mod handlers;
 mod inlay_hints;
 mod navigation_target;
@@ -80,6 +85,7 @@ mod rename;
 mod runnables;
 mod signature_help;
 mod syntax_highlighting;
+mod type_hierarchy;
 
 #[cfg(test)]
 mod fixture;
@@ -92,8 +98,11 @@ mod highlight_related;
 // @fb-only: mod meta_only;
 
 pub use annotations::Annotation;
+pub use annotations::AnnotationConfig;
 pub use annotations::AnnotationKind;
 pub use common_test::GroupName;
+pub use document_links::DocumentLink;
+pub use document_links::DocumentLinkTarget;
 pub use document_symbols::DocumentSymbol;
 pub use elp_ide_assists;
 pub use elp_ide_completion;
@@ -103,6 +112,7 @@ pub use elp_syntax::TextRange;
 pub use elp_syntax::TextSize;
 pub use folding_ranges::Fold;
 pub use folding_ranges::FoldKind;
+pub use formatting::format_text;
 pub use handlers::references::ReferenceSearchResult;
 pub use highlight_related::HighlightedRange;
 pub use inlay_hints::InlayHint;
@@ -201,6 +211,16 @@ impl Analysis {
         self.with_db(|db| diagnostics::diagnostics(db, config, file_id, include_generated))
     }
 
+    /// The diagnostics configuration for the given file, combined with
+    /// the one of its project, or `None` if the project excludes it.
+    pub fn diagnostics_config<'a>(
+        &self,
+        config: &DiagnosticsConfig<'a>,
+        file_id: FileId,
+    ) -> Cancellable<Option<DiagnosticsConfig<'a>>> {
+        self.with_db(|db| diagnostics::file_config(db, config, file_id))
+    }
+
     /// Computes the set of eqwalizer diagnostics for the given file.
     pub fn eqwalizer_diagnostics(
         &self,
@@ -281,6 +301,23 @@ impl Analysis {
         self.with_db(|db| db.module_index(project_id))
     }
 
+    /// The files of the project whose diagnostics may change along with
+    /// the given files, including those files, and the modules they
+    /// `referenced` before changing.
+    pub fn dependent_files(
+        &self,
+        project_id: ProjectId,
+        changed: &[FileId],
+        referenced: &[FileId],
+    ) -> Cancellable<Vec<FileId>> {
+        self.with_db(|db| dependent_files::dependent_files(db, project_id, changed, referenced))
+    }
+
+    /// The modules of the project the file refers to.
+    pub fn referenced_files(&self, file_id: FileId) -> Cancellable<Vec<FileId>> {
+        self.with_db(|db| dependent_files::referenced_files(db, file_id))
+    }
+
     pub fn module_file_id(
         &self,
         project_id: ProjectId,
@@ -304,6 +341,21 @@ impl Analysis {
         self.with_db(|db| document_symbols::document_symbols(db, file_id))
     }
 
+    /// Returns the links to other files and to URLs found in the file.
+    pub fn document_links(&self, file_id: FileId) -> Cancellable<Vec<DocumentLink>> {
+        self.with_db(|db| document_links::document_links(db, file_id))
+    }
+
+    /// Formats the file, or only the part of it overlapping the given
+    /// range. Returns `None` if the file has syntax errors.
+    pub fn format(
+        &self,
+        file_id: FileId,
+        range: Option<TextRange>,
+    ) -> Cancellable<Option<TextEdit>> {
+        self.with_db(|db| formatting::format(db, file_id, range))
+    }
+
     /// Returns the contents of a file
     pub fn file_text(&self, file_id: FileId) -> Cancellable<Arc<String>> {
         self.with_db(|db| db.file_text(file_id))
@@ -370,30 +422,22 @@ impl Analysis {
         self.with_db(|db| db.is_test_suite_or_test_helper(file_id))
     }
 
-    /// Search symbols. Only module names are currently supported.
+    /// Search the modules, functions, types, records, macros and callbacks
+    /// of the project, using fuzzy matching. A query can be restricted to
+    /// records with `#rec`, to macros with `?MACRO`, or to the functions,
+    /// types and callbacks of a module with `mod:fun`. Each symbol comes
+    /// with the name of the module or header defining it.
     pub fn symbol_search(
         &self,
         project_id: ProjectId,
         query: &str,
-    ) -> Cancellable<Vec<NavigationTarget>> {
-        const LIMIT: i32 = 128;
+    ) -> Cancellable<Vec<(NavigationTarget, Option<String>)>> {
         self.with_db(|db| {
-            let module_index = self.module_index(project_id).unwrap();
-            let mut total = 0;
-            module_index
-                .all_modules()
+            symbol_index::search(db, project_id, &symbol_index::Query::new(query))
                 .iter()
-                .filter_map(|name: &ModuleName| {
-                    if total <= LIMIT && name.as_str().contains(query) {
-                        let file_id = module_index.file_for_module(name)?;
-                        let module = Module {
-                            file: File { file_id },
-                        };
-                        total += 1;
-                        Some(module.to_nav(db))
-                    } else {
-                        None
-                    }
+                .map(|def| {
+                    let container_name = symbol_index::container_name(db, project_id, def);
+                    (def.to_nav(db), container_name)
                 })
                 .collect()
         })
@@ -406,6 +450,50 @@ impl Analysis {
         self.with_db(|db| goto_definition::goto_definition(db, position))
     }
 
+    /// Returns the implementations of the callback or behaviour at the
+    /// given position
+    pub fn goto_implementation(
+        &self,
+        position: FilePosition,
+    ) -> Cancellable<Option<RangeInfo<Vec<NavigationTarget>>>> {
+        self.with_db(|db| goto_implementation::goto_implementation(db, position))
+    }
+
+    /// Returns the types and records describing the value of the call,
+    /// variable or record field at the given position
+    pub fn goto_type_definition(
+        &self,
+        position: FilePosition,
+    ) -> Cancellable<Option<RangeInfo<Vec<NavigationTarget>>>> {
+        self.with_db(|db| goto_type_definition::goto_type_definition(db, position))
+    }
+
+    /// Returns the type alias, record or behaviour at the given position
+    pub fn type_hierarchy_prepare(
+        &self,
+        position: FilePosition,
+    ) -> Cancellable<Option<RangeInfo<Vec<NavigationTarget>>>> {
+        self.with_db(|db| type_hierarchy::type_hierarchy_prepare(db, position))
+    }
+
+    /// Returns the types the type hierarchy item at the given position is
+    /// defined in terms of, or the behaviours of a module
+    pub fn type_hierarchy_supertypes(
+        &self,
+        position: FilePosition,
+    ) -> Cancellable<Option<Vec<NavigationTarget>>> {
+        self.with_db(|db| type_hierarchy::supertypes(db, position))
+    }
+
+    /// Returns the types referring to the type hierarchy item at the
+    /// given position, or the implementors of a behaviour
+    pub fn type_hierarchy_subtypes(
+        &self,
+        position: FilePosition,
+    ) -> Cancellable<Option<Vec<NavigationTarget>>> {
+        self.with_db(|db| type_hierarchy::subtypes(db, position))
+    }
+
     /// Returns the docs for the symbol at the given position
     pub fn get_docs_at_position(
         &self,
@@ -503,8 +591,18 @@ impl Analysis {
         self.with_db(|db| syntax_highlighting::highlight(db, frange.file_id, Some(frange.range)))
     }
 
-    pub fn annotations(&self, file_id: FileId) -> Cancellable<Vec<Annotation>> {
-        self.with_db(|db| annotations::annotations(db, file_id))
+    pub fn annotations(
+        &self,
+        config: &AnnotationConfig,
+        file_id: FileId,
+    ) -> Cancellable<Vec<Annotation>> {
+        self.with_db(|db| annotations::annotations(db, config, file_id))
+    }
+
+    /// Computes the references or implementations of an annotation
+    /// returned by `annotations`
+    pub fn resolve_annotation(&self, annotation: Annotation) -> Cancellable<Annotation> {
+        self.with_db(|db| annotations::resolve_annotation(db, annotation))
     }
 
     pub fn runnables(&self, file_id: FileId) -> Cancellable<Vec<Runnable>> {
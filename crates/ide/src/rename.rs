@@ -9,6 +9,7 @@
 
 //! Renaming functionality.
 
+use elp_ide_db::elp_base_db::FileId;
 use elp_ide_db::elp_base_db::FilePosition;
 use elp_ide_db::rename::format_err;
 use elp_ide_db::rename::rename_error;
@@ -65,9 +66,18 @@ fn find_definitions(
     syntax: &SyntaxNode,
     position: FilePosition,
 ) -> RenameResult<Vec<SymbolDefinition>> {
+    // Include paths refer to headers
+    if let Some(path) = algo::find_node_at_offset::<ast::String>(syntax, position.offset) {
+        if let Some(res) = classify_name(sema, position.file_id, path.syntax()) {
+            return res;
+        }
+    }
     let symbols =
         if let Some(name_like) = algo::find_node_at_offset::<ast::Name>(syntax, position.offset) {
             let res = match &name_like {
+                ast::Name::Var(var) if is_macro_name(var.syntax()) => {
+                    classify_name(sema, position.file_id, var.syntax())
+                }
                 ast::Name::Var(var) => {
                     let def = sema.to_def::<ast::Var>(InFile {
                         file_id: position.file_id,
@@ -123,10 +133,51 @@ fn find_definitions(
     }
 }
 
+fn classify_name(
+    sema: &Semantic,
+    file_id: FileId,
+    syntax: &SyntaxNode,
+) -> Option<RenameResult<Vec<SymbolDefinition>>> {
+    let token = syntax.first_token()?;
+    let location = InFile {
+        file_id,
+        value: token,
+    };
+    match SymbolClass::classify(sema, location)? {
+        SymbolClass::Definition(def) => Some(Ok(vec![def])),
+        SymbolClass::Reference { refs, typ: _ } => match refs {
+            ReferenceClass::Definition(def) => Some(Ok(vec![def])),
+            ReferenceClass::MultiVar(defs) => Some(Ok(defs
+                .into_iter()
+                .map(|def| SymbolDefinition::Var(def))
+                .collect::<Vec<_>>())),
+            ReferenceClass::MultiMacro(_) => None,
+        },
+    }
+}
+
+/// Macro names can be variables, in which case they must not be
+/// treated as ordinary variables.
+fn is_macro_name(syntax: &SyntaxNode) -> bool {
+    match syntax.parent() {
+        Some(parent) => {
+            let kind = parent.kind();
+            ast::MacroLhs::can_cast(kind)
+                || ast::MacroCallExpr::can_cast(kind)
+                || ast::PpUndef::can_cast(kind)
+                || ast::PpIfdef::can_cast(kind)
+                || ast::PpIfndef::can_cast(kind)
+        }
+        None => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use elp_ide_db::elp_base_db::assert_eq_text;
     use elp_ide_db::elp_base_db::test_fixture::trim_indent;
+    use elp_ide_db::elp_base_db::SourceDatabase;
+    use elp_ide_db::source_change::FileSystemEdit;
     use text_edit::TextEdit;
 
     use crate::fixture;
@@ -154,6 +205,18 @@ mod tests {
                     let expected = analysis_after.file_text(file_id).unwrap().to_string();
                     assert_eq_text!(&*expected, &*result);
                 }
+                for edit in source_change.file_system_edits {
+                    if let FileSystemEdit::MoveFile { src, dst } = edit {
+                        let db = &analysis_after.db;
+                        let source_root = db.source_root(db.file_source_root(src));
+                        let path = source_root.path_for_file(&src).unwrap();
+                        let expected = match path.name_and_extension() {
+                            Some((name, Some(ext))) => format!("{}.{}", name, ext),
+                            _ => panic!("Unexpected file name for {}", path),
+                        };
+                        assert_eq!(expected, dst.path);
+                    }
+                }
             }
             Err(err) => {
                 if fixture_after_str.starts_with("error:") {
@@ -1022,4 +1085,747 @@ mod tests {
              "#,
         );
     }
+
+    // -----------------------------------------------------------------
+
+    #[test]
+    fn rename_module() {
+        check(
+            "new_main",
+            r#"
+             //- /src/main.erl
+             -module(ma~in).
+             -export([foo/0]).
+             foo() -> ok.
+
+             //- /src/other.erl
+             -module(other).
+             -behaviour(main).
+             -import(main, [foo/0]).
+             bar() ->
+                 main:foo(),
+                 F = fun main:foo/0,
+                 F().
+             "#,
+            r#"
+             //- /src/new_main.erl
+             -module(new_main).
+             -export([foo/0]).
+             foo() -> ok.
+
+             //- /src/other.erl
+             -module(other).
+             -behaviour(new_main).
+             -import(new_main, [foo/0]).
+             bar() ->
+                 new_main:foo(),
+                 F = fun new_main:foo/0,
+                 F().
+             "#,
+        );
+    }
+
+    #[test]
+    fn rename_module_from_remote_call() {
+        check(
+            "new_main",
+            r#"
+             //- /src/main.erl
+             -module(main).
+             -export([foo/0]).
+             foo() -> ok.
+
+             //- /src/other.erl
+             -module(other).
+             bar() -> ma~in:foo().
+             "#,
+            r#"
+             //- /src/new_main.erl
+             -module(new_main).
+             -export([foo/0]).
+             foo() -> ok.
+
+             //- /src/other.erl
+             -module(other).
+             bar() -> new_main:foo().
+             "#,
+        );
+    }
+
+    #[test]
+    fn rename_module_fails_name_clash() {
+        check(
+            "other",
+            r#"
+             //- /src/main.erl
+             -module(ma~in).
+
+             //- /src/other.erl
+             -module(other).
+             "#,
+            r#"error: Module 'other' already exists"#,
+        );
+    }
+
+    #[test]
+    fn rename_module_fails_invalid_name() {
+        check(
+            "Other",
+            r#"
+             //- /src/main.erl
+             -module(ma~in).
+             "#,
+            r#"error: Invalid new module name: 'Other'"#,
+        );
+    }
+
+    // -----------------------------------------------------------------
+
+    #[test]
+    fn rename_record() {
+        check(
+            "new_rec",
+            r#"
+             -record(fo~o, {a, b}).
+             -type foo() :: #foo{}.
+             foo(#foo{a = A} = Foo) ->
+                 B = Foo#foo.b,
+                 {#foo{a = B, b = A}, Foo#foo{a = 1}, #foo.b}."#,
+            r#"
+             -record(new_rec, {a, b}).
+             -type foo() :: #new_rec{}.
+             foo(#new_rec{a = A} = Foo) ->
+                 B = Foo#new_rec.b,
+                 {#new_rec{a = B, b = A}, Foo#new_rec{a = 1}, #new_rec.b}."#,
+        );
+    }
+
+    #[test]
+    fn rename_record_in_header() {
+        check(
+            "new_rec",
+            r#"
+             //- /src/main.hrl
+             -record(foo, {a}).
+
+             //- /src/main.erl
+             -include("main.hrl").
+             bar() -> #fo~o{}.
+
+             //- /src/another.hrl
+             -include("main.hrl").
+             -type baz() :: #foo{}.
+
+             //- /src/another.erl
+             -include("another.hrl").
+             foo(X) -> X#foo.a.
+
+             //- /src/different_record.erl
+             -record(foo, {a}).
+             should_not_match() -> #foo{}.
+             "#,
+            r#"
+             //- /src/main.hrl
+             -record(new_rec, {a}).
+
+             //- /src/main.erl
+             -include("main.hrl").
+             bar() -> #new_rec{}.
+
+             //- /src/another.hrl
+             -include("main.hrl").
+             -type baz() :: #new_rec{}.
+
+             //- /src/another.erl
+             -include("another.hrl").
+             foo(X) -> X#new_rec.a.
+
+             //- /src/different_record.erl
+             -record(foo, {a}).
+             should_not_match() -> #foo{}.
+             "#,
+        );
+    }
+
+    #[test]
+    fn rename_record_fails_name_clash() {
+        check(
+            "bar",
+            r#"
+             //- /src/main.hrl
+             -record(fo~o, {a}).
+
+             //- /src/main.erl
+             -module(main).
+             -include("main.hrl").
+             -record(bar, {b}).
+             "#,
+            r#"error: Record 'bar' already in scope in module 'main'"#,
+        );
+    }
+
+    #[test]
+    fn rename_record_fails_invalid_name() {
+        check(
+            "Bar",
+            r#"-record(fo~o, {a})."#,
+            r#"error: Invalid new record name: 'Bar'"#,
+        );
+    }
+
+    #[test]
+    fn rename_record_field() {
+        check(
+            "c",
+            r#"
+             -record(foo, {a, b~}).
+             -type foo() :: #foo{b :: integer()}.
+             foo(#foo{a = _, b = _} = Foo) ->
+                 {#foo{a = 1, b = 2}, Foo#foo{b = 3}, Foo#foo.b, #foo.b}."#,
+            r#"
+             -record(foo, {a, c}).
+             -type foo() :: #foo{c :: integer()}.
+             foo(#foo{a = _, c = _} = Foo) ->
+                 {#foo{a = 1, c = 2}, Foo#foo{c = 3}, Foo#foo.c, #foo.c}."#,
+        );
+    }
+
+    #[test]
+    fn rename_record_field_in_header() {
+        check(
+            "c",
+            r#"
+             //- /src/main.hrl
+             -record(foo, {a}).
+
+             //- /src/main.erl
+             -include("main.hrl").
+             bar(X) -> X#foo.a~.
+
+             //- /src/another.erl
+             -include("main.hrl").
+             baz() -> #foo{a = 1}.
+             "#,
+            r#"
+             //- /src/main.hrl
+             -record(foo, {c}).
+
+             //- /src/main.erl
+             -include("main.hrl").
+             bar(X) -> X#foo.c.
+
+             //- /src/another.erl
+             -include("main.hrl").
+             baz() -> #foo{c = 1}.
+             "#,
+        );
+    }
+
+    #[test]
+    fn rename_record_field_fails_name_clash() {
+        check(
+            "b",
+            r#"-record(foo, {a~, b})."#,
+            r#"error: Field 'b' already defined in record 'foo'"#,
+        );
+    }
+
+    // -----------------------------------------------------------------
+
+    #[test]
+    fn rename_macro() {
+        check(
+            "BAR",
+            r#"
+             -define(FO~O, 1).
+             foo() -> ?FOO.
+             -ifdef(FOO).
+             -undef(FOO).
+             -endif."#,
+            r#"
+             -define(BAR, 1).
+             foo() -> ?BAR.
+             -ifdef(BAR).
+             -undef(BAR).
+             -endif."#,
+        );
+    }
+
+    #[test]
+    fn rename_macro_with_other_arity() {
+        check(
+            "BAR",
+            r#"
+             -define(FO~O, 1).
+             -define(FOO(X), X).
+             foo() -> ?FOO + ?FOO(2)."#,
+            r#"
+             -define(BAR, 1).
+             -define(FOO(X), X).
+             foo() -> ?BAR + ?FOO(2)."#,
+        );
+    }
+
+    #[test]
+    fn rename_macro_fails_condition_with_other_arity() {
+        check(
+            "BAR",
+            r#"
+             -define(FO~O, 1).
+             -define(FOO(X), X).
+             foo() -> ?FOO + ?FOO(2).
+             -ifdef(FOO).
+             -undef(FOO).
+             -endif."#,
+            r#"error: Macro 'FOO' is also defined with another arity, so its '-ifdef', '-ifndef' and '-undef' attributes cannot be renamed"#,
+        );
+    }
+
+    #[test]
+    fn rename_macro_with_args_from_usage() {
+        check(
+            "BAR",
+            r#"
+             -define(FOO, 1).
+             -define(FOO(X), X).
+             foo() -> ?FOO + ?F~OO(2)."#,
+            r#"
+             -define(FOO, 1).
+             -define(BAR(X), X).
+             foo() -> ?FOO + ?BAR(2)."#,
+        );
+    }
+
+    #[test]
+    fn rename_macro_with_alternatives() {
+        check(
+            "BAR",
+            r#"
+             -ifdef(TEST).
+             -define(FO~O, test).
+             -else.
+             -define(FOO, prod).
+             -endif.
+             foo() -> ?FOO."#,
+            r#"
+             -ifdef(TEST).
+             -define(BAR, test).
+             -else.
+             -define(BAR, prod).
+             -endif.
+             foo() -> ?BAR."#,
+        );
+    }
+
+    #[test]
+    fn rename_macro_in_header() {
+        check(
+            "BAR",
+            r#"
+             //- /src/main.hrl
+             -define(FOO, 1).
+
+             //- /src/main.erl
+             -include("main.hrl").
+             foo() -> ?FO~O.
+
+             //- /src/another.hrl
+             -include("main.hrl").
+             -define(BAZ, ?FOO).
+
+             //- /src/another.erl
+             -include("another.hrl").
+             -type foo() :: ?FOO.
+
+             //- /src/different_macro.erl
+             -define(FOO, 2).
+             foo() -> ?FOO.
+             "#,
+            r#"
+             //- /src/main.hrl
+             -define(BAR, 1).
+
+             //- /src/main.erl
+             -include("main.hrl").
+             foo() -> ?BAR.
+
+             //- /src/another.hrl
+             -include("main.hrl").
+             -define(BAZ, ?BAR).
+
+             //- /src/another.erl
+             -include("another.hrl").
+             -type foo() :: ?BAR.
+
+             //- /src/different_macro.erl
+             -define(FOO, 2).
+             foo() -> ?FOO.
+             "#,
+        );
+    }
+
+    #[test]
+    fn rename_macro_fails_name_clash() {
+        check(
+            "BAR",
+            r#"
+             //- /src/main.hrl
+             -define(FO~O, 1).
+
+             //- /src/main.erl
+             -module(main).
+             -include("main.hrl").
+             -define(BAR, 2).
+             "#,
+            r#"error: Macro 'BAR' already defined in module 'main'"#,
+        );
+    }
+
+    #[test]
+    fn rename_macro_fails_guarded_name_clash() {
+        check(
+            "BAR",
+            r#"
+             -module(main).
+             -define(FO~O(X), X).
+             -ifdef(TEST).
+             -define(BAR(Y), Y).
+             -endif.
+             "#,
+            r#"error: Macro 'BAR/1' already defined in module 'main'"#,
+        );
+    }
+
+    #[test]
+    fn rename_macro_fails_condition_clash() {
+        check(
+            "BAR",
+            r#"
+             -module(main).
+             -define(FO~O, 1).
+             -ifndef(BAR).
+             foo() -> ok.
+             -endif.
+             "#,
+            r#"error: Macro 'BAR' already used in a condition in module 'main'"#,
+        );
+    }
+
+    #[test]
+    fn rename_macro_fails_invalid_name() {
+        check(
+            "Bar Baz",
+            r#"-define(FO~O, 1)."#,
+            r#"error: Invalid new macro name: 'Bar Baz'"#,
+        );
+    }
+
+    // -----------------------------------------------------------------
+
+    #[test]
+    fn rename_type() {
+        check(
+            "new_type",
+            r#"
+             -type fo~o() :: foo.
+             -opaque bar() :: {foo(), foo(integer())}.
+             -record(rec, {a :: foo()}).
+             -spec baz(foo()) -> foo().
+             baz(X) -> X."#,
+            r#"
+             -type new_type() :: foo.
+             -opaque bar() :: {new_type(), foo(integer())}.
+             -record(rec, {a :: new_type()}).
+             -spec baz(new_type()) -> new_type().
+             baz(X) -> X."#,
+        );
+    }
+
+    #[test]
+    fn rename_opaque_type() {
+        check(
+            "new_type",
+            r#"
+             -opaque fo~o() :: foo.
+             -type bar() :: foo()."#,
+            r#"
+             -opaque new_type() :: foo.
+             -type bar() :: new_type()."#,
+        );
+    }
+
+    #[test]
+    fn rename_exported_type() {
+        check(
+            "new_type",
+            r#"
+             //- /src/main.erl
+             -module(main).
+             -export_type([foo/0, foo/1]).
+             -type foo() :: foo.
+             -type foo(X) :: {X}.
+             -type bar() :: fo~o().
+
+             //- /src/another.erl
+             -module(another).
+             -type foo() :: foo.
+             -spec baz(main:foo()) -> {foo(), main:foo(integer())}.
+             baz(X) -> X.
+             "#,
+            r#"
+             //- /src/main.erl
+             -module(main).
+             -export_type([new_type/0, foo/1]).
+             -type new_type() :: foo.
+             -type foo(X) :: {X}.
+             -type bar() :: new_type().
+
+             //- /src/another.erl
+             -module(another).
+             -type foo() :: foo.
+             -spec baz(main:new_type()) -> {foo(), main:foo(integer())}.
+             baz(X) -> X.
+             "#,
+        );
+    }
+
+    #[test]
+    fn rename_type_fails_name_clash() {
+        check(
+            "bar",
+            r#"
+             -module(main).
+             -type fo~o() :: foo.
+             -type bar() :: bar.
+             "#,
+            r#"error: Type 'bar/0' already in scope in module 'main'"#,
+        );
+    }
+
+    #[test]
+    fn rename_type_fails_invalid_name() {
+        check(
+            "Bar",
+            r#"-type fo~o() :: foo."#,
+            r#"error: Invalid new type name: 'Bar'"#,
+        );
+    }
+
+    #[test]
+    fn rename_callback() {
+        check(
+            "new_cb",
+            r#"
+             //- /src/my_behaviour.erl
+             -module(my_behaviour).
+             -callback fo~o(integer()) -> ok.
+             -callback foo() -> ok.
+             -optional_callbacks([foo/1]).
+
+             //- /src/impl_a.erl
+             -module(impl_a).
+             -behaviour(my_behaviour).
+             -export([foo/1]).
+             -spec foo(integer()) -> ok.
+             foo(_) -> ok.
+
+             //- /src/impl_b.erl
+             -module(impl_b).
+             -behaviour(my_behaviour).
+             -export([foo/0, foo/1]).
+             foo() -> ok.
+             foo(X) -> foo().
+
+             //- /src/user.erl
+             -module(user).
+             bar() -> impl_a:foo(1).
+
+             //- /src/not_impl.erl
+             -module(not_impl).
+             -export([foo/1]).
+             foo(_) -> ok.
+             "#,
+            r#"
+             //- /src/my_behaviour.erl
+             -module(my_behaviour).
+             -callback new_cb(integer()) -> ok.
+             -callback foo() -> ok.
+             -optional_callbacks([new_cb/1]).
+
+             //- /src/impl_a.erl
+             -module(impl_a).
+             -behaviour(my_behaviour).
+             -export([new_cb/1]).
+             -spec new_cb(integer()) -> ok.
+             new_cb(_) -> ok.
+
+             //- /src/impl_b.erl
+             -module(impl_b).
+             -behaviour(my_behaviour).
+             -export([foo/0, new_cb/1]).
+             foo() -> ok.
+             new_cb(X) -> foo().
+
+             //- /src/user.erl
+             -module(user).
+             bar() -> impl_a:new_cb(1).
+
+             //- /src/not_impl.erl
+             -module(not_impl).
+             -export([foo/1]).
+             foo(_) -> ok.
+             "#,
+        );
+    }
+
+    #[test]
+    fn rename_callback_implemented_by_behaviour() {
+        check(
+            "new_cb",
+            r#"
+             //- /src/my_behaviour.erl
+             -module(my_behaviour).
+             -behaviour(my_behaviour).
+             -export([foo/1]).
+             -callback fo~o(integer()) -> ok.
+             -optional_callbacks([foo/1]).
+             foo(_) -> my_behaviour:foo(1).
+
+             //- /src/impl_a.erl
+             -module(impl_a).
+             -behaviour(my_behaviour).
+             -export([foo/1]).
+             foo(X) -> my_behaviour:foo(X).
+             "#,
+            r#"
+             //- /src/my_behaviour.erl
+             -module(my_behaviour).
+             -behaviour(my_behaviour).
+             -export([new_cb/1]).
+             -callback new_cb(integer()) -> ok.
+             -optional_callbacks([new_cb/1]).
+             new_cb(_) -> my_behaviour:new_cb(1).
+
+             //- /src/impl_a.erl
+             -module(impl_a).
+             -behaviour(my_behaviour).
+             -export([new_cb/1]).
+             new_cb(X) -> my_behaviour:new_cb(X).
+             "#,
+        );
+    }
+
+    #[test]
+    fn rename_callback_fails_name_clash() {
+        check(
+            "bar",
+            r#"
+             -module(main).
+             -callback fo~o() -> ok.
+             -callback bar() -> ok.
+             "#,
+            r#"error: Callback 'bar/0' already defined"#,
+        );
+    }
+
+    #[test]
+    fn rename_callback_fails_implementation_clash() {
+        check(
+            "bar",
+            r#"
+             //- /src/my_behaviour.erl
+             -module(my_behaviour).
+             -callback fo~o() -> ok.
+
+             //- /src/impl_a.erl
+             -module(impl_a).
+             -behaviour(my_behaviour).
+             foo() -> ok.
+             bar() -> ok.
+             "#,
+            r#"error: Function 'bar/0' already in scope in module 'impl_a'"#,
+        );
+    }
+
+    // -----------------------------------------------------------------
+
+    #[test]
+    fn rename_header() {
+        check(
+            "new_header",
+            r#"
+             //- /foo/include/main.hrl app:foo include_path:/foo/include
+             -define(FOO, 1).
+             //- /foo/src/main.erl app:foo
+             -include("main.hrl~").
+             //- /foo/src/another.erl app:foo
+             -include("../include/main.hrl").
+             //- /bar/src/remote.erl app:bar
+             -include_lib("foo/include/main.hrl").
+             //- /bar/src/other_main.erl app:bar
+             -include("other_main.hrl").
+             "#,
+            r#"
+             //- /foo/include/new_header.hrl app:foo include_path:/foo/include
+             -define(FOO, 1).
+             //- /foo/src/main.erl app:foo
+             -include("new_header.hrl").
+             //- /foo/src/another.erl app:foo
+             -include("../include/new_header.hrl").
+             //- /bar/src/remote.erl app:bar
+             -include_lib("foo/include/new_header.hrl").
+             //- /bar/src/other_main.erl app:bar
+             -include("other_main.hrl").
+             "#,
+        );
+    }
+
+    #[test]
+    fn rename_header_with_extension() {
+        check(
+            "new_header.hrl",
+            r#"
+             //- /src/main.hrl
+             -define(FOO, 1).
+             //- /src/main.erl
+             -include("main~.hrl").
+             "#,
+            r#"
+             //- /src/new_header.hrl
+             -define(FOO, 1).
+             //- /src/main.erl
+             -include("new_header.hrl").
+             "#,
+        );
+    }
+
+    #[test]
+    fn rename_header_fails_name_clash() {
+        check(
+            "other",
+            r#"
+             //- /src/main.hrl
+             -define(FOO, 1).
+             //- /src/other.hrl
+             -define(BAR, 1).
+             //- /src/main.erl
+             -include("main~.hrl").
+             "#,
+            r#"error: Header 'other.hrl' already exists"#,
+        );
+    }
+
+    #[test]
+    fn rename_header_fails_invalid_name() {
+        check(
+            "../other",
+            r#"
+             //- /src/main.hrl
+             -define(FOO, 1).
+             //- /src/main.erl
+             -include("main~.hrl").
+             "#,
+            r#"error: Invalid new header name: '../other'"#,
+        );
+    }
 }
@@ -51,7 +51,12 @@ pub(crate) fn check_fix_with_config(
 }
 
 #[track_caller]
-fn check_nth_fix(nth: usize, fixture_before: &str, fixture_after: &str, config: DiagnosticsConfig) {
+pub(crate) fn check_nth_fix(
+    nth: usize,
+    fixture_before: &str,
+    fixture_after: &str,
+    config: DiagnosticsConfig,
+) {
     let after = trim_indent(fixture_after);
 
     let (db, file_position) = RootDatabase::with_position(fixture_before);
@@ -103,6 +108,7 @@ pub(crate) fn check_diagnostics_with_config(config: DiagnosticsConfig, elp_fixtu
                 annotation.push_str(match d.severity {
                     Severity::Error => "error",
                     Severity::Warning => "warning",
+                    Severity::Information => "info",
                     Severity::WeakWarning => "weak",
                 });
                 annotation.push_str(": ");
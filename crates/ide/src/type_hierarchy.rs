@@ -0,0 +1,409 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use elp_ide_db::elp_base_db::FileId;
+use elp_ide_db::elp_base_db::FilePosition;
+use elp_ide_db::elp_base_db::ModuleName;
+use elp_ide_db::elp_base_db::ProjectId;
+use elp_ide_db::eqwalizer;
+use elp_ide_db::eqwalizer::TypeReference;
+use elp_ide_db::find_best_token;
+use elp_ide_db::RootDatabase;
+use elp_ide_db::SymbolClass;
+use elp_ide_db::SymbolDefinition;
+use elp_syntax::algo;
+use elp_syntax::ast;
+use elp_syntax::AstNode;
+use elp_syntax::SyntaxNode;
+use elp_syntax::TextRange;
+use hir::InFile;
+use hir::Name;
+use hir::NameArity;
+use hir::Semantic;
+
+use crate::navigation_target::ToNav;
+use crate::NavigationTarget;
+use crate::RangeInfo;
+
+// Feature: Type Hierarchy
+//
+// Shows the type hierarchy of a type alias, a record or a behaviour.
+//
+// The supertypes of a type alias or a record are the types and records
+// it is defined in terms of, and its subtypes are the type aliases and
+// records referring to it. The supertypes of a module are the
+// behaviours it declares, and the subtypes of a behaviour are the
+// modules implementing it.
+pub(crate) fn type_hierarchy_prepare(
+    db: &RootDatabase,
+    position: FilePosition,
+) -> Option<RangeInfo<Vec<NavigationTarget>>> {
+    let sema = Semantic::new(db);
+    let (range, def) = definition_at(&sema, position)?;
+    Some(RangeInfo::new(range, vec![def.to_nav(db)]))
+}
+
+pub(crate) fn supertypes(
+    db: &RootDatabase,
+    position: FilePosition,
+) -> Option<Vec<NavigationTarget>> {
+    let sema = Semantic::new(db);
+    let (_, def) = definition_at(&sema, position)?;
+    let supertypes = match &def {
+        SymbolDefinition::Type(type_alias) => {
+            let name = type_alias.name();
+            referenced_types(&sema, type_alias.file.file_id, |project_id, module| {
+                eqwalizer::type_alias_references(
+                    db,
+                    project_id,
+                    module,
+                    name.name().as_str(),
+                    name.arity(),
+                )
+            })
+        }
+        SymbolDefinition::Record(record) => {
+            referenced_types(&sema, record.file.file_id, |project_id, module| {
+                eqwalizer::record_references(db, project_id, module, record.record.name.as_str())
+            })
+        }
+        SymbolDefinition::Module(module) => {
+            let file_id = module.file.file_id;
+            sema.db
+                .file_form_list(file_id)
+                .behaviour_attributes()
+                .filter_map(|(_, behaviour)| {
+                    sema.resolve_module_name(file_id, behaviour.name.as_str())
+                })
+                .map(SymbolDefinition::Module)
+                .collect()
+        }
+        _ => Vec::default(),
+    };
+    Some(to_navs(db, &def, supertypes))
+}
+
+pub(crate) fn subtypes(db: &RootDatabase, position: FilePosition) -> Option<Vec<NavigationTarget>> {
+    let sema = Semantic::new(db);
+    let (_, def) = definition_at(&sema, position)?;
+    let subtypes = match &def {
+        SymbolDefinition::Type(_) | SymbolDefinition::Record(_) => def
+            .clone()
+            .usages(&sema)
+            .all()
+            .iter()
+            .flat_map(|(file_id, names)| {
+                names
+                    .iter()
+                    .filter_map(|name| enclosing_type(&sema, file_id, name.syntax()))
+                    .collect::<Vec<_>>()
+            })
+            .collect(),
+        SymbolDefinition::Module(module) => sema
+            .behaviour_implementors(module.file.file_id, module)
+            .into_iter()
+            .map(SymbolDefinition::Module)
+            .collect(),
+        _ => Vec::default(),
+    };
+    Some(to_navs(db, &def, subtypes))
+}
+
+/// The type alias, record or module at the given position. A module
+/// can be selected either by its name or by its `-module` attribute.
+fn definition_at(sema: &Semantic, position: FilePosition) -> Option<(TextRange, SymbolDefinition)> {
+    let source_file = sema.parse(position.file_id);
+    if let Some(attr) = algo::find_node_at_offset::<ast::ModuleAttribute>(
+        source_file.value.syntax(),
+        position.offset,
+    ) {
+        let module = sema.to_def(InFile::new(position.file_id, &attr))?;
+        return Some((attr.syntax().text_range(), SymbolDefinition::Module(module)));
+    }
+    let token = find_best_token(sema, position)?;
+    let def = SymbolClass::classify(sema, token.clone())?
+        .into_iter()
+        .find(|def| {
+            matches!(
+                def,
+                SymbolDefinition::Type(_)
+                    | SymbolDefinition::Record(_)
+                    | SymbolDefinition::Module(_)
+            )
+        })?;
+    Some((token.value.text_range(), def))
+}
+
+/// Types and records referred to from a definition in the given file,
+/// from the types of its module as converted by eqWAlizer. Definitions
+/// in headers are looked up in a module including them.
+fn referenced_types(
+    sema: &Semantic,
+    file_id: FileId,
+    references: impl FnOnce(ProjectId, ModuleName) -> Vec<TypeReference>,
+) -> Vec<SymbolDefinition> {
+    let project_id = match sema.db.app_data(sema.db.file_source_root(file_id)) {
+        Some(app_data) => app_data.project_id,
+        None => return Vec::new(),
+    };
+    let module_file = match sema.module_name(file_id) {
+        Some(_) => Some(file_id),
+        None => sema
+            .db
+            .header_includers(project_id)
+            .get(&file_id)
+            .and_then(|includers| includers.iter().copied().min()),
+    };
+    let (module_file, module) = match module_file
+        .and_then(|module_file| Some((module_file, sema.module_name(module_file)?)))
+    {
+        Some(module) => module,
+        None => return Vec::new(),
+    };
+    references(project_id, module)
+        .into_iter()
+        .filter_map(|reference| match reference {
+            TypeReference::Type(id) => {
+                let module = sema.resolve_module_name(module_file, id.module.as_str())?;
+                let name = NameArity::new(Name::from_erlang_service(&id.name), id.arity);
+                let type_alias = sema.def_map(module.file.file_id).get_type(&name)?.clone();
+                Some(SymbolDefinition::Type(type_alias))
+            }
+            TypeReference::Record(record) => {
+                let module = sema.resolve_module_name(module_file, record.module.as_str())?;
+                let name = Name::from_erlang_service(&record.name);
+                let record = sema.def_map(module.file.file_id).get_record(&name)?.clone();
+                Some(SymbolDefinition::Record(record))
+            }
+        })
+        .collect()
+}
+
+/// The type alias or record whose definition contains the given node.
+fn enclosing_type(
+    sema: &Semantic,
+    file_id: FileId,
+    syntax: &SyntaxNode,
+) -> Option<SymbolDefinition> {
+    syntax.ancestors().find_map(|node| {
+        if let Some(type_alias) = ast::TypeAlias::cast(node.clone()) {
+            let name = type_alias.name()?;
+            sema.to_def(InFile::new(file_id, &name))
+                .map(SymbolDefinition::Type)
+        } else if let Some(opaque) = ast::Opaque::cast(node.clone()) {
+            let name = opaque.name()?;
+            sema.to_def(InFile::new(file_id, &name))
+                .map(SymbolDefinition::Type)
+        } else if let Some(record) = ast::RecordDecl::cast(node) {
+            sema.to_def(InFile::new(file_id, &record))
+                .map(SymbolDefinition::Record)
+        } else {
+            None
+        }
+    })
+}
+
+fn to_navs(
+    db: &RootDatabase,
+    def: &SymbolDefinition,
+    defs: Vec<SymbolDefinition>,
+) -> Vec<NavigationTarget> {
+    let this = def.to_nav(db);
+    let mut res: Vec<NavigationTarget> = Vec::new();
+    for nav in defs.iter().map(|it| it.to_nav(db)) {
+        if !res.contains(&nav) && nav != this {
+            res.push(nav);
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture;
+    use crate::tests::check_navs;
+
+    #[track_caller]
+    fn check_supertypes(fixture: &str) {
+        let (analysis, position, expected) = fixture::annotations(fixture);
+        let navs = analysis
+            .type_hierarchy_supertypes(position)
+            .unwrap()
+            .expect("no type hierarchy item found");
+        check_navs(navs, expected);
+    }
+
+    #[track_caller]
+    fn check_subtypes(fixture: &str) {
+        let (analysis, position, expected) = fixture::annotations(fixture);
+        let navs = analysis
+            .type_hierarchy_subtypes(position)
+            .unwrap()
+            .expect("no type hierarchy item found");
+        check_navs(navs, expected);
+    }
+
+    #[test]
+    fn prepare_type_alias() {
+        let (analysis, position, _) = fixture::annotations(
+            r#"
+-module(main).
+-type fo~o() :: integer().
+"#,
+        );
+        let navs = analysis
+            .type_hierarchy_prepare(position)
+            .unwrap()
+            .unwrap()
+            .info;
+        assert_eq!(navs.len(), 1);
+        assert_eq!(navs[0].name, "foo");
+    }
+
+    #[test]
+    fn prepare_function() {
+        let (analysis, position, _) = fixture::annotations(
+            r#"
+-module(main).
+fo~o() -> ok.
+"#,
+        );
+        assert!(analysis.type_hierarchy_prepare(position).unwrap().is_none());
+    }
+
+    #[test]
+    fn type_alias_supertypes() {
+        check_supertypes(
+            r#"
+//- /src/main.erl
+-module(main).
+-type fo~o() :: bar() | other:baz() | #rec{} | integer().
+  -type bar() :: ok.
+%%      ^^^^^
+  -record(rec, {}).
+%%        ^^^
+//- /src/other.erl
+-module(other).
+-export_type([baz/0]).
+  -type baz() :: ok.
+%%      ^^^^^
+"#,
+        );
+    }
+
+    #[test]
+    fn type_alias_subtypes() {
+        check_subtypes(
+            r#"
+//- /src/main.erl
+-module(main).
+-export_type([foo/0]).
+-type fo~o() :: integer().
+  -type bar() :: foo() | atom().
+%%      ^^^^^
+  -opaque baz() :: [foo()].
+%%        ^^^^^
+  -record(rec, {field :: foo()}).
+%%        ^^^
+-spec f(foo()) -> ok.
+f(_) -> ok.
+//- /src/other.erl
+-module(other).
+  -type qux() :: main:foo().
+%%      ^^^^^
+"#,
+        );
+    }
+
+    #[test]
+    fn record_supertypes() {
+        check_supertypes(
+            r#"
+-module(main).
+  -type bar() :: ok.
+%%      ^^^^^
+-record(re~c, {a :: bar(), b :: integer()}).
+"#,
+        );
+    }
+
+    #[test]
+    fn supertypes_through_macros() {
+        check_supertypes(
+            r#"
+-module(main).
+-define(BAR, bar()).
+-type fo~o() :: ?BAR | {?MODULE, atom()}.
+  -type bar() :: ok.
+%%      ^^^^^
+"#,
+        );
+    }
+
+    #[test]
+    fn header_type_supertypes() {
+        check_supertypes(
+            r#"
+//- /src/main.erl
+-module(main).
+-include("types.hrl").
+//- /src/types.hrl
+-type fo~o() :: bar().
+  -type bar() :: ok.
+%%      ^^^^^
+"#,
+        );
+    }
+
+    #[test]
+    fn recursive_type() {
+        check_supertypes(
+            r#"
+-module(main).
+-type tr~ee() :: leaf | {node, tree(), tree()}.
+"#,
+        );
+    }
+
+    #[test]
+    fn behaviour_subtypes() {
+        check_subtypes(
+            r#"
+//- /src/my_behaviour.erl
+-module(my_beh~aviour).
+-callback foo() -> ok.
+
+//- /src/impl_a.erl
+  -module(impl_a).
+%%^^^^^^^^^^^^^^^^
+-behaviour(my_behaviour).
+
+//- /src/not_impl.erl
+-module(not_impl).
+"#,
+        );
+    }
+
+    #[test]
+    fn module_supertypes() {
+        check_supertypes(
+            r#"
+//- /src/my_behaviour.erl
+  -module(my_behaviour).
+%%^^^^^^^^^^^^^^^^^^^^^^
+-callback foo() -> ok.
+
+//- /src/impl_a.erl
+-modu~le(impl_a).
+-behaviour(my_behaviour).
+-behaviour(gen_server).
+"#,
+        );
+    }
+}
@@ -29,6 +29,7 @@ use fxhash::FxHashSet;
 use hir::Clause;
 use hir::CompileOption;
 use hir::FormList;
+use hir::FunctionDef;
 use hir::InFileAstPtr;
 use hir::InFunctionBody;
 use hir::NameArity;
@@ -252,10 +253,10 @@ pub(crate) fn simple_param_vars(clause: &InFunctionBody<&Clause>) -> Option<FxHa
 }
 
 #[derive(Debug)]
-pub(crate) struct FunctionRanges {
-    pub(crate) function: TextRange,
-    pub(crate) spec: Option<TextRange>,
-    pub(crate) edoc: Vec<TextRange>,
+pub struct FunctionRanges {
+    pub function: TextRange,
+    pub spec: Option<TextRange>,
+    pub edoc: Vec<TextRange>,
 }
 
 impl FunctionRanges {
@@ -268,6 +269,13 @@ impl FunctionRanges {
             builder.delete(*range);
         });
     }
+
+    /// All the ranges to delete, for use outside of assists.
+    pub fn ranges(&self) -> impl Iterator<Item = TextRange> + '_ {
+        iter::once(self.function)
+            .chain(self.spec)
+            .chain(self.edoc.iter().copied())
+    }
 }
 
 pub(crate) fn ranges_for_delete_function(
@@ -284,12 +292,29 @@ pub(crate) fn ranges_for_delete_function(
         _ => None,
     }?;
 
-    let def_map = ctx.sema.def_map(ctx.file_id());
+    Some(function_ranges(
+        &ctx.sema,
+        ctx.file_id(),
+        &function_def,
+        ast_fun,
+    ))
+}
+
+/// The ranges covering a function, its spec and its edoc comments, so
+/// that the function can be deleted along with them.
+pub fn function_ranges(
+    sema: &Semantic,
+    file_id: FileId,
+    function_def: &FunctionDef,
+    ast_fun: &ast::FunDecl,
+) -> FunctionRanges {
+    let source_file = sema.parse(file_id);
+    let def_map = sema.def_map(file_id);
     let spec = def_map.get_spec(&function_def.function.name);
 
-    let edoc_comments: Vec<InFileAstPtr<ast::Comment>> = if let Some(file_edoc) =
-        ctx.sema.form_edoc_comments(InFileAstPtr::new(
-            ctx.file_id(),
+    let edoc_comments: Vec<InFileAstPtr<ast::Comment>> = if let Some(file_edoc) = sema
+        .form_edoc_comments(InFileAstPtr::new(
+            file_id,
             AstPtr::new(&ast::Form::FunDecl(ast_fun.clone())),
         )) {
         file_edoc.comments()
@@ -300,24 +325,24 @@ pub(crate) fn ranges_for_delete_function(
     let edoc = edoc_comments
         .iter()
         .filter_map(|c| {
-            let comment = ctx.ast_ptr_get(*c)?;
+            let comment = c.to_node(&source_file)?;
             Some(extend_form_range_for_delete(comment.syntax()))
         })
         .collect();
 
     let spec_range = spec.map(|spec| {
-        let ast_spec = ctx.form_ast(spec.spec.form_id);
+        let ast_spec = spec.spec.form_id.get(&source_file.value);
         extend_form_range_for_delete(ast_spec.syntax())
     });
 
-    Some(FunctionRanges {
+    FunctionRanges {
         function: extend_form_range_for_delete(ast_fun.syntax()),
         spec: spec_range,
         edoc,
-    })
+    }
 }
 
-fn extend_form_range_for_delete(syntax: &SyntaxNode) -> TextRange {
+pub fn extend_form_range_for_delete(syntax: &SyntaxNode) -> TextRange {
     let orig_range = syntax.text_range();
     let start = orig_range.start();
     let end = match skip_trailing_newline(syntax) {
@@ -405,7 +430,7 @@ fn add_to_compile_attribute<'a>(
 
 // ---------------------------------------------------------------------
 
-pub(crate) struct ExportBuilder<'a> {
+pub struct ExportBuilder<'a> {
     sema: &'a Semantic<'a>,
     file_id: FileId,
     funs: &'a [NameArity],
@@ -418,7 +443,7 @@ pub(crate) struct ExportBuilder<'a> {
 }
 
 impl<'a> ExportBuilder<'a> {
-    pub(crate) fn new(
+    pub fn new(
         sema: &'a Semantic<'a>,
         file_id: FileId,
         funs: &'a [NameArity],
@@ -450,7 +475,7 @@ impl<'a> ExportBuilder<'a> {
         self
     }
 
-    pub(crate) fn finish(&mut self) {
+    pub fn finish(&mut self) {
         let source = self.sema.parse(self.file_id).value;
         let form_list = self.sema.db.file_form_list(self.file_id);
         let export_text = self
@@ -0,0 +1,167 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Index of the atoms used in each file of a project, to narrow down
+//! project-wide searches for a name to the files mentioning it, and to
+//! find the functions named in literal `Module, Function, Args`
+//! sequences, which are not resolved as references.
+//!
+//! The atoms of a file are computed by a salsa query from that file only,
+//! and combined in a project-wide index, so the index is built once for
+//! the whole project, and an edit only rescans the edited file.
+
+use std::sync::Arc;
+
+use elp_base_db::salsa;
+use elp_base_db::FileId;
+use elp_base_db::ProjectId;
+use elp_syntax::ast;
+use elp_syntax::AstNode;
+use elp_syntax::SmolStr;
+use fxhash::FxHashMap;
+use fxhash::FxHashSet;
+use hir::db::MinDefDatabase;
+use hir::known;
+use hir::NameArity;
+
+#[salsa::query_group(AtomIndexDatabaseStorage)]
+pub trait AtomIndexDatabase: MinDefDatabase {
+    /// The atoms used in the file, and the functions it names in literal
+    /// `Module, Function, Args` sequences.
+    fn file_atoms(&self, file_id: FileId) -> Arc<FileAtoms>;
+
+    /// The atoms used in the files of the project, and the functions they
+    /// name in literal `Module, Function, Args` sequences.
+    fn project_atoms(&self, project_id: ProjectId) -> Arc<ProjectAtoms>;
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FileAtoms {
+    atoms: FxHashSet<SmolStr>,
+    mfas: FxHashSet<Mfa>,
+}
+
+/// A function named by a literal `Module, Function, Args` sequence, as
+/// given to `apply/3` or `spawn/3`, or in a `{M, F, A}` tuple.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Mfa {
+    pub module: SmolStr,
+    pub function: SmolStr,
+    /// `None` when the arguments are not a literal list, in which case the
+    /// function may have any arity.
+    pub arity: Option<u32>,
+}
+
+impl FileAtoms {
+    /// The atoms used in the file.
+    pub fn atoms(&self) -> impl Iterator<Item = &SmolStr> {
+        self.atoms.iter()
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ProjectAtoms {
+    files: FxHashMap<SmolStr, Vec<FileId>>,
+    mfas: FxHashSet<Mfa>,
+}
+
+impl ProjectAtoms {
+    /// The files of the project using the atom.
+    pub fn files_with(&self, atom: &str) -> &[FileId] {
+        self.files.get(atom).map_or(&[], |files| files.as_slice())
+    }
+
+    /// Whether the function of the module is named by a literal
+    /// `Module, Function, Args` sequence anywhere in the project.
+    pub fn is_mfa_target(&self, module: &str, function: &NameArity) -> bool {
+        let mfa = |arity| Mfa {
+            module: module.into(),
+            function: function.name().as_str().into(),
+            arity,
+        };
+        self.mfas.contains(&mfa(Some(function.arity()))) || self.mfas.contains(&mfa(None))
+    }
+}
+
+fn file_atoms(db: &dyn AtomIndexDatabase, file_id: FileId) -> Arc<FileAtoms> {
+    let module = db
+        .app_data(db.file_source_root(file_id))
+        .and_then(|app_data| {
+            db.module_index(app_data.project_id)
+                .module_for_file(file_id)
+                .map(|module| SmolStr::new(module.as_str()))
+        });
+    let mut res = FileAtoms::default();
+    for atom in db
+        .parse(file_id)
+        .tree()
+        .syntax()
+        .descendants()
+        .filter_map(ast::Atom::cast)
+    {
+        if let Some(mfa) = mfa(&atom, module.as_ref()) {
+            res.mfas.insert(mfa);
+        }
+        if let Some(text) = atom.text() {
+            res.atoms.insert(text.into());
+        }
+    }
+    Arc::new(res)
+}
+
+fn project_atoms(db: &dyn AtomIndexDatabase, project_id: ProjectId) -> Arc<ProjectAtoms> {
+    let mut res = ProjectAtoms::default();
+    for &source_root_id in &db.project_data(project_id).source_roots {
+        for file_id in db.source_root(source_root_id).iter() {
+            let file_atoms = db.file_atoms(file_id);
+            for atom in &file_atoms.atoms {
+                res.files.entry(atom.clone()).or_default().push(file_id);
+            }
+            res.mfas.extend(file_atoms.mfas.iter().cloned());
+        }
+    }
+    Arc::new(res)
+}
+
+/// The function named by the atom, if it is preceded by a module and
+/// followed by arguments, either as call arguments, e.g. `apply(M, F, A)`,
+/// or in a tuple, e.g. `{M, F, A}`. `?MODULE` stands for the module of
+/// the file, if any.
+fn mfa(atom: &ast::Atom, module: Option<&SmolStr>) -> Option<Mfa> {
+    let parent = atom.syntax().parent()?;
+    let exprs: Vec<ast::Expr> = if let Some(args) = ast::ExprArgs::cast(parent.clone()) {
+        args.args().collect()
+    } else if let Some(tuple) = ast::Tuple::cast(parent) {
+        tuple.expr().collect()
+    } else {
+        return None;
+    };
+    let idx = exprs
+        .iter()
+        .position(|expr| expr.syntax() == atom.syntax())
+        .filter(|idx| *idx > 0)?;
+    let module = match &exprs[idx - 1] {
+        ast::Expr::ExprMax(ast::ExprMax::Atom(m)) => m.text()?.into(),
+        ast::Expr::ExprMax(ast::ExprMax::MacroCallExpr(m))
+            if m.name()?.raw_text() == known::MODULE.as_str() =>
+        {
+            module?.clone()
+        }
+        _ => return None,
+    };
+    let arity = match exprs.get(idx + 1)? {
+        ast::Expr::ExprMax(ast::ExprMax::List(args)) => Some(args.exprs().count() as u32),
+        _ => None,
+    };
+    Some(Mfa {
+        module,
+        function: atom.text()?.into(),
+        arity,
+    })
+}
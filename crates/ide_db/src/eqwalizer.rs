@@ -12,6 +12,8 @@ use std::sync::Arc;
 use elp_base_db::salsa;
 use elp_base_db::AbsPath;
 use elp_base_db::FileId;
+use elp_base_db::FileLoader;
+use elp_base_db::FileRange;
 use elp_base_db::FileSource;
 use elp_base_db::ModuleName;
 use elp_base_db::ProjectId;
@@ -19,11 +21,16 @@ use elp_base_db::SourceDatabase;
 use elp_base_db::SourceRootId;
 use elp_eqwalizer::ast::db::EqwalizerASTDatabase;
 use elp_eqwalizer::ast::db::EqwalizerErlASTStorage;
+use elp_eqwalizer::ast::types::RecordType;
+use elp_eqwalizer::ast::types::Type;
 use elp_eqwalizer::ast::Error;
+use elp_eqwalizer::ast::Id;
+use elp_eqwalizer::ast::RemoteId;
 use elp_eqwalizer::ipc::IpcHandle;
 use elp_eqwalizer::EqwalizerDiagnostics;
 use elp_eqwalizer::EqwalizerDiagnosticsDatabase;
 use elp_eqwalizer::EqwalizerStats;
+use elp_eqwalizer::EqwalizerTypeInfo;
 use elp_syntax::ast;
 use parking_lot::Mutex;
 
@@ -36,6 +43,13 @@ pub trait EqwalizerLoader {
         build_info_path: &AbsPath,
         modules: Vec<FileId>,
     ) -> EqwalizerDiagnostics;
+
+    fn type_info(
+        &self,
+        project_id: ProjectId,
+        build_info_path: &AbsPath,
+        file_id: FileId,
+    ) -> Option<Arc<Vec<EqwalizerTypeInfo>>>;
 }
 
 impl EqwalizerLoader for crate::RootDatabase {
@@ -53,6 +67,34 @@ impl EqwalizerLoader for crate::RootDatabase {
         self.eqwalizer
             .typecheck(build_info_path.as_ref(), self, project_id, module_names)
     }
+
+    fn type_info(
+        &self,
+        project_id: ProjectId,
+        build_info_path: &AbsPath,
+        file_id: FileId,
+    ) -> Option<Arc<Vec<EqwalizerTypeInfo>>> {
+        let text = self.file_text(file_id);
+        let module_index = self.module_index(project_id);
+        let module_name = module_index.module_for_file(file_id)?;
+        let type_info = match self.eqwalizer.type_info(
+            build_info_path.as_ref(),
+            self,
+            project_id,
+            module_name.as_str(),
+        ) {
+            Ok(type_info) => Arc::new(type_info),
+            Err(err) => {
+                log::error!("Getting eqWAlizer type info failed: {}", err);
+                return None;
+            }
+        };
+        // Kept for hover, which only shows type info already computed
+        self.eqwalizer_type_info_cache
+            .write()
+            .insert(file_id, (text, type_info.clone()));
+        Some(type_info)
+    }
 }
 
 #[salsa::query_group(EqwalizerDatabaseStorage)]
@@ -68,6 +110,11 @@ pub trait EqwalizerDatabase:
         project_id: ProjectId,
         file_ids: Vec<FileId>,
     ) -> Arc<EqwalizerDiagnostics>;
+    fn eqwalizer_type_info(
+        &self,
+        project_id: ProjectId,
+        file_id: FileId,
+    ) -> Option<Arc<Vec<EqwalizerTypeInfo>>>;
     fn eqwalizer_stats(
         &self,
         project_id: ProjectId,
@@ -96,6 +143,106 @@ fn eqwalizer_diagnostics(
     }
 }
 
+/// The types inferred by eqWAlizer for the expressions and variables of
+/// the module. This runs eqWAlizer on the module, asking it to record them.
+fn eqwalizer_type_info(
+    db: &dyn EqwalizerDatabase,
+    project_id: ProjectId,
+    file_id: FileId,
+) -> Option<Arc<Vec<EqwalizerTypeInfo>>> {
+    if !db.is_eqwalizer_enabled(file_id, false) {
+        return None;
+    }
+    let project = db.project_data(project_id);
+    let build_info_path = project.build_info_path.as_ref()?;
+    db.type_info(project_id, build_info_path, file_id)
+}
+
+/// The type of the innermost expression or variable enclosing the range,
+/// as last inferred by eqWAlizer. This never runs eqWAlizer.
+pub fn type_at_range(db: &crate::RootDatabase, range: FileRange) -> Option<EqwalizerTypeInfo> {
+    db.cached_eqwalizer_type_info(range.file_id)?
+        .iter()
+        .filter(|info| info.range.contains_range(range.range))
+        .min_by_key(|info| info.range.len())
+        .cloned()
+}
+
+/// A type or record referred to from the definition of another one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeReference {
+    Type(RemoteId),
+    Record(RecordType),
+}
+
+/// The types and records the type alias or opaque of the module is
+/// defined in terms of, as converted by eqWAlizer. Macros are expanded,
+/// local types are qualified with their module and built-in types are
+/// left out.
+pub fn type_alias_references(
+    db: &dyn EqwalizerDatabase,
+    project_id: ProjectId,
+    module: ModuleName,
+    name: &str,
+    arity: u32,
+) -> Vec<TypeReference> {
+    let stub = match db.expanded_stub(project_id, module) {
+        Ok(stub) => stub,
+        Err(_) => return Vec::new(),
+    };
+    let id = Id {
+        name: name.into(),
+        arity,
+    };
+    let mut acc = Vec::new();
+    let decl = stub
+        .types
+        .get(&id)
+        .or_else(|| stub.private_opaques.get(&id));
+    if let Some(decl) = decl {
+        collect_type_references(&decl.body, &mut acc);
+    }
+    acc
+}
+
+/// The types and records the fields of the record of the module are
+/// declared with, as converted by eqWAlizer.
+pub fn record_references(
+    db: &dyn EqwalizerDatabase,
+    project_id: ProjectId,
+    module: ModuleName,
+    name: &str,
+) -> Vec<TypeReference> {
+    let stub = match db.expanded_stub(project_id, module) {
+        Ok(stub) => stub,
+        Err(_) => return Vec::new(),
+    };
+    let mut acc = Vec::new();
+    if let Some(decl) = stub.records.get(name) {
+        decl.fields
+            .iter()
+            .filter_map(|field| field.tp.as_ref())
+            .for_each(|ty| collect_type_references(ty, &mut acc));
+    }
+    acc
+}
+
+fn collect_type_references(ty: &Type, acc: &mut Vec<TypeReference>) {
+    let _: Result<(), ()> = ty.traverse(&mut |ty| {
+        let reference = match ty {
+            Type::RemoteType(ty) => TypeReference::Type(ty.id.clone()),
+            Type::OpaqueType(ty) => TypeReference::Type(ty.id.clone()),
+            Type::RecordType(ty) => TypeReference::Record(ty.clone()),
+            Type::RefinedRecordType(ty) => TypeReference::Record(ty.rec_type.clone()),
+            _ => return Ok(()),
+        };
+        if !acc.contains(&reference) {
+            acc.push(reference);
+        }
+        Ok(())
+    });
+}
+
 fn eqwalizer_stats(
     db: &dyn EqwalizerDatabase,
     project_id: ProjectId,
@@ -41,6 +41,7 @@ use serde::Deserialize;
 use serde::Serialize;
 
 mod apply_change;
+pub mod atom_index;
 mod defs;
 pub mod docs;
 pub mod eqwalizer;
@@ -48,6 +49,7 @@ mod erl_ast;
 mod fixmes;
 mod line_index;
 mod search;
+pub mod symbol_index;
 
 // ---------------------------------------------------------------------
 pub mod assists;
@@ -56,6 +58,7 @@ pub mod label;
 pub mod rename;
 pub mod source_change;
 
+pub use atom_index::AtomIndexDatabase;
 pub use defs::ReferenceClass;
 pub use defs::ReferenceType;
 pub use defs::SymbolClass;
@@ -66,6 +69,7 @@ pub use elp_eqwalizer::Eqwalizer;
 pub use elp_eqwalizer::EqwalizerDiagnostic;
 pub use elp_eqwalizer::EqwalizerDiagnostics;
 pub use elp_eqwalizer::EqwalizerStats;
+pub use elp_eqwalizer::EqwalizerTypeInfo;
 pub use elp_erlang_service as erlang_service;
 pub use eqwalizer::EqwalizerDatabase;
 pub use erl_ast::ErlAstDatabase;
@@ -75,6 +79,7 @@ pub use search::FindUsages;
 pub use search::ReferenceCategory;
 pub use search::SearchScope;
 pub use search::UsageSearchResult;
+pub use symbol_index::SymbolIndexDatabase;
 
 pub type FxIndexMap<K, V> =
     indexmap::IndexMap<K, V, std::hash::BuildHasherDefault<rustc_hash::FxHasher>>;
@@ -84,6 +89,11 @@ pub type FxIndexMap<K, V> =
 type EqwalizerProgressReporterBox =
     Arc<AssertUnwindSafe<Mutex<Option<Box<dyn EqwalizerProgressReporter>>>>>;
 
+/// The eqWAlizer type info last computed for each file, with the text it
+/// was computed for.
+type EqwalizerTypeInfoCache =
+    Arc<AssertUnwindSafe<RwLock<FxHashMap<FileId, (Arc<String>, Arc<Vec<EqwalizerTypeInfo>>)>>>>;
+
 pub trait EqwalizerProgressReporter: Send + Sync + RefUnwindSafe {
     fn start_module(&mut self, module: String);
     fn done_module(&mut self, module: &str);
@@ -91,6 +101,7 @@ pub trait EqwalizerProgressReporter: Send + Sync + RefUnwindSafe {
 
 #[salsa::database(
     LineIndexDatabaseStorage,
+    atom_index::AtomIndexDatabaseStorage,
     docs::DocDatabaseStorage,
     elp_base_db::SourceDatabaseExtStorage,
     elp_base_db::SourceDatabaseStorage,
@@ -99,7 +110,8 @@ pub trait EqwalizerProgressReporter: Send + Sync + RefUnwindSafe {
     elp_eqwalizer::EqwalizerDiagnosticsDatabaseStorage,
     erl_ast::ErlAstDatabaseStorage,
     hir::db::MinInternDatabaseStorage,
-    hir::db::MinDefDatabaseStorage
+    hir::db::MinDefDatabaseStorage,
+    symbol_index::SymbolIndexDatabaseStorage
 )]
 pub struct RootDatabase {
     storage: salsa::Storage<Self>,
@@ -107,6 +119,7 @@ pub struct RootDatabase {
     eqwalizer: Eqwalizer,
     eqwalizer_progress_reporter: EqwalizerProgressReporterBox,
     ipc_handles: Arc<AssertUnwindSafe<RwLock<FxHashMap<String, Arc<Mutex<IpcHandle>>>>>>,
+    eqwalizer_type_info_cache: EqwalizerTypeInfoCache,
 }
 
 impl Default for RootDatabase {
@@ -117,6 +130,7 @@ impl Default for RootDatabase {
             eqwalizer: Eqwalizer::default(),
             eqwalizer_progress_reporter: EqwalizerProgressReporterBox::default(),
             ipc_handles: Arc::default(),
+            eqwalizer_type_info_cache: Arc::default(),
         };
         db.set_include_files_revision(0);
         db
@@ -163,6 +177,7 @@ impl salsa::ParallelDatabase for RootDatabase {
             eqwalizer: self.eqwalizer.clone(),
             eqwalizer_progress_reporter: self.eqwalizer_progress_reporter.clone(),
             ipc_handles: self.ipc_handles.clone(),
+            eqwalizer_type_info_cache: self.eqwalizer_type_info_cache.clone(),
         })
     }
 }
@@ -220,6 +235,18 @@ impl RootDatabase {
         self.eqwalizer.shell = true
     }
 
+    /// The eqWAlizer type info last computed for the file, if the file has
+    /// not changed since. Unlike `eqwalizer_type_info`, this never runs
+    /// eqWAlizer.
+    pub fn cached_eqwalizer_type_info(
+        &self,
+        file_id: FileId,
+    ) -> Option<Arc<Vec<EqwalizerTypeInfo>>> {
+        let cache = self.eqwalizer_type_info_cache.read();
+        let (text, type_info) = cache.get(&file_id)?;
+        Arc::ptr_eq(text, &self.file_text(file_id)).then(|| type_info.clone())
+    }
+
     pub fn resolved_includes(&self, file_id: FileId) -> Option<Includes> {
         let source_file = self.parse(file_id).tree();
         let project_id = self.app_data(self.file_source_root(file_id))?.project_id;
@@ -14,16 +14,28 @@
 use std::fmt;
 use std::iter::once;
 
+use elp_base_db::to_quoted_string;
+use elp_base_db::AnchoredPathBuf;
 use elp_base_db::FileId;
 use elp_base_db::FileRange;
 use elp_syntax::ast;
 use elp_syntax::ast::in_erlang_module;
 use elp_syntax::AstNode;
+use fxhash::FxHashMap;
+use fxhash::FxHashSet;
+use hir::CallbackDef;
+use hir::DefineDef;
+use hir::FunctionDef;
 use hir::InFile;
+use hir::MacroName;
+use hir::Module;
+use hir::PPCondition;
 use hir::Semantic;
+use text_edit::Indel;
 use text_edit::TextEdit;
 
 use crate::search::NameLike;
+use crate::source_change::FileSystemEdit;
 use crate::source_change::SourceChange;
 use crate::SymbolDefinition;
 
@@ -77,6 +89,99 @@ pub fn is_valid_function_name(new_name: String) -> bool {
     }
 }
 
+// Delegate checking name validity to the parser
+pub fn is_valid_atom_name(new_name: &str) -> bool {
+    let parse = ast::SourceFile::parse_text(format!("foo() -> {}.", new_name).as_str());
+    parse.errors().is_empty()
+        && parse.tree().syntax().descendants().any(|node| {
+            if let Some(atom) = ast::Atom::cast(node) {
+                atom.syntax().text().to_string() == new_name
+            } else {
+                false
+            }
+        })
+}
+
+// Delegate checking name validity to the parser.  Module names also
+// become file names, so we do not accept names that need quoting.
+pub fn is_valid_module_name(new_name: String) -> bool {
+    if to_quoted_string(&new_name) != new_name {
+        return false;
+    }
+    let parse = ast::SourceFile::parse_text(format!("-module({}).", new_name).as_str());
+    match parse.tree().forms().next() {
+        Some(ast::Form::ModuleAttribute(attr)) => match attr.name() {
+            Some(ast::Name::Atom(atom)) => atom.syntax().text().to_string() == new_name,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+// Delegate checking name validity to the parser
+pub fn is_valid_macro_name(new_name: &str) -> bool {
+    let parse = ast::SourceFile::parse_text(format!("-define({}, 1).", new_name).as_str());
+    match parse.tree().forms().next() {
+        Some(ast::Form::PreprocessorDirective(ast::PreprocessorDirective::PpDefine(define))) => {
+            match define.lhs().and_then(|lhs| lhs.name()) {
+                Some(name) => name.syntax().text().to_string() == new_name,
+                None => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+/// Header names become file names, so we only accept a conservative
+/// set of characters.
+pub fn is_valid_header_name(new_name: &str) -> bool {
+    match new_name.strip_suffix(".hrl") {
+        Some(stem) => {
+            !stem.is_empty()
+                && new_name
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '.')
+        }
+        None => false,
+    }
+}
+
+/// The file name for a renamed module, keeping the extension of the
+/// file it is defined in, e.g. `.escript`.
+fn module_file_name(sema: &Semantic, file_id: FileId, new_name: &str) -> String {
+    let source_root = sema.db.source_root(sema.db.file_source_root(file_id));
+    let ext = source_root
+        .path_for_file(&file_id)
+        .and_then(|path| path.name_and_extension())
+        .and_then(|(_name, ext)| ext)
+        .unwrap_or("erl");
+    format!("{}.{}", new_name, ext)
+}
+
+/// The new name for a header may be given with or without its
+/// extension.
+fn header_file_name(new_name: &str) -> String {
+    if new_name.ends_with(".hrl") {
+        new_name.to_string()
+    } else {
+        format!("{}.hrl", new_name)
+    }
+}
+
+/// Replace the last component of the quoted include path `text`, if
+/// it is `old_name`. Everything before it is kept as is, so relative
+/// paths stay relative and `-include_lib` paths keep their
+/// application prefix.
+fn rename_include_path(text: &str, old_name: &str, new_name: &str) -> Option<String> {
+    let path = text.strip_prefix('"')?.strip_suffix('"')?;
+    let dir = path.strip_suffix(old_name)?;
+    if dir.is_empty() || dir.ends_with('/') {
+        Some(format!("\"{}{}\"", dir, new_name))
+    } else {
+        None
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SafetyChecks {
     Yes,
@@ -91,8 +196,19 @@ impl SymbolDefinition {
         safety_check: SafetyChecks,
     ) -> RenameResult<SourceChange> {
         match self.clone() {
-            SymbolDefinition::Module(_) => {
-                rename_error!("Cannot rename module")
+            SymbolDefinition::Module(module) => {
+                if safety_check == SafetyChecks::Yes {
+                    if !is_valid_module_name(get_name(None)) {
+                        rename_error!("Invalid new module name: '{}'", get_name(None));
+                    }
+                    if sema
+                        .resolve_module_name(module.file.file_id, &get_name(None))
+                        .is_some()
+                    {
+                        rename_error!("Module '{}' already exists", get_name(None));
+                    }
+                }
+                self.rename_reference(sema, get_name, safety_check)
             }
             SymbolDefinition::Function(fun) => {
                 if safety_check == SafetyChecks::Yes {
@@ -111,22 +227,160 @@ impl SymbolDefinition {
                 }
             }
             SymbolDefinition::Record(_) => {
-                rename_error!("Cannot rename record")
+                let new_name = get_name(None);
+                if safety_check == SafetyChecks::Yes {
+                    if !is_valid_atom_name(&new_name) {
+                        rename_error!("Invalid new record name: '{}'", new_name);
+                    }
+                    let scope = self.search_scope(sema);
+                    let mut problems = scope.file_ids().filter(|file_id| {
+                        sema.db
+                            .def_map(*file_id)
+                            .get_records()
+                            .keys()
+                            .any(|name| name.as_str() == new_name)
+                    });
+                    // Report the first one only, an existence proof of problems
+                    if let Some(file_id) = problems.next() {
+                        if let Some(module_name) = sema.module_name(file_id) {
+                            rename_error!(
+                                "Record '{}' already in scope in module '{}'",
+                                new_name,
+                                module_name.as_str()
+                            );
+                        } else {
+                            rename_error!("Record '{}' already in scope", new_name);
+                        }
+                    }
+                }
+                self.rename_reference(sema, get_name, safety_check)
             }
-            SymbolDefinition::RecordField(_) => {
-                rename_error!("Cannot rename record field")
+            SymbolDefinition::RecordField(field) => {
+                let new_name = get_name(None);
+                if safety_check == SafetyChecks::Yes {
+                    if !is_valid_atom_name(&new_name) {
+                        rename_error!("Invalid new record field name: '{}'", new_name);
+                    }
+                    if field
+                        .record
+                        .field_names(sema.db)
+                        .any(|name| name.as_str() == new_name)
+                    {
+                        rename_error!(
+                            "Field '{}' already defined in record '{}'",
+                            new_name,
+                            field.record.record.name
+                        );
+                    }
+                }
+                self.rename_reference(sema, get_name, safety_check)
             }
-            SymbolDefinition::Type(_) => {
-                rename_error!("Cannot rename type")
+            SymbolDefinition::Type(alias) => {
+                let new_name = get_name(None);
+                if safety_check == SafetyChecks::Yes {
+                    if !is_valid_atom_name(&new_name) {
+                        rename_error!("Invalid new type name: '{}'", new_name);
+                    }
+                    // Remote references are qualified by the module, so an
+                    // exported type can only clash in its own module.
+                    let file_ids: Vec<FileId> = if self.is_local() {
+                        self.search_scope(sema).file_ids().collect()
+                    } else {
+                        vec![alias.file.file_id]
+                    };
+                    let arity = alias.name().arity();
+                    let mut problems =
+                        file_ids.into_iter().filter(|file_id| {
+                            sema.db.def_map(*file_id).get_types().keys().any(|name| {
+                                name.name().as_str() == new_name && name.arity() == arity
+                            })
+                        });
+                    // Report the first one only, an existence proof of problems
+                    if let Some(file_id) = problems.next() {
+                        if let Some(module_name) = sema.module_name(file_id) {
+                            rename_error!(
+                                "Type '{}/{}' already in scope in module '{}'",
+                                new_name,
+                                arity,
+                                module_name.as_str()
+                            );
+                        } else {
+                            rename_error!("Type '{}/{}' already in scope", new_name, arity);
+                        }
+                    }
+                }
+                self.rename_reference(sema, get_name, safety_check)
             }
-            SymbolDefinition::Callback(_) => {
-                rename_error!("Cannot rename callback")
+            SymbolDefinition::Callback(callback) => {
+                let new_name = get_name(None);
+                if safety_check == SafetyChecks::Yes {
+                    if !is_valid_function_name(new_name.clone()) {
+                        rename_error!("Invalid new callback name: '{}'", new_name);
+                    }
+                    let arity = callback.callback.name.arity();
+                    if sema
+                        .db
+                        .def_map(callback.file.file_id)
+                        .get_callbacks()
+                        .keys()
+                        .any(|name| name.name().as_str() == new_name && name.arity() == arity)
+                    {
+                        rename_error!("Callback '{}/{}' already defined", new_name, arity);
+                    }
+                    for fun in behaviour_implementations(sema, &callback) {
+                        if !is_safe_function(sema, fun.file.file_id, &new_name, arity) {
+                            if let Some(module_name) = sema.module_name(fun.file.file_id) {
+                                rename_error!(
+                                    "Function '{}/{}' already in scope in module '{}'",
+                                    new_name,
+                                    arity,
+                                    module_name.as_str()
+                                );
+                            } else {
+                                rename_error!("Function '{}/{}' already in scope", new_name, arity);
+                            }
+                        }
+                    }
+                }
+                self.rename_reference(sema, get_name, safety_check)
             }
-            SymbolDefinition::Define(_) => {
-                rename_error!("Cannot rename define")
+            SymbolDefinition::Define(define) => {
+                let new_name = get_name(None);
+                if safety_check == SafetyChecks::Yes {
+                    if !is_valid_macro_name(&new_name) {
+                        rename_error!("Invalid new macro name: '{}'", new_name);
+                    }
+                    let arity = define.define.name.arity();
+                    let scope = self.search_scope(sema);
+                    for file_id in scope.file_ids() {
+                        if let Some(problem) = macro_clash(sema, file_id, &new_name, arity) {
+                            if let Some(module_name) = sema.module_name(file_id) {
+                                rename_error!("{} in module '{}'", problem, module_name.as_str());
+                            } else {
+                                rename_error!("{}", problem);
+                            }
+                        }
+                    }
+                }
+                self.rename_reference(sema, get_name, safety_check)
             }
-            SymbolDefinition::Header(_) => {
-                rename_error!("Cannot rename header")
+            SymbolDefinition::Header(header) => {
+                let new_name = header_file_name(&get_name(None));
+                if safety_check == SafetyChecks::Yes {
+                    if !is_valid_header_name(&new_name) {
+                        rename_error!("Invalid new header name: '{}'", get_name(None));
+                    }
+                    let source_root = sema
+                        .db
+                        .source_root(sema.db.file_source_root(header.file_id));
+                    if source_root
+                        .relative_path(header.file_id, &new_name)
+                        .is_some()
+                    {
+                        rename_error!("Header '{}' already exists", new_name);
+                    }
+                }
+                self.rename_reference(sema, get_name, safety_check)
             }
             SymbolDefinition::Var(_) => {
                 if safety_check == SafetyChecks::Yes {
@@ -166,6 +420,156 @@ impl SymbolDefinition {
         let file_id = self.file().file_id;
         let mut source_change = SourceChange::default();
         match self {
+            SymbolDefinition::Module(module) => {
+                let def_name = module
+                    .file
+                    .source(sema.db.upcast())
+                    .forms()
+                    .find_map(|form| match form {
+                        ast::Form::ModuleAttribute(attr) => attr.name(),
+                        _ => None,
+                    });
+                self.source_edit_with_def(sema, &mut source_change, def_name, get_name);
+
+                // The module name must match the file name, so move the file too
+                source_change.push_file_system_edit(FileSystemEdit::MoveFile {
+                    src: file_id,
+                    dst: AnchoredPathBuf {
+                        anchor: file_id,
+                        path: module_file_name(sema, file_id, &get_name(None)),
+                    },
+                });
+                Ok(source_change)
+            }
+            SymbolDefinition::Record(record) => {
+                let def_name = record.source(sema.db.upcast()).name();
+                self.source_edit_with_def(sema, &mut source_change, def_name, get_name);
+                Ok(source_change)
+            }
+            SymbolDefinition::RecordField(field) => {
+                let def_name = field.source(sema.db.upcast()).name();
+                self.source_edit_with_def(sema, &mut source_change, def_name, get_name);
+                Ok(source_change)
+            }
+            SymbolDefinition::Header(header) => {
+                let old_name = header.name(sema.db.upcast());
+                let new_name = header_file_name(&get_name(None));
+                let usages = self.clone().usages(sema).all();
+                for (file_id, refs) in usages.iter() {
+                    let mut edit = TextEdit::builder();
+                    let mut edited_ranges = FxHashSet::default();
+                    for name in refs {
+                        if let NameLike::String(path) = name {
+                            let range = path.syntax().text_range();
+                            // A path can mention the header name more than once
+                            if !edited_ranges.insert(range) {
+                                continue;
+                            }
+                            let text = path.syntax().text().to_string();
+                            if let Some(new_path) = rename_include_path(&text, &old_name, &new_name)
+                            {
+                                edit.replace(range, new_path);
+                            }
+                        }
+                    }
+                    source_change.insert_source_edit(file_id, edit.finish());
+                }
+
+                // The header stays in the same directory, so only the
+                // last component of each include path changes.
+                source_change.push_file_system_edit(FileSystemEdit::MoveFile {
+                    src: file_id,
+                    dst: AnchoredPathBuf {
+                        anchor: file_id,
+                        path: new_name,
+                    },
+                });
+                Ok(source_change)
+            }
+            SymbolDefinition::Type(alias) => {
+                let def_name = alias
+                    .source(sema.db.upcast())
+                    .type_name()
+                    .and_then(|type_name| type_name.name());
+                self.source_edit_with_def(sema, &mut source_change, def_name, get_name);
+                Ok(source_change)
+            }
+            SymbolDefinition::Callback(callback) => {
+                let def_name = callback.source(sema.db.upcast()).fun();
+                self.source_edit_with_def(sema, &mut source_change, def_name, get_name);
+
+                // The implementations must keep matching the callback
+                let mut changes = vec![source_change];
+                for fun in behaviour_implementations(sema, callback) {
+                    changes.push(SymbolDefinition::Function(fun).rename_reference(
+                        sema,
+                        get_name,
+                        safety_check,
+                    )?);
+                }
+                Ok(merge_renames(changes))
+            }
+            SymbolDefinition::Define(define) => {
+                // Conditional compilation can give several alternative
+                // definitions of the same macro in the defining file,
+                // they must all be renamed together.
+                let form_list = sema.db.file_form_list(file_id);
+                let alternatives: Vec<_> = form_list
+                    .defines()
+                    .filter(|(_, alt)| alt.name == define.define.name)
+                    .map(|(_, alt)| DefineDef {
+                        file: define.file,
+                        define: alt.clone(),
+                    })
+                    .collect();
+
+                let mut seen = FxHashSet::default();
+                let mut usages: FxHashMap<FileId, Vec<NameLike>> = FxHashMap::default();
+                for alt in alternatives {
+                    let def_name = alt
+                        .source(sema.db.upcast())
+                        .lhs()
+                        .and_then(|lhs| lhs.name())
+                        .map(|name| match name {
+                            ast::MacroName::Atom(atom) => ast::Name::Atom(atom),
+                            ast::MacroName::Var(var) => ast::Name::Var(var),
+                        });
+                    let alt_usages = SymbolDefinition::Define(alt).usages(sema).all();
+                    let def_usages = def_name.map(|name| (file_id, NameLike::Name(name)));
+                    for (file_id, name) in alt_usages
+                        .into_iter()
+                        .flat_map(|(file_id, refs)| refs.into_iter().map(move |r| (file_id, r)))
+                        .chain(def_usages)
+                    {
+                        // `-ifdef` and friends refer to all alternatives
+                        if seen.insert((file_id, name.syntax().text_range())) {
+                            usages.entry(file_id).or_default().push(name);
+                        }
+                    }
+                }
+                // `-ifdef`, `-ifndef` and `-undef` refer to the macro name
+                // whatever its arity, so they can only follow the rename
+                // when no other arity is defined.
+                if has_other_arity(sema, self, &define.define.name)
+                    && usages.values().flatten().any(is_pp_condition_name)
+                {
+                    if safety_check == SafetyChecks::Yes {
+                        rename_error!(
+                            "Macro '{}' is also defined with another arity, so its '-ifdef', '-ifndef' and '-undef' attributes cannot be renamed",
+                            define.define.name.name().as_str()
+                        );
+                    }
+                    for refs in usages.values_mut() {
+                        refs.retain(|name| !is_pp_condition_name(name));
+                    }
+                }
+                let usages: Vec<_> = usages
+                    .iter()
+                    .map(|(file_id, refs)| (*file_id, &refs[..]))
+                    .collect();
+                source_edit_from_usages(&mut source_change, usages, get_name);
+                Ok(source_change)
+            }
             SymbolDefinition::Function(function) => {
                 let usages = self.clone().usages(sema).all();
                 let mut def_usages = Vec::default();
@@ -256,6 +660,26 @@ impl SymbolDefinition {
     }
 }
 
+impl SymbolDefinition {
+    /// Rename all the usages of the definition found by
+    /// `FindUsages`, together with the name in the definition itself.
+    fn source_edit_with_def(
+        &self,
+        sema: &Semantic,
+        source_change: &mut SourceChange,
+        def_name: Option<ast::Name>,
+        get_name: &dyn Fn(Option<&ast::Name>) -> String,
+    ) {
+        let usages = self.clone().usages(sema).all();
+        let def_usages: Vec<_> = def_name.map(NameLike::Name).into_iter().collect();
+        let usages: Vec<_> = usages
+            .iter()
+            .chain(once((self.file().file_id, &def_usages[..])))
+            .collect();
+        source_edit_from_usages(source_change, usages, get_name);
+    }
+}
+
 fn source_edit_from_usages(
     source_change: &mut SourceChange,
     usages: Vec<(FileId, &[NameLike])>,
@@ -367,6 +791,109 @@ fn is_safe_var_anonymous(var_in: InFile<&ast::Var>) -> bool {
     }
 }
 
+/// The functions implementing the given callback, in every module
+/// declaring the behaviour that defines it.
+fn behaviour_implementations(sema: &Semantic, callback: &CallbackDef) -> Vec<FunctionDef> {
+    let behaviour = Module {
+        file: callback.file,
+    };
+    sema.behaviour_implementors(callback.file.file_id, &behaviour)
+        .into_iter()
+        .filter_map(|module| {
+            sema.def_map(module.file.file_id)
+                .get_function(&callback.callback.name)
+                .cloned()
+        })
+        .collect()
+}
+
+/// Combine the changes renaming several definitions together. They can
+/// edit the same ranges, e.g. when a behaviour module implements its
+/// own callbacks, so the edits are merged per file, dropping those
+/// already made.
+fn merge_renames(changes: Vec<SourceChange>) -> SourceChange {
+    let mut indels: FxHashMap<FileId, Vec<Indel>> = FxHashMap::default();
+    let mut source_change = SourceChange::default();
+    for change in changes {
+        for (file_id, edit) in change.source_file_edits {
+            indels.entry(file_id).or_default().extend(edit);
+        }
+        source_change.extend(change.file_system_edits);
+    }
+    for (file_id, mut indels) in indels {
+        indels.sort_by_key(|indel| (indel.delete.start(), indel.delete.end()));
+        let mut edit = TextEdit::builder();
+        let mut end = None;
+        for indel in indels {
+            if end.map_or(false, |end| indel.delete.start() < end) {
+                continue;
+            }
+            end = Some(indel.delete.end());
+            edit.replace(indel.delete, indel.insert);
+        }
+        source_change.insert_source_edit(file_id, edit.finish());
+    }
+    source_change
+}
+
+/// Check whether the new macro name is already used in the given
+/// file, either by a definition with the same arity (including one
+/// guarded by conditional compilation) or by a preprocessor
+/// condition testing for it.
+fn macro_clash(
+    sema: &Semantic,
+    file_id: FileId,
+    new_name: &str,
+    arity: Option<u32>,
+) -> Option<String> {
+    let form_list = sema.db.file_form_list(file_id);
+    let macro_name = match arity {
+        Some(arity) => format!("{}/{}", new_name, arity),
+        None => new_name.to_string(),
+    };
+    if form_list
+        .defines()
+        .any(|(_, define)| define.name.name().as_str() == new_name && define.name.arity() == arity)
+    {
+        return Some(format!("Macro '{}' already defined", macro_name));
+    }
+    let tested = form_list.pp_conditions().any(|(_, cond)| match cond {
+        PPCondition::Ifdef { name, .. } | PPCondition::Ifndef { name, .. } => {
+            name.as_str() == new_name
+        }
+        _ => false,
+    });
+    if tested {
+        return Some(format!("Macro '{}' already used in a condition", new_name));
+    }
+    None
+}
+
+/// Whether a macro with the same name but a different arity is defined
+/// anywhere the macro is visible.
+fn has_other_arity(sema: &Semantic, def: &SymbolDefinition, name: &MacroName) -> bool {
+    def.search_scope(sema).file_ids().any(|file_id| {
+        sema.db
+            .file_form_list(file_id)
+            .defines()
+            .any(|(_, define)| {
+                define.name.name() == name.name() && define.name.arity() != name.arity()
+            })
+    })
+}
+
+fn is_pp_condition_name(name: &NameLike) -> bool {
+    match name.syntax().parent() {
+        Some(parent) => {
+            let kind = parent.kind();
+            ast::PpIfdef::can_cast(kind)
+                || ast::PpIfndef::can_cast(kind)
+                || ast::PpUndef::can_cast(kind)
+        }
+        None => false,
+    }
+}
+
 /// Check that the new function name is not in scope already.  This
 /// includes checking for auto-included functions from the `erlang`
 /// module.
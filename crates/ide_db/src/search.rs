@@ -24,7 +24,9 @@ use elp_syntax::AstNode;
 use elp_syntax::TextRange;
 use elp_syntax::TextSize;
 use fxhash::FxHashMap;
+use fxhash::FxHashSet;
 use hir::db::MinDefDatabase;
+use hir::File;
 use hir::FileKind;
 use hir::InFile;
 use hir::Semantic;
@@ -99,7 +101,7 @@ impl SearchScope {
         }
     }
 
-    fn files(files: impl Iterator<Item = FileId>) -> SearchScope {
+    pub fn files(files: impl Iterator<Item = FileId>) -> SearchScope {
         SearchScope {
             entries: files.map(|file_id| (file_id, None)).collect(),
         }
@@ -111,6 +113,10 @@ impl SearchScope {
         }
     }
 
+    pub fn file_ids(&self) -> impl Iterator<Item = FileId> + '_ {
+        self.entries.keys().copied()
+    }
+
     fn project(db: &dyn MinDefDatabase, project_id: ProjectId) -> SearchScope {
         let mut entries = FxHashMap::default();
 
@@ -135,7 +141,7 @@ impl IntoIterator for SearchScope {
 }
 
 impl SymbolDefinition {
-    fn search_scope(&self, sema: &Semantic) -> SearchScope {
+    pub fn search_scope(&self, sema: &Semantic) -> SearchScope {
         if let SymbolDefinition::Var(var) = self {
             let range = var
                 .source(sema.db.upcast())
@@ -161,9 +167,7 @@ impl SymbolDefinition {
                 FileKind::Header => {
                     let def_map = file.def_map(sema.db);
                     let included = def_map.get_included_files();
-                    let header = SymbolDefinition::Header(file.clone());
-                    let usages = header.usages(sema).all();
-                    let includers = usages.iter().map(|(file_id, _)| file_id);
+                    let includers = transitive_includers(sema, *file);
                     SearchScope::files(iter::once(file.file_id).chain(included).chain(includers))
                 }
                 FileKind::Other => SearchScope::single_file(self.file().file_id, None),
@@ -191,6 +195,20 @@ impl SymbolDefinition {
     }
 }
 
+/// All the files including the given header, either directly or
+/// through other headers.
+fn transitive_includers(sema: &Semantic, header: File) -> FxHashSet<FileId> {
+    let project_id = match sema.db.app_data(sema.db.file_source_root(header.file_id)) {
+        Some(app_data) => app_data.project_id,
+        None => return FxHashSet::default(),
+    };
+    sema.db
+        .header_includers(project_id)
+        .get(&header.file_id)
+        .cloned()
+        .unwrap_or_default()
+}
+
 #[derive(Clone)]
 pub struct FindUsages<'a> {
     def: SymbolDefinition,
@@ -0,0 +1,498 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Index of the symbols defined in each file, used for workspace symbol
+//! search.
+//!
+//! The symbols of a file are computed by a salsa query from its local
+//! definitions only, so an edit only invalidates the symbols of the edited
+//! file, and searching after a change does not rescan the rest of the
+//! project.
+
+use std::sync::Arc;
+
+use elp_base_db::salsa;
+use elp_base_db::FileId;
+use elp_base_db::ProjectId;
+use fxhash::FxHashSet;
+use hir::db::MinDefDatabase;
+use hir::File;
+use hir::Module;
+
+use crate::SymbolDefinition;
+
+/// Maximum number of results returned by a search.
+const LIMIT: usize = 128;
+
+#[salsa::query_group(SymbolIndexDatabaseStorage)]
+pub trait SymbolIndexDatabase: MinDefDatabase {
+    /// The functions, types, records, macros and callbacks defined in the
+    /// file itself, not in the headers it includes.
+    fn file_symbols(&self, file_id: FileId) -> Arc<Vec<FileSymbol>>;
+
+    /// The modules of the project and the headers they include, either
+    /// directly or through other headers.
+    fn project_symbol_files(&self, project_id: ProjectId) -> Arc<Vec<FileId>>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileSymbol {
+    /// The name of the symbol, without arity.
+    pub name: String,
+    pub arity: Option<u32>,
+    pub definition: SymbolDefinition,
+}
+
+impl FileSymbol {
+    fn label(&self) -> String {
+        match self.arity {
+            Some(arity) => format!("{}/{arity}", self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+fn file_symbols(db: &dyn SymbolIndexDatabase, file_id: FileId) -> Arc<Vec<FileSymbol>> {
+    let def_map = db.local_def_map(file_id);
+    let mut symbols = Vec::new();
+    for (name, def) in def_map.get_functions() {
+        symbols.push(FileSymbol {
+            name: name.name().to_string(),
+            arity: Some(name.arity()),
+            definition: SymbolDefinition::Function(def.clone()),
+        });
+    }
+    for (name, def) in def_map.get_types() {
+        symbols.push(FileSymbol {
+            name: name.name().to_string(),
+            arity: Some(name.arity()),
+            definition: SymbolDefinition::Type(def.clone()),
+        });
+    }
+    for (name, def) in def_map.get_records() {
+        symbols.push(FileSymbol {
+            name: name.to_string(),
+            arity: None,
+            definition: SymbolDefinition::Record(def.clone()),
+        });
+    }
+    for (name, def) in def_map.get_macros() {
+        symbols.push(FileSymbol {
+            name: name.name().to_string(),
+            arity: name.arity(),
+            definition: SymbolDefinition::Define(def.clone()),
+        });
+    }
+    for (name, def) in def_map.get_callbacks() {
+        symbols.push(FileSymbol {
+            name: name.name().to_string(),
+            arity: Some(name.arity()),
+            definition: SymbolDefinition::Callback(def.clone()),
+        });
+    }
+    symbols.retain(|symbol| symbol.definition.file().file_id == file_id);
+    symbols.sort_by(|a, b| (&a.name, a.arity).cmp(&(&b.name, b.arity)));
+    Arc::new(symbols)
+}
+
+fn project_symbol_files(db: &dyn SymbolIndexDatabase, project_id: ProjectId) -> Arc<Vec<FileId>> {
+    let own: FxHashSet<FileId> = db
+        .module_index(project_id)
+        .iter_own()
+        .map(|(_, _, file_id)| file_id)
+        .collect();
+    let includers = db.header_includers(project_id);
+    let headers = includers
+        .iter()
+        .filter(|(_, includers)| !includers.is_disjoint(&own))
+        .map(|(header, _)| *header);
+    let mut files: Vec<_> = own.iter().copied().chain(headers).collect();
+    files.sort();
+    files.dedup();
+    Arc::new(files)
+}
+
+/// The name shown alongside a symbol to tell it apart from symbols with
+/// the same name: the module defining it, or the header for symbols
+/// defined in headers.
+pub fn container_name(
+    db: &dyn SymbolIndexDatabase,
+    project_id: ProjectId,
+    definition: &SymbolDefinition,
+) -> Option<String> {
+    let file = match definition {
+        SymbolDefinition::Module(_) => return None,
+        definition => definition.file(),
+    };
+    match db.module_index(project_id).module_for_file(file.file_id) {
+        Some(module) => Some(module.as_str().to_string()),
+        None => Some(file.name(db.upcast()).to_string()),
+    }
+}
+
+/// What a query is restricted to, based on its prefix.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Scope {
+    /// Modules, and every symbol of the project modules and their headers.
+    All,
+    /// `#rec`: records only.
+    Records,
+    /// `?MACRO`: macros only.
+    Macros,
+    /// `mod:fun`: the functions, types and callbacks of the given module.
+    Module(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query {
+    scope: Scope,
+    pattern: String,
+}
+
+impl Query {
+    pub fn new(query: &str) -> Query {
+        let query = query.trim();
+        let (scope, pattern) = if let Some(pattern) = query.strip_prefix('#') {
+            (Scope::Records, pattern)
+        } else if let Some(pattern) = query.strip_prefix('?') {
+            (Scope::Macros, pattern)
+        } else if let Some((module, pattern)) = query.split_once(':') {
+            (Scope::Module(module.to_string()), pattern)
+        } else {
+            (Scope::All, query)
+        };
+        Query {
+            scope,
+            pattern: pattern.to_string(),
+        }
+    }
+
+    fn accepts(&self, symbol: &FileSymbol) -> bool {
+        match (&self.scope, &symbol.definition) {
+            (Scope::All, _) => true,
+            (Scope::Records, SymbolDefinition::Record(_)) => true,
+            (Scope::Macros, SymbolDefinition::Define(_)) => true,
+            (
+                Scope::Module(_),
+                SymbolDefinition::Function(_)
+                | SymbolDefinition::Type(_)
+                | SymbolDefinition::Callback(_),
+            ) => true,
+            _ => false,
+        }
+    }
+
+    /// The score of the symbol for the query, or `None` if it does not
+    /// match. The arity is only taken into account if the query has one.
+    fn score(&self, name: &str, label: impl FnOnce() -> String) -> Option<i64> {
+        if self.pattern.contains('/') {
+            fuzzy_score(&self.pattern, &label())
+        } else {
+            fuzzy_score(&self.pattern, name)
+        }
+    }
+}
+
+/// Search the symbols of the project matching the query, the best matches
+/// first.
+pub fn search(
+    db: &dyn SymbolIndexDatabase,
+    project_id: ProjectId,
+    query: &Query,
+) -> Vec<SymbolDefinition> {
+    let module_index = db.module_index(project_id);
+    let mut matches: Vec<(i64, String, SymbolDefinition)> = Vec::new();
+    let add_symbols = |file_id: FileId, matches: &mut Vec<_>| {
+        for symbol in db.file_symbols(file_id).iter() {
+            if !query.accepts(symbol) {
+                continue;
+            }
+            if let Some(score) = query.score(&symbol.name, || symbol.label()) {
+                matches.push((score, symbol.label(), symbol.definition.clone()));
+            }
+        }
+    };
+
+    match &query.scope {
+        Scope::Module(module) => {
+            if let Some(file_id) = module_index.file_for_module(module.as_str()) {
+                add_symbols(file_id, &mut matches);
+            }
+        }
+        scope => {
+            for &file_id in db.project_symbol_files(project_id).iter() {
+                add_symbols(file_id, &mut matches);
+            }
+            if *scope == Scope::All {
+                for name in module_index.all_modules() {
+                    if let Some(score) = fuzzy_score(&query.pattern, name.as_str()) {
+                        if let Some(file_id) = module_index.file_for_module(&name) {
+                            let module = Module {
+                                file: File { file_id },
+                            };
+                            matches.push((
+                                score,
+                                name.as_str().to_string(),
+                                SymbolDefinition::Module(module),
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    matches.sort_by(|(score_a, label_a, _), (score_b, label_b, _)| {
+        score_b.cmp(score_a).then_with(|| label_a.cmp(label_b))
+    });
+    matches
+        .into_iter()
+        .take(LIMIT)
+        .map(|(_, _, definition)| definition)
+        .collect()
+}
+
+/// Score a candidate against a fuzzy pattern, ignoring case. Every
+/// character of the pattern must appear in the candidate, in order.
+/// Matches at the start of words, after a `_` or on a camel hump, as well
+/// as consecutive matches score higher, so that `hc` ranks `handle_call`
+/// above `hack`. Returns `None` if the candidate does not match.
+pub fn fuzzy_score(pattern: &str, candidate: &str) -> Option<i64> {
+    let pattern: Vec<char> = pattern.chars().map(|c| c.to_ascii_lowercase()).collect();
+    let chars: Vec<char> = candidate.chars().collect();
+    let lower: Vec<char> = chars.iter().map(|c| c.to_ascii_lowercase()).collect();
+    if pattern.is_empty() {
+        return Some(0);
+    }
+    if !is_subsequence(&pattern, &lower) {
+        return None;
+    }
+
+    let mut score = 0;
+    let mut next = 0;
+    let mut prev: Option<usize> = None;
+    for (i, &c) in pattern.iter().enumerate() {
+        let rest = &pattern[i + 1..];
+        let valid = |j: usize| lower[j] == c && is_subsequence(rest, &lower[j + 1..]);
+        let pos = if prev.is_some() && next < lower.len() && valid(next) {
+            next
+        } else {
+            (next..lower.len())
+                .find(|&j| valid(j) && is_word_start(&chars, j))
+                .or_else(|| (next..lower.len()).find(|&j| valid(j)))?
+        };
+        score += 1;
+        if pos == 0 {
+            score += 10;
+        } else if is_word_start(&chars, pos) {
+            score += 8;
+        }
+        if prev.map(|prev| prev + 1) == Some(pos) {
+            score += 4;
+        }
+        prev = Some(pos);
+        next = pos + 1;
+    }
+
+    if lower == pattern {
+        score += 100;
+    } else if lower.starts_with(&pattern) {
+        score += 50;
+    }
+    // Prefer shorter candidates among otherwise equal matches
+    score -= (lower.len() - pattern.len()) as i64;
+    Some(score)
+}
+
+fn is_subsequence(pattern: &[char], candidate: &[char]) -> bool {
+    let mut candidate = candidate.iter();
+    pattern.iter().all(|c| candidate.any(|x| x == c))
+}
+
+fn is_word_start(chars: &[char], pos: usize) -> bool {
+    if pos == 0 {
+        return true;
+    }
+    let prev = chars[pos - 1];
+    let cur = chars[pos];
+    !prev.is_alphanumeric() || (prev.is_lowercase() && cur.is_uppercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use elp_base_db::fixture::WithFixture;
+    use elp_base_db::SourceDatabase;
+    use elp_base_db::SourceDatabaseExt;
+    use expect_test::expect;
+    use expect_test::Expect;
+
+    use super::*;
+    use crate::RootDatabase;
+
+    fn check(fixture: &str, query: &str, expect: Expect) {
+        let (db, files) = RootDatabase::with_many_files(fixture);
+        let project_id = db
+            .app_data(db.file_source_root(files[0]))
+            .unwrap()
+            .project_id;
+        let actual = search(&db, project_id, &Query::new(query))
+            .iter()
+            .map(|def| match def {
+                SymbolDefinition::Module(it) => format!("Module {}\n", it.name(&db)),
+                SymbolDefinition::Function(it) => format!("Function {}\n", it.function.name),
+                SymbolDefinition::Type(it) => format!("Type {}\n", it.name()),
+                SymbolDefinition::Record(it) => format!("Record {}\n", it.record.name),
+                SymbolDefinition::Define(it) => format!("Define {}\n", it.define.name),
+                SymbolDefinition::Callback(it) => format!("Callback {}\n", it.callback.name),
+                _ => format!("{def:?}\n"),
+            })
+            .collect::<String>();
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn fuzzy_ranking() {
+        assert_eq!(fuzzy_score("xyz", "handle_call"), None);
+        assert_eq!(fuzzy_score("", "handle_call"), Some(0));
+        assert!(fuzzy_score("hc", "handle_call") > fuzzy_score("hc", "hack_it"));
+        assert!(fuzzy_score("handle", "handle") > fuzzy_score("handle", "handle_call"));
+        assert!(fuzzy_score("hcall", "handle_call") > fuzzy_score("hcall", "hecall"));
+        assert!(fuzzy_score("mm", "MyMacro") > fuzzy_score("mm", "mymacro"));
+    }
+
+    #[test]
+    fn search_all_kinds() {
+        check(
+            r#"
+//- /include/main.hrl include_path:/include
+-record(handler_state, {a}).
+-define(HANDLER_TIMEOUT, 100).
+//- /src/main.erl
+-module(main).
+-include("main.hrl").
+-callback handle(term()) -> ok.
+-type handler() :: fun().
+handle_call(_, _, _) -> ok.
+hack() -> ok.
+//- /src/handler.erl
+-module(handler).
+"#,
+            "handle",
+            expect![[r#"
+                Callback handle/1
+                Module handler
+                Type handler/0
+                Function handle_call/3
+                Record handler_state
+                Define HANDLER_TIMEOUT
+            "#]],
+        );
+    }
+
+    #[test]
+    fn search_with_prefixes() {
+        let fixture = r#"
+//- /include/main.hrl include_path:/include
+-record(state, {a}).
+-define(STATE, state).
+//- /src/main.erl
+-module(main).
+-include("main.hrl").
+-record(other_state, {a}).
+-type state() :: #state{}.
+state() -> ok.
+//- /src/other.erl
+-module(other).
+state(_) -> ok.
+"#;
+        check(
+            fixture,
+            "#st",
+            expect![[r#"
+                Record state
+                Record other_state
+            "#]],
+        );
+        check(
+            fixture,
+            "?st",
+            expect![[r#"
+                Define STATE
+            "#]],
+        );
+        check(
+            fixture,
+            "main:st",
+            expect![[r#"
+                Function state/0
+                Type state/0
+            "#]],
+        );
+        check(
+            fixture,
+            "state/1",
+            expect![[r#"
+                Function state/1
+            "#]],
+        );
+    }
+
+    #[test]
+    fn symbols_are_updated_incrementally() {
+        let (mut db, files) = RootDatabase::with_many_files(
+            r#"
+//- /src/main.erl
+-module(main).
+foo() -> ok.
+//- /src/other.erl
+-module(other).
+bar() -> ok.
+"#,
+        );
+        let other = db.file_symbols(files[1]);
+        db.set_file_text(
+            files[0],
+            Arc::new("-module(main).\nbaz() -> ok.\n".to_string()),
+        );
+        let names: Vec<_> = db
+            .file_symbols(files[0])
+            .iter()
+            .map(|symbol| symbol.name.clone())
+            .collect();
+        assert_eq!(names, vec!["baz"]);
+        assert!(Arc::ptr_eq(&other, &db.file_symbols(files[1])));
+    }
+
+    #[test]
+    fn container_names() {
+        let (db, files) = RootDatabase::with_many_files(
+            r#"
+//- /include/main.hrl include_path:/include
+-record(handler_state, {a}).
+//- /src/main.erl
+-module(main).
+-include("main.hrl").
+handle_call(_, _, _) -> ok.
+"#,
+        );
+        let project_id = db
+            .app_data(db.file_source_root(files[1]))
+            .unwrap()
+            .project_id;
+        let actual = search(&db, project_id, &Query::new("hand"))
+            .iter()
+            .map(|def| format!("{:?}\n", container_name(&db, project_id, def)))
+            .collect::<String>();
+        expect![[r#"
+            Some("main")
+            Some("main.hrl")
+        "#]]
+        .assert_eq(&actual);
+    }
+}
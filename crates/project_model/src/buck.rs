@@ -12,6 +12,7 @@ extern crate serde_json;
 
 use std::borrow::Cow;
 use std::cmp::Ordering;
+use std::collections::BTreeMap;
 use std::convert::TryFrom;
 use std::ffi::OsStr;
 use std::fs;
@@ -32,6 +33,7 @@ use parking_lot::Mutex;
 use paths::AbsPath;
 use paths::AbsPathBuf;
 use paths::RelPath;
+use regex::Regex;
 use serde::Deserialize;
 use serde::Serialize;
 use tempfile::NamedTempFile;
@@ -58,18 +60,42 @@ pub const ELP_CONFIG_FILE: &str = ".elp.toml";
 //
 // [eqwalizer]
 // enable_all = true
+//
+// [diagnostics]
+// enabled = [ "unused_exported_function" ]
+// disabled = [ "W0011" ]
+// enable_experimental = false
+// exclude_apps = [ "third_party" ]
+// exclude_paths = [ "test/fixtures/**" ]
+//
+// [diagnostics.severity]
+// W0017 = "error"
 //```
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Ord, PartialOrd, Deserialize)]
 pub struct ElpConfig {
     #[serde(skip_deserializing)]
     config_path: Option<AbsPathBuf>,
+    #[serde(default)]
     pub buck: BuckConfig,
     #[serde(default)]
     pub eqwalizer: EqwalizerConfig,
+    #[serde(default)]
+    pub diagnostics: ProjectDiagnosticsConfig,
 }
 
 impl ElpConfig {
     pub fn try_parse(path: &AbsPath) -> Result<ElpConfig> {
+        let mut config = ElpConfig::parse_file(path)?;
+        if config.buck.enabled {
+            let path = config.config_path().to_path_buf();
+            BuckConfig::make_config(&path, &mut config)?;
+        }
+        Ok(config)
+    }
+
+    /// Read the `.elp.toml` file at or in the given path, without
+    /// resolving anything that requires running buck.
+    fn parse_file(path: &AbsPath) -> Result<ElpConfig> {
         let p = Path::new(ELP_CONFIG_FILE);
         let path = if !path.ends_with(RelPath::new_unchecked(p)) {
             path.join(p)
@@ -78,12 +104,28 @@ impl ElpConfig {
         };
         let config_content = fs::read_to_string(&path)?;
         let mut config: ElpConfig = toml::from_str(config_content.as_str())?;
-        BuckConfig::make_config(&path, &mut config)?;
         config.config_path = Some(path);
 
         Ok(config)
     }
 
+    /// The `[diagnostics]` section of the `.elp.toml` file in the
+    /// given directory, if any. Used for projects which are not
+    /// configured via `.elp.toml` themselves, such as rebar ones.
+    pub fn diagnostics_config_in(dir: &AbsPath) -> ProjectDiagnosticsConfig {
+        let path = dir.join(ELP_CONFIG_FILE);
+        if !path.exists() {
+            return ProjectDiagnosticsConfig::default();
+        }
+        match ElpConfig::parse_file(&path) {
+            Ok(config) => config.diagnostics,
+            Err(err) => {
+                log::warn!("Failed to load diagnostics config from {:?}\n{}", path, err);
+                ProjectDiagnosticsConfig::default()
+            }
+        }
+    }
+
     pub fn config_path(&self) -> &AbsPath {
         self.config_path.as_ref().unwrap()
     }
@@ -168,6 +210,79 @@ pub struct EqwalizerConfig {
     pub enable_all: bool,
 }
 
+/// Project-wide diagnostics policy, shared by the language server and
+/// `elp lint`. Codes can be given either as code (`W0017`) or label
+/// (`undefined_function`), and path globs are relative to the project
+/// root.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    Ord,
+    PartialOrd,
+    Deserialize,
+    Default
+)]
+#[serde(default)]
+pub struct ProjectDiagnosticsConfig {
+    pub enabled: Vec<String>,
+    pub disabled: Vec<String>,
+    pub enable_experimental: bool,
+    /// Severity overrides, from code to one of `error`, `warning`,
+    /// `info` or `hint`.
+    pub severity: BTreeMap<String, String>,
+    pub exclude_apps: Vec<String>,
+    pub exclude_paths: Vec<String>,
+}
+
+impl ProjectDiagnosticsConfig {
+    pub fn is_app_excluded(&self, app: &AppName) -> bool {
+        self.exclude_apps.iter().any(|name| name == app.as_str())
+    }
+
+    /// Whether the given path, relative to the project root, matches
+    /// one of the `exclude_paths` globs.
+    pub fn is_path_excluded(&self, path: &Path) -> bool {
+        let path = path.to_string_lossy();
+        self.exclude_paths
+            .iter()
+            .any(|glob| glob_to_regex(glob).map_or(false, |re| re.is_match(&path)))
+    }
+}
+
+/// Translate a path glob into an anchored regex. `**` matches across
+/// directories, `*` and `?` only within a single path component.
+fn glob_to_regex(glob: &str) -> Option<Regex> {
+    let mut re = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    re.push_str("(.*/)?");
+                } else {
+                    re.push_str(".*");
+                }
+            }
+            '*' => re.push_str("[^/]*"),
+            '?' => re.push_str("[^/]"),
+            c => re.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    re.push('$');
+    match Regex::new(&re) {
+        Ok(re) => Some(re),
+        Err(err) => {
+            log::warn!("Invalid exclude_paths glob {:?}: {}", glob, err);
+            None
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Default)]
 pub struct TargetInfo {
     pub targets: FxHashMap<TargetFullName, Target>,
@@ -858,3 +973,60 @@ impl From<ProjectAppDataAcc> for ProjectAppData {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_diagnostics_config() {
+        let config: ElpConfig = toml::from_str(
+            r#"
+[diagnostics]
+enabled = [ "unused_exported_function" ]
+disabled = [ "W0011" ]
+enable_experimental = true
+exclude_apps = [ "third_party" ]
+exclude_paths = [ "test/fixtures/**" ]
+
+[diagnostics.severity]
+W0017 = "error"
+"#,
+        )
+        .unwrap();
+        assert!(!config.buck.enabled);
+        assert_eq!(
+            config.diagnostics,
+            ProjectDiagnosticsConfig {
+                enabled: vec!["unused_exported_function".to_string()],
+                disabled: vec!["W0011".to_string()],
+                enable_experimental: true,
+                severity: BTreeMap::from([("W0017".to_string(), "error".to_string())]),
+                exclude_apps: vec!["third_party".to_string()],
+                exclude_paths: vec!["test/fixtures/**".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn diagnostics_config_exclusions() {
+        let config = ProjectDiagnosticsConfig {
+            exclude_apps: vec!["third_party".to_string()],
+            exclude_paths: vec![
+                "test/fixtures/**".to_string(),
+                "src/*_generated.erl".to_string(),
+                "**/scratch.erl".to_string(),
+            ],
+            ..Default::default()
+        };
+        assert!(config.is_app_excluded(&AppName("third_party".to_string())));
+        assert!(!config.is_app_excluded(&AppName("my_app".to_string())));
+
+        assert!(config.is_path_excluded(Path::new("test/fixtures/a/b.erl")));
+        assert!(config.is_path_excluded(Path::new("src/foo_generated.erl")));
+        assert!(config.is_path_excluded(Path::new("scratch.erl")));
+        assert!(config.is_path_excluded(Path::new("apps/a/src/scratch.erl")));
+        assert!(!config.is_path_excluded(Path::new("src/sub/foo_generated.erl")));
+        assert!(!config.is_path_excluded(Path::new("test/foo_SUITE.erl")));
+    }
+}
@@ -25,6 +25,7 @@ use anyhow::bail;
 use anyhow::Context;
 use anyhow::Result;
 use buck::EqwalizerConfig;
+use buck::ProjectDiagnosticsConfig;
 use elp_log::timeit;
 use lazy_static::lazy_static;
 use parking_lot::MutexGuard;
@@ -325,6 +326,16 @@ impl Project {
             ProjectBuildData::Rebar(_) => EqwalizerConfig::default(),
         }
     }
+
+    /// The `[diagnostics]` section of the project `.elp.toml`. For rebar
+    /// projects this is read from the project root.
+    pub fn diagnostics_config(&self) -> ProjectDiagnosticsConfig {
+        match &self.project_build_data {
+            ProjectBuildData::Buck(buck) => buck.config.diagnostics.clone(),
+            ProjectBuildData::Otp => ProjectDiagnosticsConfig::default(),
+            ProjectBuildData::Rebar(rebar) => buck::ElpConfig::diagnostics_config_in(&rebar.root),
+        }
+    }
 }
 
 impl fmt::Debug for Project {
@@ -8,8 +8,9 @@
  */
 
 // Based on the Microsoft template code at https://github.com/Microsoft/vscode-extension-samples
-import { workspace, ExtensionContext } from 'vscode';
+import { commands, workspace, ExtensionContext } from 'vscode';
 
+import * as lsp from 'vscode-languageclient/node';
 import {
 	LanguageClient,
 	LanguageClientOptions,
@@ -48,6 +49,23 @@ export function activate(context: ExtensionContext) {
 		clientOptions
 	);
 
+	// The `References` and `Implementations` lenses carry the locations
+	// computed by the server, shown with the built-in references peek
+	context.subscriptions.push(
+		commands.registerCommand(
+			'elp.showReferences',
+			(uri: string, position: lsp.Position, locations: lsp.Location[]) => {
+				const converter = client.protocol2CodeConverter;
+				return commands.executeCommand(
+					'editor.action.showReferences',
+					converter.asUri(uri),
+					converter.asPosition(position),
+					locations.map(converter.asLocation)
+				);
+			}
+		)
+	);
+
 	// Start the client. This will also launch the server
 	client.start();
 }